@@ -11,7 +11,7 @@ use tracing::info;
 use crate::{
     brokers::state_broker::StateBroker,
     config::Config,
-    models::error::Error,
+    models::{cache::CacheStore, error::Error},
     proto::action_service_server::ActionServiceServer,
     server::ActionsLauncher,
     services::{
@@ -77,7 +77,11 @@ impl App {
         docker.ping().await.map_err(Error::DockerConnectionError)?;
 
         let state_broker = Arc::new(StateBroker::new());
-        let action_service = ActionService::new(docker, state_broker.clone());
+        let cache_store = Arc::new(CacheStore::new(
+            config.cache_dir.clone(),
+            config.cache_budget * 1024 * 1024,
+        ));
+        let action_service = ActionService::new(docker, state_broker.clone(), cache_store);
         let actions = ActionsLauncher { action_service };
         let action_service_grpc = ActionServiceServer::new(actions);
 
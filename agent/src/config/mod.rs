@@ -16,14 +16,23 @@ pub struct Config {
     /// The port of the agent to listen on
     #[clap(long, default_value = "9001")]
     pub port: u32,
+
+    /// The directory where the caches of the actions are kept
+    #[clap(long, default_value = "/tmp/sealci/cache")]
+    pub cache_dir: String,
+
+    /// The disk budget of the caches, in megabytes, past which the least
+    /// recently used ones are removed
+    #[clap(long, default_value = "10240")]
+    pub cache_budget: u64,
 }
 
 impl Display for Config {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(
             f,
-            "--shost {}, --ahost {}, --port {}",
-            self.shost, self.ahost, self.port
+            "--shost {}, --ahost {}, --port {}, --cache-dir {}, --cache-budget {}",
+            self.shost, self.ahost, self.port, self.cache_dir, self.cache_budget
         )
     }
 }
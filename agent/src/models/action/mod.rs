@@ -1,14 +1,31 @@
-use super::{container::ContainerOperations, error::Error::ExecError, step::Step};
 use super::{
-    error::Error::{self, StepOutputError},
-    output_pipe::OutputPipe,
+    cache::{parse_key, Cache, CacheStore, KeyPart},
+    checkout::Checkout,
+    container::ContainerOperations,
+    error::Error::ExecError,
+    step::{Step, StepDefinition},
+};
+use super::{
+    error::Error::{
+        self, ActionTimeoutError, CacheError, CheckoutError, ContainerExecError, StepOutputError,
+    },
+    output_pipe::{unix_millis, OutputPipe},
 };
 use crate::brokers::state_broker::{StateBroker, StateEvent};
 use crate::brokers::Broker;
-use crate::{models::output_pipe::Pipe, proto::ActionResponseStream};
+use crate::{
+    models::output_pipe::Pipe,
+    proto::{ActionResponseStream, ActionStatus, LogStream, StepFinished, StepStarted},
+};
+use bollard::container::LogOutput;
 use state::State;
-use std::sync::Arc;
-use tokio::{sync::mpsc::UnboundedSender, task};
+use std::{future::Future, sync::Arc, time::Duration};
+use tokio::{
+    io::AsyncWriteExt,
+    sync::mpsc::UnboundedSender,
+    task::{self, JoinHandle},
+    time::{timeout, Instant},
+};
 use tokio_stream::StreamExt;
 use tonic::Status;
 use tracing::{debug, error};
@@ -21,24 +38,39 @@ pub struct Action<T: ContainerOperations> {
     steps: Vec<Step<T>>,
     pipe: Arc<OutputPipe>,
     pub repository_url: String,
+    /// Revision of the repository checked out before the steps run
+    pub checkout: Checkout,
     pub state: State,
     pub state_broker: Arc<StateBroker>,
+    /// Maximum time the action may run for once its container is started,
+    /// checkout and archives included, unbounded when not set
+    pub timeout: Option<Duration>,
+    /// Time the action was created at, right after its container started
+    started_at: Instant,
+    /// Paths archived and sent back once the steps have run
+    pub artifacts: Vec<String>,
+    /// Files restored before the steps run and saved once they succeed
+    pub cache: Option<Cache>,
+    cache_store: Option<Arc<CacheStore>>,
 }
 
 impl<T: ContainerOperations> Action<T> {
     pub fn new(
         id: u32,
         container: T,
-        commands: Vec<String>,
+        commands: Vec<impl Into<StepDefinition>>,
         stdout: UnboundedSender<Result<ActionResponseStream, Status>>,
         repository_url: String,
         state_broker: Arc<StateBroker>,
     ) -> Self {
         let pipe = Arc::new(OutputPipe::new(id, stdout));
         let container = Arc::new(container);
+        // Steps run in the folder the repository is cloned in, named after
+        // the action
+        let repository = format!("/{}", id);
         let steps: Vec<Step<T>> = commands
-            .iter()
-            .map(|c| Step::new(c.into(), Some(format!("/{}", id)), container.clone()))
+            .into_iter()
+            .map(|c| Step::from_definition(c.into(), &repository, container.clone()))
             .collect();
         let state = State::InProgress;
         Self {
@@ -46,28 +78,147 @@ impl<T: ContainerOperations> Action<T> {
             container,
             steps,
             repository_url,
+            checkout: Checkout::default(),
             pipe,
             state,
             state_broker,
+            timeout: None,
+            started_at: Instant::now(),
+            artifacts: Vec::new(),
+            cache: None,
+            cache_store: None,
         }
     }
 
+    pub fn with_checkout(mut self, checkout: Checkout) -> Self {
+        self.checkout = checkout;
+        self
+    }
+
+    pub fn with_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn with_artifacts(mut self, artifacts: Vec<String>) -> Self {
+        self.artifacts = artifacts;
+        self
+    }
+
+    pub fn with_cache(mut self, cache: Option<Cache>, cache_store: Arc<CacheStore>) -> Self {
+        self.cache = cache;
+        self.cache_store = Some(cache_store);
+        self
+    }
+
     pub async fn execute(&mut self) -> Result<(), Error> {
-        for step in &self.steps {
+        match self.within_timeout(self.run()).await {
+            Err(ActionTimeoutError(duration)) => {
+                error!("Action {} timed out after {:?}", self.id, duration);
+                self.set_state(State::Failed);
+                self.pipe.output_log(
+                    format!("Action timed out after {}s", duration.as_secs()),
+                    ActionStatus::TimedOut as i32,
+                    None,
+                );
+                // Removing the container kills the exec that overran the deadline
+                self.release().await;
+                Err(ActionTimeoutError(duration))
+            }
+            // The action ran to its end: its exit code tells the scheduler it failed
+            Err(StepOutputError(exit_code)) => {
+                self.set_state(State::Failed);
+                self.pipe.output_log(
+                    "Action failed".to_string(),
+                    ActionStatus::Completed as i32,
+                    Some(exit_code),
+                );
+                self.release().await;
+                Err(StepOutputError(exit_code))
+            }
+            // Without a result, the scheduler reports the action as lost
+            Err(e) => {
+                error!("Action {} failed: {}", self.id, e);
+                self.set_state(State::Failed);
+                self.release().await;
+                Err(e)
+            }
+            Ok(()) => {
+                self.set_state(State::Completed);
+                self.pipe.output_log(
+                    "Action completed".to_string(),
+                    ActionStatus::Completed as i32,
+                    Some(0),
+                );
+                self.release().await;
+                Ok(())
+            }
+        }
+    }
+
+    /// Runs the action in its container: restores the cache, runs the steps,
+    /// then saves the cache and collects the artifacts. The artifacts of a
+    /// failed step are collected as well.
+    async fn run(&self) -> Result<(), Error> {
+        let cache_miss = self.restore_cache().await;
+        if let Err(e) = self.run_steps().await {
+            if let StepOutputError(_) = e {
+                self.collect_artifacts().await;
+            }
+            return Err(e);
+        }
+        if let Some(key) = cache_miss {
+            self.save_cache(&key).await;
+        }
+        self.collect_artifacts().await;
+        Ok(())
+    }
+
+    /// Bounds `future` by what remains of the timeout of the action, counted
+    /// from the start of its container.
+    async fn within_timeout<R>(
+        &self,
+        future: impl Future<Output = Result<R, Error>>,
+    ) -> Result<R, Error> {
+        let Some(duration) = self.timeout else {
+            return future.await;
+        };
+        let remaining = duration.saturating_sub(self.started_at.elapsed());
+        timeout(remaining, future)
+            .await
+            .unwrap_or(Err(ActionTimeoutError(duration)))
+    }
+
+    /// Runs the steps one after the other, stopping at the first one exiting
+    /// with a non-zero code unless it may fail.
+    async fn run_steps(&self) -> Result<(), Error> {
+        for (index, step) in self.steps.iter().enumerate() {
+            let index = index as u32;
+            let name = step.name.clone().unwrap_or(step.command.clone());
+            let started_at = unix_millis();
+            self.pipe.output_step_started(StepStarted {
+                index,
+                name: name.clone(),
+                started_at,
+            });
             // Execute the step in the folder where we cloned the repository
             // When cloning we use the action id as a name for the folder
             let mut exec_result = step.execute().await?;
-            let command = step.command.clone();
-            debug!("Executing command {} for action {}", command, self.id);
-            self.pipe.clone().output_log(command, 2, None);
+            debug!("Executing command {} for action {}", step.command, self.id);
+            self.pipe
+                .output_step_log(name.clone(), LogStream::System, index);
             let pipe = self.pipe.clone();
             let id = self.id.clone();
-            task::spawn(async move {
+            let forwarding = task::spawn(async move {
                 while let Some(log) = exec_result.output.next().await {
                     match log {
                         Ok(log_output) => {
                             debug!("Command output: {} ", log_output);
-                            pipe.output_log(log_output.to_string(), 2, None);
+                            let stream = match log_output {
+                                LogOutput::StdErr { .. } => LogStream::Stderr,
+                                _ => LogStream::Stdout,
+                            };
+                            pipe.output_step_log(log_output.to_string(), stream, index);
                         }
                         Err(e) => {
                             error!("Action {} failed: {}", id, e);
@@ -77,34 +228,232 @@ impl<T: ContainerOperations> Action<T> {
                 }
                 Ok(())
             });
-            let exit_status = exec_result.exec_handle.await;
-            if let Ok(exit_code) = exit_status {
-                if exit_code != 0 {
-                    self.cleanup().await?;
-                    self.set_state(State::Completed);
-                    self.pipe
-                        .output_log("Action failed".to_string(), 3, Some(exit_code));
-                    return Err(StepOutputError(exit_code));
-                }
+            let exit_code = exec_result.exec_handle.await.map_err(ExecError)?;
+            // The last lines of the step are forwarded before it is reported finished
+            if let Err(e) = forwarding.await {
+                error!("Failed to forward the output of action {}: {}", self.id, e);
+            }
+            self.pipe.output_step_finished(StepFinished {
+                index,
+                name,
+                exit_code,
+                started_at,
+                finished_at: unix_millis(),
+            });
+            if exit_code != 0 && step.continue_on_error {
+                self.pipe.output_step_log(
+                    format!("Step failed with exit code {}, continuing", exit_code),
+                    LogStream::System,
+                    index,
+                );
+            } else if exit_code != 0 {
+                return Err(StepOutputError(exit_code));
             }
         }
-        self.cleanup().await?;
-        self.set_state(State::Completed);
         Ok(())
     }
 
-    pub async fn setup_repository(&self) -> Result<(), Error> {
-        // Cloning the repository in a folder that takes as name the id of the action
-        let setup_command = format!("git clone --depth 1 {} {}", self.repository_url, self.id);
-        let exec_result = self.container.exec(setup_command, None).await?;
-        exec_result.exec_handle.await.map_err(ExecError)?;
+    /// Sends the archive of the artifact paths through the pipe. Failing to
+    /// collect them is reported in the logs but does not fail the action.
+    async fn collect_artifacts(&self) {
+        if self.artifacts.is_empty() {
+            return;
+        }
+        if let Err(e) = self.archive_artifacts().await {
+            error!("Failed to collect artifacts of action {}: {}", self.id, e);
+            self.pipe
+                .output_log(format!("Failed to collect artifacts: {}", e), 2, None);
+        }
+    }
+
+    async fn archive_artifacts(&self) -> Result<(), Error> {
+        let mut exec_result = self
+            .container
+            .archive(self.artifacts.clone(), false, self.workdir())
+            .await?;
+        while let Some(output) = exec_result.output.next().await {
+            match output.map_err(ContainerExecError)? {
+                LogOutput::StdOut { message } => self.pipe.output_artifact(message.to_vec()),
+                log_output => self.pipe.output_log(log_output.to_string(), 2, None),
+            }
+        }
+        // The controller only keeps an archive the agent reported complete
+        wait_success(exec_result.exec_handle).await?;
+        self.pipe.output_artifact_complete();
         Ok(())
     }
 
+    /// Restores the archive saved under the cache key, if any. Returns the
+    /// key to save the cache under once the steps succeed when there was no
+    /// archive to restore. Failures are reported in the logs.
+    async fn restore_cache(&self) -> Option<String> {
+        let (cache, cache_store) = (self.cache.as_ref()?, self.cache_store.as_ref()?);
+        let key = match self.resolve_cache_key(&cache.key).await {
+            Ok(key) => key,
+            Err(e) => {
+                error!("Failed to resolve cache key of action {}: {}", self.id, e);
+                self.pipe
+                    .output_log(format!("Failed to resolve cache key: {}", e), 2, None);
+                return None;
+            }
+        };
+        let Some(archive) = cache_store.lookup(&self.repository_url, &key).await else {
+            self.pipe
+                .output_log(format!("No cache found for key {}", key), 2, None);
+            return Some(key);
+        };
+        let restored = async {
+            let mut exec_result = self.container.extract(archive, self.workdir()).await?;
+            while let Some(output) = exec_result.output.next().await {
+                let log_output = output.map_err(ContainerExecError)?;
+                self.pipe.output_log(log_output.to_string(), 2, None);
+            }
+            wait_success(exec_result.exec_handle).await
+        };
+        match restored.await {
+            Ok(()) => self
+                .pipe
+                .output_log(format!("Cache restored from key {}", key), 2, None),
+            Err(e) => {
+                error!("Failed to restore cache of action {}: {}", self.id, e);
+                self.pipe
+                    .output_log(format!("Failed to restore cache: {}", e), 2, None);
+            }
+        }
+        None
+    }
+
+    /// Replaces the `hashFiles` expressions of a cache key with the hash of
+    /// the files of the repository they match.
+    async fn resolve_cache_key(&self, key: &str) -> Result<String, Error> {
+        let mut resolved = String::new();
+        for part in parse_key(key) {
+            match part {
+                KeyPart::Literal(literal) => resolved.push_str(&literal),
+                KeyPart::HashFiles(patterns) => {
+                    let mut exec_result =
+                        self.container.hash_files(patterns, self.workdir()).await?;
+                    let mut hash = Vec::new();
+                    while let Some(output) = exec_result.output.next().await {
+                        match output.map_err(ContainerExecError)? {
+                            LogOutput::StdOut { message } => hash.extend_from_slice(&message),
+                            log_output => self.pipe.output_log(log_output.to_string(), 2, None),
+                        }
+                    }
+                    wait_success(exec_result.exec_handle).await?;
+                    resolved.push_str(String::from_utf8_lossy(&hash).trim());
+                }
+            }
+        }
+        Ok(resolved)
+    }
+
+    /// Saves the cache paths under `key`. Failing to do so is reported in
+    /// the logs but does not fail the action.
+    async fn save_cache(&self, key: &str) {
+        let (Some(cache), Some(cache_store)) = (&self.cache, &self.cache_store) else {
+            return;
+        };
+        match self.archive_cache(cache, cache_store, key).await {
+            Ok(()) => self
+                .pipe
+                .output_log(format!("Cache saved with key {}", key), 2, None),
+            Err(e) => {
+                error!("Failed to save cache of action {}: {}", self.id, e);
+                self.pipe
+                    .output_log(format!("Failed to save cache: {}", e), 2, None);
+            }
+        }
+    }
+
+    async fn archive_cache(
+        &self,
+        cache: &Cache,
+        cache_store: &CacheStore,
+        key: &str,
+    ) -> Result<(), Error> {
+        let (temporary, mut file) = cache_store
+            .create(&self.repository_url, key)
+            .await
+            .map_err(CacheError)?;
+        let archived = async {
+            // Absolute paths, such as the home of the container user, are kept as such
+            let mut exec_result = self
+                .container
+                .archive(cache.paths.clone(), true, self.workdir())
+                .await?;
+            while let Some(output) = exec_result.output.next().await {
+                match output.map_err(ContainerExecError)? {
+                    LogOutput::StdOut { message } => {
+                        file.write_all(&message).await.map_err(CacheError)?
+                    }
+                    log_output => self.pipe.output_log(log_output.to_string(), 2, None),
+                }
+            }
+            file.flush().await.map_err(CacheError)?;
+            wait_success(exec_result.exec_handle).await
+        };
+        match archived.await {
+            Ok(()) => cache_store
+                .commit(&self.repository_url, key, &temporary)
+                .await
+                .map_err(CacheError),
+            Err(e) => {
+                let _ = tokio::fs::remove_file(&temporary).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// The directory the repository is cloned in, named after the action
+    fn workdir(&self) -> Option<String> {
+        Some(format!("/{}", self.id))
+    }
+
+    pub async fn setup_repository(&self) -> Result<(), Error> {
+        // Checking the repository out in a folder that takes as name the id of
+        // the action, the action fails when the revision cannot be fetched
+        let setup_command = self
+            .checkout
+            .command(&self.repository_url, &self.id.to_string());
+        self.within_timeout(async {
+            let exec_result = self
+                .container
+                .exec(setup_command, None, self.checkout.env())
+                .await?;
+            match wait_success(exec_result.exec_handle).await {
+                Err(StepOutputError(exit_code)) => Err(CheckoutError(exit_code)),
+                result => result,
+            }
+        })
+        .await
+    }
+
     pub async fn cleanup(&self) -> Result<(), Error> {
         self.container.remove().await
     }
 
+    /// Removes the containers of an action that has sent its result. The
+    /// result stands whether or not they are removed.
+    async fn release(&self) {
+        if let Err(e) = self.cleanup().await {
+            error!("Failed to clean up action {}: {}", self.id, e);
+        }
+    }
+
+    /// Stops the action on request, removing the container kills the exec
+    /// still running.
+    pub async fn cancel(&mut self) -> Result<(), Error> {
+        let removed = self.cleanup().await;
+        self.set_state(State::Failed);
+        self.pipe.output_log(
+            "Action cancelled".to_string(),
+            ActionStatus::Cancelled as i32,
+            None,
+        );
+        removed
+    }
+
     fn set_state(&mut self, state: State) {
         self.state = state.clone();
         let _ = self.state_broker.state_channel.send_event(StateEvent {
@@ -114,10 +463,19 @@ impl<T: ContainerOperations> Action<T> {
     }
 }
 
+/// Waits for an exec the action depends on, failing on a non-zero exit code.
+async fn wait_success(exec_handle: JoinHandle<i32>) -> Result<(), Error> {
+    match exec_handle.await.map_err(ExecError)? {
+        0 => Ok(()),
+        exit_code => Err(StepOutputError(exit_code)),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::models::container::mock::MockContainer;
+    use std::collections::HashMap;
     use std::sync::{Arc, Mutex};
     use tokio::sync::mpsc;
 
@@ -153,7 +511,7 @@ mod tests {
         assert_eq!(calls.len(), 1);
 
         // Verify the git clone command has correct format
-        let expected_cmd = format!("git clone --depth 1 {} {}", repo_url, action_id);
+        let expected_cmd = format!("git clone --depth 1 -- {} {}", repo_url, action_id);
         assert_eq!(calls[0].0, expected_cmd);
         assert_eq!(calls[0].1, None); // No working directory for clone
     }
@@ -203,6 +561,437 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_action_execute_reports_completion() {
+        // Arrange
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mock_container = MockContainer {
+            exec_calls: Mutex::new(Vec::new()),
+            should_fail: false,
+        };
+
+        let mut action = Action::new(
+            7,
+            mock_container,
+            vec!["echo 'done'".to_string()],
+            tx,
+            "https://example.com/repo.git".to_string(),
+            Arc::new(StateBroker::new()),
+        );
+
+        // Act
+        let result = action.execute().await;
+
+        // Assert
+        assert!(result.is_ok());
+        let mut last = None;
+        while let Ok(Ok(message)) = rx.try_recv() {
+            last = Some(message);
+        }
+        let last = last.expect("Action should have sent messages");
+        assert_eq!(last.action_id, 7);
+        let result = last.result.unwrap();
+        assert_eq!(result.completion, 3);
+        assert_eq!(result.exit_code, Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_action_execute_sends_artifacts_before_result() {
+        // Arrange
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mock_container = MockContainer {
+            exec_calls: Mutex::new(Vec::new()),
+            should_fail: false,
+        };
+
+        let mut action = Action::new(
+            5,
+            mock_container,
+            vec!["cargo build".to_string()],
+            tx,
+            "https://example.com/repo.git".to_string(),
+            Arc::new(StateBroker::new()),
+        )
+        .with_artifacts(vec!["target/release/app".to_string(), "*.log".to_string()]);
+
+        // Act
+        let result = action.execute().await;
+
+        // Assert
+        assert!(result.is_ok());
+        let calls = action.container.exec_calls.lock().unwrap();
+        assert_eq!(calls[1].0, "tar -cf - target/release/app *.log");
+        assert_eq!(calls[1].1, Some("/5".to_string()));
+        let mut messages = Vec::new();
+        while let Ok(Ok(message)) = rx.try_recv() {
+            messages.push(message);
+        }
+        let artifact = messages
+            .iter()
+            .position(|m| m.artifact.is_some())
+            .expect("Artifacts should have been sent");
+        assert_eq!(
+            messages[artifact].artifact.as_ref().unwrap().data,
+            b"archive"
+        );
+        assert!(!messages[artifact].artifact.as_ref().unwrap().complete);
+        assert!(messages[artifact].result.is_none());
+        assert_eq!(artifact, messages.len() - 3);
+        // The archive is reported complete once the archive exec succeeded
+        let complete = messages[messages.len() - 2].artifact.as_ref().unwrap();
+        assert!(complete.complete && complete.data.is_empty());
+        assert_eq!(
+            messages.last().unwrap().result.as_ref().unwrap().completion,
+            3
+        );
+    }
+
+    #[tokio::test]
+    async fn test_action_execute_restores_and_saves_cache() {
+        let dir = std::env::temp_dir().join(format!("sealci-cache-{}", rand::random::<u64>()));
+        let cache_store = Arc::new(CacheStore::new(&dir, 1024));
+        let cache = Cache {
+            key: "cargo-${{ hashFiles('Cargo.lock') }}".to_string(),
+            paths: vec!["~/.cargo/registry".to_string(), "target".to_string()],
+        };
+        let run = |id: u32| {
+            let (tx, _rx) = mpsc::unbounded_channel();
+            let mock_container = MockContainer {
+                exec_calls: Mutex::new(Vec::new()),
+                should_fail: false,
+            };
+            Action::new(
+                id,
+                mock_container,
+                vec!["cargo build".to_string()],
+                tx,
+                "https://example.com/repo.git".to_string(),
+                Arc::new(StateBroker::new()),
+            )
+            .with_cache(Some(cache.clone()), cache_store.clone())
+        };
+
+        // The first run finds no cache and saves one once its steps succeed
+        let mut first = run(1);
+        assert!(first.execute().await.is_ok());
+        let calls: Vec<String> = first
+            .container
+            .exec_calls
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|(command, _)| command.clone())
+            .collect();
+        assert_eq!(
+            calls,
+            vec![
+                "sha256sum Cargo.lock",
+                "cargo build",
+                "tar -cPf - ~/.cargo/registry target"
+            ]
+        );
+        let archive = cache_store
+            .lookup("https://example.com/repo.git", "cargo-0123abcd")
+            .await
+            .unwrap();
+        assert_eq!(std::fs::read(&archive).unwrap(), b"archive");
+
+        // The next one restores it before its steps
+        let mut second = run(2);
+        assert!(second.execute().await.is_ok());
+        let calls = second.container.exec_calls.lock().unwrap();
+        assert_eq!(calls.len(), 3);
+        assert_eq!(calls[1].0, format!("tar -xPf {}", archive.display()));
+        assert_eq!(calls[1].1, Some("/2".to_string()));
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    /// A container whose commands never return, to exercise timeouts
+    struct HangingContainer {
+        removed: std::sync::atomic::AtomicBool,
+    }
+
+    impl ContainerOperations for HangingContainer {
+        async fn start(&self) -> Result<(), Error> {
+            Ok(())
+        }
+
+        async fn exec(
+            &self,
+            _command: String,
+            _workdir: Option<String>,
+            _env: HashMap<String, String>,
+        ) -> Result<crate::models::container::exec_handle::ExecResult, Error> {
+            Ok(crate::models::container::exec_handle::ExecResult {
+                output: Box::pin(futures_util::stream::pending()),
+                exec_handle: tokio::task::spawn(std::future::pending()),
+            })
+        }
+
+        async fn archive(
+            &self,
+            paths: Vec<String>,
+            _absolute_names: bool,
+            workdir: Option<String>,
+        ) -> Result<crate::models::container::exec_handle::ExecResult, Error> {
+            self.exec(paths.join(" "), workdir, HashMap::new()).await
+        }
+
+        async fn extract(
+            &self,
+            archive: std::path::PathBuf,
+            workdir: Option<String>,
+        ) -> Result<crate::models::container::exec_handle::ExecResult, Error> {
+            self.exec(archive.display().to_string(), workdir, HashMap::new())
+                .await
+        }
+
+        async fn hash_files(
+            &self,
+            patterns: Vec<String>,
+            workdir: Option<String>,
+        ) -> Result<crate::models::container::exec_handle::ExecResult, Error> {
+            self.exec(patterns.join(" "), workdir, HashMap::new()).await
+        }
+
+        async fn remove(&self) -> Result<(), Error> {
+            self.removed
+                .store(true, std::sync::atomic::Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_action_execute_times_out() {
+        // Arrange
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let container = HangingContainer {
+            removed: std::sync::atomic::AtomicBool::new(false),
+        };
+
+        let mut action = Action::new(
+            3,
+            container,
+            vec!["sleep infinity".to_string()],
+            tx,
+            "https://example.com/repo.git".to_string(),
+            Arc::new(StateBroker::new()),
+        )
+        .with_timeout(Some(Duration::from_millis(50)));
+
+        // Act
+        let result = action.execute().await;
+
+        // Assert
+        assert!(matches!(result, Err(Error::ActionTimeoutError(_))));
+        assert!(action
+            .container
+            .removed
+            .load(std::sync::atomic::Ordering::SeqCst));
+        let mut last = None;
+        while let Ok(Ok(message)) = rx.try_recv() {
+            last = Some(message);
+        }
+        let result = last.unwrap().result.unwrap();
+        assert_eq!(result.completion, ActionStatus::TimedOut as i32);
+        assert_eq!(result.exit_code, None);
+    }
+
+    #[tokio::test]
+    async fn test_action_cache_restore_counts_towards_timeout() {
+        // Arrange
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let container = HangingContainer {
+            removed: std::sync::atomic::AtomicBool::new(false),
+        };
+        let dir = std::env::temp_dir().join(format!("sealci-cache-{}", rand::random::<u64>()));
+        let cache = Cache {
+            key: "cargo-${{ hashFiles('Cargo.lock') }}".to_string(),
+            paths: vec!["target".to_string()],
+        };
+
+        let mut action = Action::new(
+            4,
+            container,
+            vec!["true".to_string()],
+            tx,
+            "https://example.com/repo.git".to_string(),
+            Arc::new(StateBroker::new()),
+        )
+        .with_timeout(Some(Duration::from_millis(50)))
+        .with_cache(Some(cache), Arc::new(CacheStore::new(&dir, 1024)));
+
+        // Act
+        let result = action.execute().await;
+
+        // Assert
+        assert!(matches!(result, Err(Error::ActionTimeoutError(_))));
+        let mut last = None;
+        while let Ok(Ok(message)) = rx.try_recv() {
+            last = Some(message);
+        }
+        let result = last.unwrap().result.unwrap();
+        assert_eq!(result.completion, ActionStatus::TimedOut as i32);
+    }
+
+    #[tokio::test]
+    async fn test_action_setup_repository_times_out() {
+        // Arrange
+        let (tx, _rx) = mpsc::unbounded_channel();
+        let container = HangingContainer {
+            removed: std::sync::atomic::AtomicBool::new(false),
+        };
+
+        let action = Action::new(
+            5,
+            container,
+            vec!["true".to_string()],
+            tx,
+            "https://example.com/repo.git".to_string(),
+            Arc::new(StateBroker::new()),
+        )
+        .with_timeout(Some(Duration::from_millis(50)));
+
+        // Act
+        let result = action.setup_repository().await;
+
+        // Assert
+        assert!(matches!(result, Err(Error::ActionTimeoutError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_action_execute_keeps_stderr_apart() {
+        // Arrange
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mock_container = MockContainer {
+            exec_calls: Mutex::new(Vec::new()),
+            should_fail: false,
+        };
+
+        let mut action = Action::new(
+            6,
+            mock_container,
+            vec!["echo warning >&2".to_string()],
+            tx,
+            "https://example.com/repo.git".to_string(),
+            Arc::new(StateBroker::new()),
+        );
+
+        // Act
+        let result = action.execute().await;
+
+        // Assert
+        assert!(result.is_ok());
+        let mut messages = Vec::new();
+        while let Ok(Ok(message)) = rx.try_recv() {
+            messages.push(message);
+        }
+        let line = messages
+            .iter()
+            .position(|message| {
+                message.log == "warning\n" && message.stream == LogStream::Stderr as i32
+            })
+            .unwrap();
+        // The output of the step is sent before the step is reported finished
+        let finished = messages
+            .iter()
+            .position(|message| message.step_finished.is_some())
+            .unwrap();
+        assert!(line < finished);
+    }
+
+    #[tokio::test]
+    async fn test_action_execute_continues_after_allowed_failure() {
+        // Arrange
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mock_container = MockContainer {
+            exec_calls: Mutex::new(Vec::new()),
+            should_fail: false,
+        };
+        let lint = StepDefinition {
+            run: "exit 1".to_string(),
+            name: Some("Lint".to_string()),
+            continue_on_error: true,
+            ..Default::default()
+        };
+
+        let mut action = Action::new(
+            5,
+            mock_container,
+            vec![lint, StepDefinition::from("exit 2".to_string())],
+            tx,
+            "https://example.com/repo.git".to_string(),
+            Arc::new(StateBroker::new()),
+        );
+
+        // Act
+        let result = action.execute().await;
+
+        // Assert
+        assert!(matches!(result, Err(Error::StepOutputError(2))));
+        assert_eq!(action.container.exec_calls.lock().unwrap().len(), 2);
+        let (mut logs, mut finished) = (Vec::new(), Vec::new());
+        while let Ok(Ok(message)) = rx.try_recv() {
+            match message.step_finished {
+                Some(step) => finished.push((step.index, step.name, step.exit_code)),
+                None if message.result.is_some() => logs.push(message.log),
+                None => {}
+            }
+        }
+        assert_eq!(
+            finished,
+            vec![(0, "Lint".to_string(), 1), (1, "exit 2".to_string(), 2)]
+        );
+        assert_eq!(logs[0], "Lint");
+        assert!(logs.contains(&"Step failed with exit code 1, continuing".to_string()));
+        assert!(logs.contains(&"exit 2".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_action_cancel_removes_container() {
+        // Arrange
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let container = HangingContainer {
+            removed: std::sync::atomic::AtomicBool::new(false),
+        };
+
+        let mut action = Action::new(
+            4,
+            container,
+            vec!["sleep infinity".to_string()],
+            tx,
+            "https://example.com/repo.git".to_string(),
+            Arc::new(StateBroker::new()),
+        );
+
+        // Act
+        let cancelled = {
+            let execution = action.execute();
+            tokio::pin!(execution);
+            tokio::select! {
+                _ = &mut execution => false,
+                _ = tokio::time::sleep(Duration::from_millis(50)) => true,
+            }
+        };
+        assert!(cancelled);
+        let result = action.cancel().await;
+
+        // Assert
+        assert!(result.is_ok());
+        assert!(action
+            .container
+            .removed
+            .load(std::sync::atomic::Ordering::SeqCst));
+        let mut last = None;
+        while let Ok(Ok(message)) = rx.try_recv() {
+            last = Some(message);
+        }
+        let result = last.unwrap().result.unwrap();
+        assert_eq!(result.completion, ActionStatus::Cancelled as i32);
+        assert_eq!(result.exit_code, None);
+    }
+
     #[tokio::test]
     async fn test_action_execute_handles_step_failure() {
         // Arrange - Setup a mock that will fail on execution
@@ -230,6 +1019,122 @@ mod tests {
             Err(Error::ContainerExecError(_)) => (), // Expected error
             _ => panic!("Unexpected result"),
         }
+        assert!(matches!(action.state, State::Failed));
+    }
+
+    #[tokio::test]
+    async fn test_action_with_failing_step_is_failed() {
+        // Arrange
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let mock_container = MockContainer {
+            exec_calls: Mutex::new(Vec::new()),
+            should_fail: false,
+        };
+
+        let mut action = Action::new(
+            3,
+            mock_container,
+            vec!["exit 2".to_string()],
+            tx,
+            "https://example.com/repo.git".to_string(),
+            Arc::new(StateBroker::new()),
+        );
+
+        // Act
+        let result = action.execute().await;
+
+        // Assert
+        assert!(matches!(result, Err(StepOutputError(2))));
+        assert!(matches!(action.state, State::Failed));
+        let mut last = None;
+        while let Ok(Ok(message)) = rx.try_recv() {
+            last = Some(message);
+        }
+        let result = last.unwrap().result.unwrap();
+        assert_eq!(result.completion, ActionStatus::Completed as i32);
+        assert_eq!(result.exit_code, Some(2));
+    }
+
+    /// A container that cannot be removed once its commands have run
+    struct UnremovableContainer(MockContainer);
+
+    impl ContainerOperations for UnremovableContainer {
+        async fn start(&self) -> Result<(), Error> {
+            self.0.start().await
+        }
+
+        async fn exec(
+            &self,
+            command: String,
+            workdir: Option<String>,
+            env: HashMap<String, String>,
+        ) -> Result<crate::models::container::exec_handle::ExecResult, Error> {
+            self.0.exec(command, workdir, env).await
+        }
+
+        async fn archive(
+            &self,
+            paths: Vec<String>,
+            absolute_names: bool,
+            workdir: Option<String>,
+        ) -> Result<crate::models::container::exec_handle::ExecResult, Error> {
+            self.0.archive(paths, absolute_names, workdir).await
+        }
+
+        async fn extract(
+            &self,
+            archive: std::path::PathBuf,
+            workdir: Option<String>,
+        ) -> Result<crate::models::container::exec_handle::ExecResult, Error> {
+            self.0.extract(archive, workdir).await
+        }
+
+        async fn hash_files(
+            &self,
+            patterns: Vec<String>,
+            workdir: Option<String>,
+        ) -> Result<crate::models::container::exec_handle::ExecResult, Error> {
+            self.0.hash_files(patterns, workdir).await
+        }
+
+        async fn remove(&self) -> Result<(), Error> {
+            Err(ContainerExecError(bollard::errors::Error::from(
+                std::io::Error::new(std::io::ErrorKind::Other, "Mock removal error"),
+            )))
+        }
+    }
+
+    #[tokio::test]
+    async fn test_action_result_is_sent_when_cleanup_fails() {
+        // Arrange
+        let (tx, mut rx) = mpsc::unbounded_channel();
+        let container = UnremovableContainer(MockContainer {
+            exec_calls: Mutex::new(Vec::new()),
+            should_fail: false,
+        });
+
+        let mut action = Action::new(
+            3,
+            container,
+            vec!["exit 0".to_string()],
+            tx,
+            "https://example.com/repo.git".to_string(),
+            Arc::new(StateBroker::new()),
+        );
+
+        // Act
+        let result = action.execute().await;
+
+        // Assert
+        assert!(result.is_ok());
+        assert!(matches!(action.state, State::Completed));
+        let mut last = None;
+        while let Ok(Ok(message)) = rx.try_recv() {
+            last = Some(message);
+        }
+        let result = last.unwrap().result.unwrap();
+        assert_eq!(result.completion, ActionStatus::Completed as i32);
+        assert_eq!(result.exit_code, Some(0));
     }
 
     #[tokio::test]
@@ -244,7 +1149,7 @@ mod tests {
         let action = Action::new(
             1,
             mock_container,
-            vec![],
+            Vec::<String>::new(),
             tx,
             "https://example.com/repo.git".to_string(),
             Arc::new(StateBroker::new()),
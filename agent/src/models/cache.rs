@@ -0,0 +1,313 @@
+use sha2::{Digest, Sha256};
+use std::{
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use tokio::{
+    fs::{self, File, OpenOptions},
+    sync::Mutex,
+};
+use tracing::info;
+
+use crate::proto;
+
+const EXPRESSION_START: &str = "${{";
+const EXPRESSION_END: &str = "}}";
+const ARCHIVE_EXTENSION: &str = "tar";
+
+/// Files of an action kept between its runs, under a key.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Cache {
+    /// Key template, which may contain `${{ hashFiles(...) }}` expressions
+    pub key: String,
+    /// Paths of the container to keep, relative to the repository or absolute
+    pub paths: Vec<String>,
+}
+
+impl From<proto::Cache> for Cache {
+    fn from(cache: proto::Cache) -> Self {
+        Self {
+            key: cache.key,
+            paths: cache.paths,
+        }
+    }
+}
+
+/// Part of a cache key: either text kept as-is or the patterns of the files
+/// whose content is hashed in its place.
+#[derive(Debug, PartialEq)]
+pub enum KeyPart {
+    Literal(String),
+    HashFiles(Vec<String>),
+}
+
+/// Splits a cache key template into its literal parts and its
+/// `${{ hashFiles('<pattern>', ...) }}` expressions. Other expressions are
+/// kept as literals.
+pub fn parse_key(key: &str) -> Vec<KeyPart> {
+    let mut parts = Vec::new();
+    let mut literal = String::new();
+    let mut rest = key;
+    while let Some(start) = rest.find(EXPRESSION_START) {
+        let after = &rest[start + EXPRESSION_START.len()..];
+        let Some(end) = after.find(EXPRESSION_END) else {
+            break;
+        };
+        let expression = &rest[..start + EXPRESSION_START.len() + end + EXPRESSION_END.len()];
+        match hash_files_patterns(after[..end].trim()) {
+            Some(patterns) => {
+                literal.push_str(&rest[..start]);
+                if !literal.is_empty() {
+                    parts.push(KeyPart::Literal(std::mem::take(&mut literal)));
+                }
+                parts.push(KeyPart::HashFiles(patterns));
+            }
+            None => literal.push_str(expression),
+        }
+        rest = &after[end + EXPRESSION_END.len()..];
+    }
+    literal.push_str(rest);
+    if !literal.is_empty() {
+        parts.push(KeyPart::Literal(literal));
+    }
+    parts
+}
+
+/// Returns the patterns of a `hashFiles('<pattern>', ...)` call.
+fn hash_files_patterns(expression: &str) -> Option<Vec<String>> {
+    let arguments = expression
+        .strip_prefix("hashFiles(")?
+        .strip_suffix(')')?
+        .trim();
+    if arguments.is_empty() {
+        return None;
+    }
+    arguments
+        .split(',')
+        .map(|argument| {
+            let argument = argument.trim();
+            argument
+                .strip_prefix('\'')
+                .and_then(|a| a.strip_suffix('\''))
+                .or_else(|| argument.strip_prefix('"').and_then(|a| a.strip_suffix('"')))
+                .filter(|pattern| !pattern.is_empty())
+                .map(String::from)
+        })
+        .collect()
+}
+
+/// Cache archives kept on the agent disk, as
+/// `<dir>/<sha256 of the repository>/<sha256 of the key>.tar`.
+///
+/// Each repository has its own archives: keys are chosen by the manifest
+/// authors, and the archives are extracted with absolute paths, so a
+/// repository must not restore an archive another one saved.
+///
+/// Archives are never overwritten: a key is saved once, by the first run that
+/// succeeds without finding it. Once the archives exceed the disk budget, the
+/// least recently used ones are removed.
+pub struct CacheStore {
+    dir: PathBuf,
+    /// Disk budget of the archives, in bytes
+    budget: u64,
+    /// Serializes the evictions
+    eviction: Mutex<()>,
+}
+
+impl CacheStore {
+    pub fn new(dir: impl Into<PathBuf>, budget: u64) -> Self {
+        Self {
+            dir: dir.into(),
+            budget,
+            eviction: Mutex::new(()),
+        }
+    }
+
+    fn repository_dir(&self, repository: &str) -> PathBuf {
+        self.dir.join(digest(repository))
+    }
+
+    fn archive_path(&self, repository: &str, key: &str) -> PathBuf {
+        // Keys are free text, hashing them gives every key its own file name
+        self.repository_dir(repository)
+            .join(format!("{}.{}", digest(key), ARCHIVE_EXTENSION))
+    }
+
+    /// Returns the archive the repository saved under `key`, if any, and
+    /// marks it as used.
+    pub async fn lookup(&self, repository: &str, key: &str) -> Option<PathBuf> {
+        let path = self.archive_path(repository, key);
+        let file = OpenOptions::new().write(true).open(&path).await.ok()?;
+        let file = file.into_std().await;
+        let _ = file.set_modified(SystemTime::now());
+        Some(path)
+    }
+
+    /// Creates a temporary file to write the archive of `key` to, to be
+    /// committed once complete.
+    pub async fn create(
+        &self,
+        repository: &str,
+        key: &str,
+    ) -> Result<(PathBuf, File), std::io::Error> {
+        fs::create_dir_all(self.repository_dir(repository)).await?;
+        let path = self
+            .archive_path(repository, key)
+            .with_extension(format!("{:x}.tmp", rand::random::<u64>()));
+        let file = File::create(&path).await?;
+        Ok((path, file))
+    }
+
+    /// Saves a temporary archive under `key` and evicts the archives over
+    /// the disk budget.
+    pub async fn commit(
+        &self,
+        repository: &str,
+        key: &str,
+        temporary: &Path,
+    ) -> Result<(), std::io::Error> {
+        fs::rename(temporary, self.archive_path(repository, key)).await?;
+        self.evict().await
+    }
+
+    /// Removes the least recently used archives, of every repository, until
+    /// they fit in the budget.
+    pub async fn evict(&self) -> Result<(), std::io::Error> {
+        let _guard = self.eviction.lock().await;
+        let mut archives = Vec::new();
+        let mut repositories = fs::read_dir(&self.dir).await?;
+        while let Some(repository) = repositories.next_entry().await? {
+            if !repository.file_type().await?.is_dir() {
+                continue;
+            }
+            let mut entries = fs::read_dir(repository.path()).await?;
+            while let Some(entry) = entries.next_entry().await? {
+                let path = entry.path();
+                if path.extension().and_then(|e| e.to_str()) != Some(ARCHIVE_EXTENSION) {
+                    continue;
+                }
+                let metadata = entry.metadata().await?;
+                archives.push((metadata.modified()?, metadata.len(), path));
+            }
+        }
+        archives.sort();
+
+        let mut size: u64 = archives.iter().map(|(_, len, _)| len).sum();
+        for (_, len, path) in archives {
+            if size <= self.budget {
+                break;
+            }
+            info!("Evicting cache archive {}", path.display());
+            fs::remove_file(&path).await?;
+            size -= len;
+        }
+        Ok(())
+    }
+}
+
+/// Hex SHA-256 of a value, safe in a file name whatever the value.
+fn digest(value: &str) -> String {
+    format!("{:x}", Sha256::digest(value.as_bytes()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+    use tokio::io::AsyncWriteExt;
+
+    #[test]
+    fn test_parse_key_splits_hash_files_expressions() {
+        let parts = parse_key("cargo-${{ hashFiles('Cargo.lock', \"*/Cargo.toml\") }}-v1");
+
+        assert_eq!(
+            parts,
+            vec![
+                KeyPart::Literal("cargo-".to_string()),
+                KeyPart::HashFiles(vec!["Cargo.lock".to_string(), "*/Cargo.toml".to_string()]),
+                KeyPart::Literal("-v1".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_key_keeps_other_expressions() {
+        let parts = parse_key("deps-${{ runner.os }}-${{ hashFiles() }}");
+
+        assert_eq!(
+            parts,
+            vec![KeyPart::Literal(
+                "deps-${{ runner.os }}-${{ hashFiles() }}".to_string()
+            )]
+        );
+    }
+
+    const REPOSITORY: &str = "https://github.com/dev-sealci/sealci";
+
+    async fn save(store: &CacheStore, repository: &str, key: &str, content: &[u8]) {
+        let (temporary, mut file) = store.create(repository, key).await.unwrap();
+        file.write_all(content).await.unwrap();
+        file.flush().await.unwrap();
+        store.commit(repository, key, &temporary).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_cache_store_evicts_least_recently_used() {
+        let dir = std::env::temp_dir().join(format!("sealci-cache-{}", rand::random::<u64>()));
+        let store = CacheStore::new(&dir, 10);
+
+        save(&store, REPOSITORY, "first", b"12345").await;
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        save(&store, REPOSITORY, "second", b"12345").await;
+        tokio::time::sleep(Duration::from_millis(20)).await;
+        // Using the first archive makes the second one the least recently used
+        assert!(store.lookup(REPOSITORY, "first").await.is_some());
+        save(
+            &store,
+            "https://github.com/dev-sealci/other",
+            "third",
+            b"12345",
+        )
+        .await;
+
+        assert!(store.lookup(REPOSITORY, "first").await.is_some());
+        assert!(store.lookup(REPOSITORY, "second").await.is_none());
+        assert!(store
+            .lookup("https://github.com/dev-sealci/other", "third")
+            .await
+            .is_some());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_cache_store_keeps_repositories_apart() {
+        let dir = std::env::temp_dir().join(format!("sealci-cache-{}", rand::random::<u64>()));
+        let store = CacheStore::new(&dir, 1024);
+        let other = "https://github.com/someone/else";
+
+        save(&store, other, "cargo-1234", b"poisoned").await;
+
+        assert!(store.lookup(REPOSITORY, "cargo-1234").await.is_none());
+        save(&store, REPOSITORY, "cargo-1234", b"genuine").await;
+        let archive = store.lookup(REPOSITORY, "cargo-1234").await.unwrap();
+        assert_eq!(std::fs::read(archive).unwrap(), b"genuine");
+        let archive = store.lookup(other, "cargo-1234").await.unwrap();
+        assert_eq!(std::fs::read(archive).unwrap(), b"poisoned");
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+
+    #[tokio::test]
+    async fn test_cache_store_does_not_mix_up_similar_keys() {
+        let dir = std::env::temp_dir().join(format!("sealci-cache-{}", rand::random::<u64>()));
+        let store = CacheStore::new(&dir, 1024);
+
+        save(&store, REPOSITORY, "a/b", b"slash").await;
+
+        assert!(store.lookup(REPOSITORY, "a_b").await.is_none());
+        assert!(store.lookup(REPOSITORY, "../a/b").await.is_none());
+
+        let _ = std::fs::remove_dir_all(dir);
+    }
+}
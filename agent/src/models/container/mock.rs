@@ -1,4 +1,4 @@
-use std::{pin::Pin, sync::Mutex};
+use std::{collections::HashMap, path::PathBuf, pin::Pin, sync::Mutex};
 
 use bollard::container::LogOutput;
 use futures_util::{stream, Stream};
@@ -7,6 +7,18 @@ use crate::models::error::Error;
 
 use super::{exec_handle::ExecResult, ContainerOperations};
 
+/// A successful exec writing `stdout` to its standard output
+fn stdout_result(stdout: &'static [u8]) -> ExecResult {
+    let output = Box::pin(stream::iter(vec![Ok(LogOutput::StdOut {
+        message: stdout.into(),
+    })]))
+        as Pin<Box<dyn Stream<Item = Result<LogOutput, bollard::errors::Error>> + Send>>;
+    ExecResult {
+        output,
+        exec_handle: tokio::task::spawn(async { 0 }),
+    }
+}
+
 // A simpler mock implementation of ContainerOperations
 pub struct MockContainer {
     // Track what was passed to exec
@@ -20,7 +32,18 @@ impl ContainerOperations for MockContainer {
         Ok(())
     }
 
-    async fn exec(&self, command: String, workdir: Option<String>) -> Result<ExecResult, Error> {
+    async fn exec(
+        &self,
+        command: String,
+        workdir: Option<String>,
+        _env: HashMap<String, String>,
+    ) -> Result<ExecResult, Error> {
+        // Commands of the form `exit <code>` exit with that code
+        let exit_code = command
+            .strip_prefix("exit ")
+            .and_then(|code| code.trim().parse::<i32>().ok())
+            .unwrap_or(0);
+
         // Record the call
         self.exec_calls.lock().unwrap().push((command, workdir));
 
@@ -30,19 +53,74 @@ impl ContainerOperations for MockContainer {
             )));
         }
 
-        // Create a simple empty stream for the output
-        let empty_stream = Box::pin(stream::empty::<Result<LogOutput, bollard::errors::Error>>())
+        // Commands of the form `echo <text> >&2` write to the standard error,
+        // the others write nothing
+        let output: Vec<Result<LogOutput, bollard::errors::Error>> = self
+            .exec_calls
+            .lock()
+            .unwrap()
+            .last()
+            .and_then(|(command, _)| command.strip_prefix("echo ")?.strip_suffix(" >&2"))
+            .map(|text| {
+                Ok(LogOutput::StdErr {
+                    message: format!("{}\n", text).into(),
+                })
+            })
+            .into_iter()
+            .collect();
+        let output = Box::pin(stream::iter(output))
             as Pin<Box<dyn Stream<Item = Result<LogOutput, bollard::errors::Error>> + Send>>;
 
-        // Create a task that just returns 0
-        let handle = tokio::task::spawn(async { 0 });
+        // Create a task that just returns the exit code
+        let handle = tokio::task::spawn(async move { exit_code });
 
         Ok(ExecResult {
-            output: empty_stream,
+            output,
             exec_handle: handle,
         })
     }
 
+    async fn archive(
+        &self,
+        paths: Vec<String>,
+        absolute_names: bool,
+        workdir: Option<String>,
+    ) -> Result<ExecResult, Error> {
+        // Record the call as the command it stands for
+        let flags = if absolute_names { "-cPf" } else { "-cf" };
+        self.exec_calls
+            .lock()
+            .unwrap()
+            .push((format!("tar {} - {}", flags, paths.join(" ")), workdir));
+
+        // Stream a fake archive on the standard output
+        Ok(stdout_result(b"archive"))
+    }
+
+    async fn extract(
+        &self,
+        archive: PathBuf,
+        workdir: Option<String>,
+    ) -> Result<ExecResult, Error> {
+        self.exec_calls
+            .lock()
+            .unwrap()
+            .push((format!("tar -xPf {}", archive.display()), workdir));
+        Ok(stdout_result(b""))
+    }
+
+    async fn hash_files(
+        &self,
+        patterns: Vec<String>,
+        workdir: Option<String>,
+    ) -> Result<ExecResult, Error> {
+        self.exec_calls
+            .lock()
+            .unwrap()
+            .push((format!("sha256sum {}", patterns.join(" ")), workdir));
+        Ok(stdout_result(b"0123abcd\n"))
+    }
+
     async fn remove(&self) -> Result<(), Error> {
         Ok(())
     }
@@ -1,25 +1,46 @@
-use std::{sync::Arc, time::Duration};
-use tokio::{task, time::sleep};
+use std::{collections::HashMap, path::PathBuf, sync::Arc, time::Duration};
+use tokio::{
+    fs::File,
+    io::AsyncWriteExt,
+    task,
+    time::{sleep, Instant},
+};
 pub mod exec_handle;
 pub mod mock;
+pub mod service;
 use bollard::{
-    container::Config,
+    container::{Config, RemoveContainerOptions},
     exec::{CreateExecOptions, StartExecResults},
     image::CreateImageOptions,
+    models::HostConfig,
+    network::CreateNetworkOptions,
     Docker,
 };
 use exec_handle::ExecResult;
 use futures_util::TryStreamExt;
+use service::{readiness, Readiness, Service};
+use tracing::{info, warn};
 
 use super::error::Error::{
     self, ContainerExecDetachedError, ContainerExecError, ContainerRemoveError,
-    ContainerStartError, PullImageError,
+    ContainerStartError, PullImageError, ServiceError,
 };
 
+/// Time the services of an action may take to be ready
+const SERVICES_READY_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Shell running the commands when the action does not set one, stopping a
+/// multi-line script at its first failing line
+pub const DEFAULT_SHELL: [&str; 3] = ["/bin/sh", "-e", "-c"];
+
 #[derive(Debug, Clone)]
 pub struct Container {
     pub id: String,
     pub config: Config<String>,
+    /// Containers started next to this one, on a network they share
+    pub services: Vec<Service>,
+    /// Program and arguments the commands are passed to
+    pub shell: Vec<String>,
     docker: Option<Arc<Docker>>,
 }
 
@@ -28,11 +49,38 @@ pub trait ContainerOperations {
     /// Start the container
     fn start(&self) -> impl std::future::Future<Output = Result<(), Error>>;
 
-    /// Execute a command in the container
+    /// Execute a command in the container, through its shell, with `env`
+    /// added to its environment
     fn exec(
         &self,
         command: String,
         workdir: Option<String>,
+        env: HashMap<String, String>,
+    ) -> impl std::future::Future<Output = Result<ExecResult, Error>>;
+
+    /// Archive the given paths of the container, the tar archive is written
+    /// to the standard output of the exec and errors to its standard error.
+    /// Absolute paths are only kept as such with `absolute_names`.
+    fn archive(
+        &self,
+        paths: Vec<String>,
+        absolute_names: bool,
+        workdir: Option<String>,
+    ) -> impl std::future::Future<Output = Result<ExecResult, Error>>;
+
+    /// Extract a tar archive of the host in the container
+    fn extract(
+        &self,
+        archive: PathBuf,
+        workdir: Option<String>,
+    ) -> impl std::future::Future<Output = Result<ExecResult, Error>>;
+
+    /// Hash the content of the files matching the given patterns, the hash is
+    /// written to the standard output of the exec
+    fn hash_files(
+        &self,
+        patterns: Vec<String>,
+        workdir: Option<String>,
     ) -> impl std::future::Future<Output = Result<ExecResult, Error>>;
 
     /// Remove the container
@@ -40,12 +88,19 @@ pub trait ContainerOperations {
 }
 
 impl Container {
-    pub fn new(image: String, docker: Arc<Docker>) -> Self {
+    pub fn new(image: String, env: HashMap<String, String>, docker: Arc<Docker>) -> Self {
         let id = format!("{:x}", rand::random::<u128>());
         let entrypoint = Some(vec!["/bin/sh".to_string()]);
+        // Docker expects the environment as a list of KEY=VALUE strings
+        let mut env: Vec<String> = env
+            .into_iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        env.sort();
         let config = Config {
             entrypoint,
             image: Some(image),
+            env: Some(env),
             attach_stdin: Some(true),
             attach_stdout: Some(true),
             attach_stderr: Some(true),
@@ -55,69 +110,68 @@ impl Container {
         Container {
             id,
             config,
+            services: Vec::new(),
+            shell: DEFAULT_SHELL.map(String::from).to_vec(),
             docker: Some(docker),
         }
     }
 
+    /// Runs the commands through the given shell, or the default one when
+    /// empty.
+    pub fn with_shell(mut self, shell: Vec<String>) -> Self {
+        if !shell.is_empty() {
+            self.shell = shell;
+        }
+        self
+    }
+
+    /// Runs the container next to the given services, on a network of its own.
+    pub fn with_services(mut self, services: Vec<Service>) -> Self {
+        if !services.is_empty() {
+            self.config.host_config = Some(HostConfig {
+                network_mode: Some(self.network()),
+                ..Default::default()
+            });
+        }
+        self.services = services;
+        self
+    }
+
+    /// Name of the network shared by the container and its services.
+    fn network(&self) -> String {
+        format!("sealci-{}", self.id)
+    }
+
     fn docker(&self) -> Result<Arc<Docker>, Error> {
         self.docker
             .clone()
             .ok_or(Error::Error("Docker not set".to_string()))
     }
-}
-
-impl ContainerOperations for Container {
-    async fn start(&self) -> Result<(), Error> {
-        let docker = self.docker()?;
-        // Get the image
-        let image = self
-            .config
-            .image
-            .clone()
-            .ok_or(Error::Error("Image was not provided".to_string()))?;
-        docker
-            .create_image(
-                Some(CreateImageOptions {
-                    from_image: image,
-                    ..Default::default()
-                }),
-                None,
-                None,
-            )
-            .try_collect::<Vec<_>>()
-            .await
-            .map_err(PullImageError)?;
-
-        docker
-            .create_container::<String, String>(
-                Some(bollard::container::CreateContainerOptions {
-                    name: self.id.clone(),
-                    platform: None,
-                }),
-                self.config.clone(),
-            )
-            .await
-            .map_err(ContainerStartError)?;
-        docker
-            .start_container::<String>(&self.id, None)
-            .await
-            .map_err(ContainerStartError)?;
-        Ok(())
-    }
 
-    async fn exec(&self, command: String, workdir: Option<String>) -> Result<ExecResult, Error> {
+    /// Run a command in the container. It gets no tty, so that its standard
+    /// output and error are kept apart and not altered. The `KEY=value`
+    /// pairs of `env` are added to the environment of the command and the
+    /// content of `stdin`, if any, is written to its standard input.
+    async fn run(
+        &self,
+        cmd: Vec<String>,
+        workdir: Option<String>,
+        env: Vec<String>,
+        stdin: Option<File>,
+    ) -> Result<ExecResult, Error> {
         let docker = self.docker()?;
 
         let exec = docker
             .create_exec(
                 &self.id,
                 CreateExecOptions {
-                    cmd: Some(command.split(' ').map(String::from).collect()),
-                    tty: Some(true),
+                    cmd: Some(cmd),
+                    tty: Some(false),
                     attach_stdin: Some(true),
                     attach_stdout: Some(true),
                     attach_stderr: Some(true),
                     working_dir: workdir,
+                    env: Some(env),
                     ..Default::default()
                 },
             )
@@ -151,7 +205,16 @@ impl ContainerOperations for Container {
 
         // The stream of stdout of the exec
         let output = match exec_result {
-            StartExecResults::Attached { output, input: _ } => output,
+            StartExecResults::Attached { output, mut input } => {
+                if let Some(mut stdin) = stdin {
+                    task::spawn(async move {
+                        // Closing the input lets the command know it is complete
+                        let _ = tokio::io::copy(&mut stdin, &mut input).await;
+                        let _ = input.shutdown().await;
+                    });
+                }
+                output
+            }
             StartExecResults::Detached => return Err(ContainerExecDetachedError),
         };
 
@@ -161,17 +224,229 @@ impl ContainerOperations for Container {
         })
     }
 
-    async fn remove(&self) -> Result<(), Error> {
+    async fn pull_image(&self, image: String) -> Result<(), Error> {
         self.docker()?
-            .stop_container(&self.id, None)
+            .create_image(
+                Some(CreateImageOptions {
+                    from_image: image,
+                    ..Default::default()
+                }),
+                None,
+                None,
+            )
+            .try_collect::<Vec<_>>()
             .await
-            .map_err(ContainerRemoveError)?;
-        self.docker()?
-            .remove_container(&self.id, None)
+            .map_err(PullImageError)?;
+        Ok(())
+    }
+
+    /// Creates the network of the container and starts its services on it,
+    /// returning once they are all ready.
+    async fn start_services(&self) -> Result<(), Error> {
+        let docker = self.docker()?;
+        let network = self.network();
+        docker
+            .create_network(CreateNetworkOptions {
+                name: network.clone(),
+                check_duplicate: true,
+                ..Default::default()
+            })
             .await
-            .map_err(ContainerRemoveError)?;
+            .map_err(ContainerStartError)?;
+
+        for service in &self.services {
+            info!(
+                "Starting service {} for container {}",
+                service.name, self.id
+            );
+            self.pull_image(service.image.clone()).await?;
+            let name = service.container_name(&self.id);
+            docker
+                .create_container::<String, String>(
+                    Some(bollard::container::CreateContainerOptions {
+                        name: name.clone(),
+                        platform: None,
+                    }),
+                    service.config(&network),
+                )
+                .await
+                .map_err(ContainerStartError)?;
+            docker
+                .start_container::<String>(&name, None)
+                .await
+                .map_err(ContainerStartError)?;
+        }
+
+        let deadline = Instant::now() + SERVICES_READY_TIMEOUT;
+        for service in &self.services {
+            let name = service.container_name(&self.id);
+            loop {
+                let state = docker
+                    .inspect_container(&name, None)
+                    .await
+                    .map_err(ContainerStartError)?
+                    .state
+                    .unwrap_or_default();
+                match readiness(&state) {
+                    Readiness::Ready => break,
+                    Readiness::Failed(reason) => {
+                        return Err(ServiceError(format!("{} {}", service.name, reason)))
+                    }
+                    Readiness::Starting if Instant::now() >= deadline => {
+                        return Err(ServiceError(format!(
+                            "{} is not ready after {}s",
+                            service.name,
+                            SERVICES_READY_TIMEOUT.as_secs()
+                        )))
+                    }
+                    Readiness::Starting => sleep(Duration::from_secs(1)).await,
+                }
+            }
+        }
         Ok(())
     }
+
+    /// Removes the services of the container and their network, carrying on
+    /// past failures so that as much as possible is cleaned up.
+    async fn remove_services(&self) {
+        if self.services.is_empty() {
+            return;
+        }
+        let Ok(docker) = self.docker() else {
+            return;
+        };
+        for service in &self.services {
+            let name = service.container_name(&self.id);
+            if let Err(e) = docker
+                .remove_container(
+                    &name,
+                    Some(RemoveContainerOptions {
+                        force: true,
+                        ..Default::default()
+                    }),
+                )
+                .await
+            {
+                warn!("Failed to remove service container {}: {}", name, e);
+            }
+        }
+        if let Err(e) = docker.remove_network(&self.network()).await {
+            warn!("Failed to remove network {}: {}", self.network(), e);
+        }
+    }
+}
+
+impl ContainerOperations for Container {
+    async fn start(&self) -> Result<(), Error> {
+        let docker = self.docker()?;
+        // Get the image
+        let image = self
+            .config
+            .image
+            .clone()
+            .ok_or(Error::Error("Image was not provided".to_string()))?;
+        self.pull_image(image).await?;
+
+        let result = async {
+            if !self.services.is_empty() {
+                self.start_services().await?;
+            }
+            docker
+                .create_container::<String, String>(
+                    Some(bollard::container::CreateContainerOptions {
+                        name: self.id.clone(),
+                        platform: None,
+                    }),
+                    self.config.clone(),
+                )
+                .await
+                .map_err(ContainerStartError)?;
+            docker
+                .start_container::<String>(&self.id, None)
+                .await
+                .map_err(ContainerStartError)
+        }
+        .await;
+        if result.is_err() {
+            // The action never runs, so nothing else removes the services
+            self.remove_services().await;
+        }
+        result
+    }
+
+    async fn exec(
+        &self,
+        command: String,
+        workdir: Option<String>,
+        env: HashMap<String, String>,
+    ) -> Result<ExecResult, Error> {
+        // The shell takes care of quoting, pipes, redirections and expansions
+        let mut cmd = self.shell.clone();
+        cmd.push(command);
+        let mut env: Vec<String> = env
+            .into_iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        env.sort();
+        self.run(cmd, workdir, env, None).await
+    }
+
+    async fn archive(
+        &self,
+        paths: Vec<String>,
+        absolute_names: bool,
+        workdir: Option<String>,
+    ) -> Result<ExecResult, Error> {
+        // Paths are shell patterns, so they are expanded by a shell
+        let flags = if absolute_names { "-cPf" } else { "-cf" };
+        let command = format!("tar {} - {}", flags, paths.join(" "));
+        let cmd = vec!["/bin/sh".to_string(), "-c".to_string(), command];
+        self.run(cmd, workdir, Vec::new(), None).await
+    }
+
+    async fn extract(
+        &self,
+        archive: PathBuf,
+        workdir: Option<String>,
+    ) -> Result<ExecResult, Error> {
+        let archive = File::open(archive)
+            .await
+            .map_err(|e| Error::Error(format!("Failed to open archive: {}", e)))?;
+        let cmd = ["tar", "-xPf", "-"].map(String::from).to_vec();
+        self.run(cmd, workdir, Vec::new(), Some(archive)).await
+    }
+
+    async fn hash_files(
+        &self,
+        patterns: Vec<String>,
+        workdir: Option<String>,
+    ) -> Result<ExecResult, Error> {
+        // Patterns matching no file hash to the hash of an empty input
+        let command = format!(
+            "for f in {}; do [ -f \"$f\" ] && sha256sum \"$f\"; done | sha256sum | cut -d ' ' -f 1",
+            patterns.join(" ")
+        );
+        let cmd = vec!["/bin/sh".to_string(), "-c".to_string(), command];
+        self.run(cmd, workdir, Vec::new(), None).await
+    }
+
+    async fn remove(&self) -> Result<(), Error> {
+        let docker = self.docker()?;
+        let result = async {
+            docker
+                .stop_container(&self.id, None)
+                .await
+                .map_err(ContainerRemoveError)?;
+            docker
+                .remove_container(&self.id, None)
+                .await
+                .map_err(ContainerRemoveError)
+        }
+        .await;
+        // The services go away with the container, even if it failed to
+        self.remove_services().await;
+        result
+    }
 }
 
 impl Default for Container {
@@ -179,7 +454,29 @@ impl Default for Container {
         Self {
             id: String::new(),
             config: Config::default(),
+            services: Vec::new(),
+            shell: DEFAULT_SHELL.map(String::from).to_vec(),
             docker: None,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_container_with_shell_defaults_to_sh() {
+        let container = Container::default().with_shell(Vec::new());
+        assert_eq!(container.shell, vec!["/bin/sh", "-e", "-c"]);
+
+        let shell = vec![
+            "bash".to_string(),
+            "-eo".to_string(),
+            "pipefail".to_string(),
+            "-c".to_string(),
+        ];
+        let container = Container::default().with_shell(shell.clone());
+        assert_eq!(container.shell, shell);
+    }
+}
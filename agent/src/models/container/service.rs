@@ -0,0 +1,183 @@
+use std::collections::HashMap;
+
+use bollard::{
+    container::{Config, NetworkingConfig},
+    models::{
+        ContainerState, ContainerStateStatusEnum, EndpointSettings, HealthConfig, HealthStatusEnum,
+    },
+};
+
+use crate::proto;
+
+/// Time between two runs of the healthcheck of a service, in nanoseconds
+const HEALTHCHECK_INTERVAL: i64 = 2_000_000_000;
+/// Time a run of the healthcheck of a service may take, in nanoseconds
+const HEALTHCHECK_TIMEOUT: i64 = 5_000_000_000;
+/// Failed runs of the healthcheck after which a service is unhealthy
+const HEALTHCHECK_RETRIES: i64 = 30;
+
+/// Container started next to the container of an action, on a network they
+/// share, such as a database the steps run their tests against.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Service {
+    /// Name of the service, the steps reach it under this host name
+    pub name: String,
+    pub image: String,
+    pub env: HashMap<String, String>,
+    /// Shell command run in the service until it succeeds, the service is
+    /// ready as soon as it runs when not set
+    pub healthcheck: Option<String>,
+}
+
+impl From<proto::Service> for Service {
+    fn from(service: proto::Service) -> Self {
+        Self {
+            name: service.name,
+            image: service.image,
+            env: service.env,
+            healthcheck: service.healthcheck,
+        }
+    }
+}
+
+/// Where a service is at in its startup.
+#[derive(Debug, PartialEq)]
+pub enum Readiness {
+    Starting,
+    Ready,
+    Failed(String),
+}
+
+impl Service {
+    /// Name of the container of the service, unique to the action.
+    pub fn container_name(&self, action_container: &str) -> String {
+        format!("{}-{}", action_container, self.name)
+    }
+
+    /// Configuration of the container of the service, attached to `network`
+    /// under the name of the service.
+    pub fn config(&self, network: &str) -> Config<String> {
+        let mut env: Vec<String> = self
+            .env
+            .iter()
+            .map(|(key, value)| format!("{}={}", key, value))
+            .collect();
+        env.sort();
+        let endpoint = EndpointSettings {
+            aliases: Some(vec![self.name.clone()]),
+            ..Default::default()
+        };
+        let healthcheck = self.healthcheck.as_ref().map(|command| HealthConfig {
+            test: Some(vec!["CMD-SHELL".to_string(), command.clone()]),
+            interval: Some(HEALTHCHECK_INTERVAL),
+            timeout: Some(HEALTHCHECK_TIMEOUT),
+            retries: Some(HEALTHCHECK_RETRIES),
+            ..Default::default()
+        });
+        Config {
+            image: Some(self.image.clone()),
+            env: Some(env),
+            healthcheck,
+            networking_config: Some(NetworkingConfig {
+                endpoints_config: HashMap::from([(network.to_string(), endpoint)]),
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+/// Tells whether a started service is ready from the state of its container.
+/// A service is ready once it runs and, when it has a healthcheck (its own or
+/// the one of its image), once it is healthy.
+pub fn readiness(state: &ContainerState) -> Readiness {
+    if state.status == Some(ContainerStateStatusEnum::CREATED) {
+        return Readiness::Starting;
+    }
+    if state.running != Some(true) {
+        return Readiness::Failed(format!(
+            "exited with code {}",
+            state.exit_code.unwrap_or_default()
+        ));
+    }
+    match state.health.as_ref().and_then(|health| health.status) {
+        Some(HealthStatusEnum::STARTING) => Readiness::Starting,
+        Some(HealthStatusEnum::UNHEALTHY) => Readiness::Failed("is unhealthy".to_string()),
+        _ => Readiness::Ready,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bollard::models::Health;
+
+    #[test]
+    fn test_service_config_attaches_alias_and_healthcheck() {
+        let service = Service {
+            name: "db".to_string(),
+            image: "postgres:16".to_string(),
+            env: HashMap::from([
+                ("POSTGRES_USER".to_string(), "ci".to_string()),
+                ("POSTGRES_DB".to_string(), "test".to_string()),
+            ]),
+            healthcheck: Some("pg_isready -U ci".to_string()),
+        };
+
+        let config = service.config("sealci-42");
+
+        assert_eq!(service.container_name("42"), "42-db");
+        assert_eq!(config.image.as_deref(), Some("postgres:16"));
+        assert_eq!(
+            config.env,
+            Some(vec![
+                "POSTGRES_DB=test".to_string(),
+                "POSTGRES_USER=ci".to_string()
+            ])
+        );
+        let endpoints = config.networking_config.unwrap().endpoints_config;
+        assert_eq!(endpoints["sealci-42"].aliases, Some(vec!["db".to_string()]));
+        assert_eq!(
+            config.healthcheck.unwrap().test,
+            Some(vec![
+                "CMD-SHELL".to_string(),
+                "pg_isready -U ci".to_string()
+            ])
+        );
+    }
+
+    #[test]
+    fn test_readiness_waits_for_health() {
+        let state = |running: bool, health: Option<HealthStatusEnum>| ContainerState {
+            status: Some(if running {
+                ContainerStateStatusEnum::RUNNING
+            } else {
+                ContainerStateStatusEnum::EXITED
+            }),
+            running: Some(running),
+            exit_code: Some(if running { 0 } else { 1 }),
+            health: health.map(|status| Health {
+                status: Some(status),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+
+        assert_eq!(readiness(&state(true, None)), Readiness::Ready);
+        assert_eq!(
+            readiness(&state(true, Some(HealthStatusEnum::STARTING))),
+            Readiness::Starting
+        );
+        assert_eq!(
+            readiness(&state(true, Some(HealthStatusEnum::HEALTHY))),
+            Readiness::Ready
+        );
+        assert_eq!(
+            readiness(&state(true, Some(HealthStatusEnum::UNHEALTHY))),
+            Readiness::Failed("is unhealthy".to_string())
+        );
+        assert_eq!(
+            readiness(&state(false, None)),
+            Readiness::Failed("exited with code 1".to_string())
+        );
+    }
+}
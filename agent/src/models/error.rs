@@ -1,3 +1,4 @@
+use std::time::Duration;
 use tokio::task::JoinError;
 use tonic::Status;
 
@@ -14,6 +15,7 @@ pub enum Error {
     ContainerExecDetachedError,
     ExecError(JoinError),
     StepOutputError(i32),
+    ActionTimeoutError(Duration),
     ConnectionError(tonic::transport::Error),
     ServeError(tonic::transport::Error),
     RegistrationError(Status),
@@ -24,6 +26,10 @@ pub enum Error {
     ActionStateError,
     BrokerSendError(String),
     ChannelError(String),
+    CacheError(std::io::Error),
+    ServiceError(String),
+    CheckoutError(i32),
+    InvalidCheckoutError(String),
 }
 
 impl std::fmt::Display for Error {
@@ -38,6 +44,7 @@ impl std::fmt::Display for Error {
             Error::ContainerExecDetachedError => write!(f, "Container exec detached error"),
             Error::ExecError(e) => write!(f, "Exec error: {}", e),
             Error::StepOutputError(code) => write!(f, "Step output error with code: {}", code),
+            Error::ActionTimeoutError(timeout) => write!(f, "Action timed out after {:?}", timeout),
             Error::ConnectionError(e) => write!(f, "Connection error: {}", e),
             Error::ServeError(e) => write!(f, "Serve error: {}", e),
             Error::RegistrationError(status) => write!(f, "Registration error: {}", status),
@@ -48,6 +55,12 @@ impl std::fmt::Display for Error {
             Error::ActionStateError => write!(f, "Action state error"),
             Error::BrokerSendError(msg) => write!(f, "Broker send error: {}", msg),
             Error::ChannelError(msg) => write!(f, "Channel error: {}", msg),
+            Error::CacheError(e) => write!(f, "Cache error: {}", e),
+            Error::ServiceError(msg) => write!(f, "Service error: {}", msg),
+            Error::CheckoutError(code) => {
+                write!(f, "Repository checkout failed with code: {}", code)
+            }
+            Error::InvalidCheckoutError(msg) => write!(f, "Invalid checkout: {}", msg),
         }
     }
 }
@@ -1,4 +1,6 @@
 pub mod action;
+pub mod cache;
+pub mod checkout;
 pub mod container;
 pub mod error;
 pub mod output_pipe;
@@ -1,7 +1,20 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
 use tokio::sync::mpsc::UnboundedSender;
 use tonic::Status;
 
-use crate::proto::{ActionResponseStream, ActionResult};
+use crate::proto::{
+    ActionResponseStream, ActionResult, ActionStatus, ArtifactChunk, LogStream, StepFinished,
+    StepStarted,
+};
+
+/// Current time in milliseconds since the Unix epoch.
+pub fn unix_millis() -> i64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_millis() as i64)
+        .unwrap_or_default()
+}
 
 /// An output pipe is used to stream the output of an action.
 /// It is directly associated with an action and provides a way to send logs and results back to the client.
@@ -12,6 +25,13 @@ pub struct OutputPipe {
 
 pub trait Pipe {
     fn output_log(&self, log: String, completion: i32, exit_code: Option<i32>);
+    /// Sends a line written by a step of the running action.
+    fn output_step_log(&self, log: String, stream: LogStream, step_index: u32);
+    fn output_artifact(&self, data: Vec<u8>);
+    /// Tells that every chunk of the artifacts archive was sent
+    fn output_artifact_complete(&self);
+    fn output_step_started(&self, step: StepStarted);
+    fn output_step_finished(&self, step: StepFinished);
 }
 
 impl OutputPipe {
@@ -32,6 +52,83 @@ impl Pipe for OutputPipe {
                 completion,
                 exit_code,
             }),
+            artifact: None,
+            step_started: None,
+            step_finished: None,
+            timestamp: unix_millis(),
+            stream: LogStream::System.into(),
+            step_index: None,
+        }));
+    }
+
+    fn output_step_log(&self, log: String, stream: LogStream, step_index: u32) {
+        let _ = self.pipe.send(Ok(ActionResponseStream {
+            log,
+            action_id: self.action_id,
+            result: Some(ActionResult {
+                completion: ActionStatus::Running.into(),
+                exit_code: None,
+            }),
+            artifact: None,
+            step_started: None,
+            step_finished: None,
+            timestamp: unix_millis(),
+            stream: stream.into(),
+            step_index: Some(step_index),
+        }));
+    }
+
+    fn output_artifact(&self, data: Vec<u8>) {
+        let _ = self.pipe.send(Ok(ActionResponseStream {
+            log: String::new(),
+            action_id: self.action_id,
+            result: None,
+            artifact: Some(ArtifactChunk {
+                data,
+                complete: false,
+            }),
+            step_started: None,
+            step_finished: None,
+            ..Default::default()
+        }));
+    }
+
+    fn output_artifact_complete(&self) {
+        let _ = self.pipe.send(Ok(ActionResponseStream {
+            log: String::new(),
+            action_id: self.action_id,
+            result: None,
+            artifact: Some(ArtifactChunk {
+                data: Vec::new(),
+                complete: true,
+            }),
+            step_started: None,
+            step_finished: None,
+            ..Default::default()
+        }));
+    }
+
+    fn output_step_started(&self, step: StepStarted) {
+        let _ = self.pipe.send(Ok(ActionResponseStream {
+            log: String::new(),
+            action_id: self.action_id,
+            result: None,
+            artifact: None,
+            step_started: Some(step),
+            step_finished: None,
+            ..Default::default()
+        }));
+    }
+
+    fn output_step_finished(&self, step: StepFinished) {
+        let _ = self.pipe.send(Ok(ActionResponseStream {
+            log: String::new(),
+            action_id: self.action_id,
+            result: None,
+            artifact: None,
+            step_started: None,
+            step_finished: Some(step),
+            ..Default::default()
         }));
     }
 }
@@ -108,6 +205,36 @@ mod tests {
         }
     }
 
+    #[tokio::test]
+    async fn test_output_step_log_carries_stream_and_step() {
+        let (pipe, mut rx) = create_test_pipe(7);
+        let before = unix_millis();
+
+        pipe.output_step_log("warning: unused".to_string(), LogStream::Stderr, 2);
+
+        let message = rx.recv().await.unwrap().unwrap();
+        assert_eq!(message.log, "warning: unused");
+        assert_eq!(message.stream, LogStream::Stderr as i32);
+        assert_eq!(message.step_index, Some(2));
+        assert!(message.timestamp >= before);
+        assert_eq!(
+            message.result.unwrap().completion,
+            ActionStatus::Running as i32
+        );
+    }
+
+    #[tokio::test]
+    async fn test_output_log_is_a_timestamped_system_line() {
+        let (pipe, mut rx) = create_test_pipe(7);
+
+        pipe.output_log("Action completed".to_string(), 3, Some(0));
+
+        let message = rx.recv().await.unwrap().unwrap();
+        assert_eq!(message.stream, LogStream::System as i32);
+        assert_eq!(message.step_index, None);
+        assert!(message.timestamp > 0);
+    }
+
     #[tokio::test]
     async fn test_new_creates_pipe_with_correct_id() {
         let action_id = 789;
@@ -1,15 +1,61 @@
 use super::{container::ContainerOperations, error::Error};
 use crate::models::container::exec_handle::ExecResult;
-use std::sync::Arc;
+use crate::proto;
+use std::{collections::HashMap, sync::Arc};
+
+/// A command of an action along with the options it runs with, as sent by
+/// the scheduler.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StepDefinition {
+    pub run: String,
+    /// Shown in the logs in place of the command
+    pub name: Option<String>,
+    /// Relative to the repository, or absolute
+    pub working_directory: Option<String>,
+    /// Variables added to the environment of the action for this step only
+    pub env: HashMap<String, String>,
+    /// Whether the next steps still run when this one fails
+    pub continue_on_error: bool,
+}
+
+impl From<proto::Step> for StepDefinition {
+    fn from(step: proto::Step) -> Self {
+        Self {
+            run: step.run,
+            name: step.name,
+            working_directory: step.working_directory,
+            env: step.env,
+            continue_on_error: step.continue_on_error,
+        }
+    }
+}
+
+impl From<String> for StepDefinition {
+    fn from(run: String) -> Self {
+        Self {
+            run,
+            ..Default::default()
+        }
+    }
+}
 
 #[derive(Clone)]
 pub struct Step<T: ContainerOperations> {
     /// This is the command that will be executed in the container
     pub command: String,
 
+    /// Shown in the logs in place of the command
+    pub name: Option<String>,
+
     /// This is the directory in which the command will be executed
     execute_in: Option<String>,
 
+    /// Variables added to the environment of the container for the command
+    env: HashMap<String, String>,
+
+    /// A failure of the command does not stop the action
+    pub continue_on_error: bool,
+
     /// Container
     container: Arc<T>,
 }
@@ -18,7 +64,32 @@ impl<T: ContainerOperations> Step<T> {
     pub fn new(command: String, execute_in: Option<String>, container: Arc<T>) -> Self {
         Self {
             command,
+            name: None,
             execute_in,
+            env: HashMap::new(),
+            continue_on_error: false,
+            container,
+        }
+    }
+
+    /// Creates the step of a definition, whose working directory is relative
+    /// to `repository` unless absolute.
+    pub fn from_definition(
+        definition: StepDefinition,
+        repository: &str,
+        container: Arc<T>,
+    ) -> Self {
+        let execute_in = match definition.working_directory {
+            Some(dir) if dir.starts_with('/') => dir,
+            Some(dir) => format!("{}/{}", repository, dir.trim_end_matches('/')),
+            None => repository.to_string(),
+        };
+        Self {
+            command: definition.run,
+            name: definition.name,
+            execute_in: Some(execute_in),
+            env: definition.env,
+            continue_on_error: definition.continue_on_error,
             container,
         }
     }
@@ -26,7 +97,11 @@ impl<T: ContainerOperations> Step<T> {
     /// Execute the command in the container
     pub async fn execute(&self) -> Result<ExecResult, Error> {
         self.container
-            .exec(self.command.clone(), self.execute_in.clone())
+            .exec(
+                self.command.clone(),
+                self.execute_in.clone(),
+                self.env.clone(),
+            )
             .await
     }
 }
@@ -80,4 +155,31 @@ mod tests {
             _ => panic!("Unexpected result"),
         }
     }
+
+    #[tokio::test]
+    async fn test_step_from_definition_resolves_working_directory() {
+        let container = Arc::new(MockContainer {
+            exec_calls: Mutex::new(Vec::new()),
+            should_fail: false,
+        });
+        let definition = |working_directory: Option<&str>| StepDefinition {
+            run: "cargo test".to_string(),
+            name: Some("Unit tests".to_string()),
+            working_directory: working_directory.map(String::from),
+            env: HashMap::from([("RUST_LOG".to_string(), "debug".to_string())]),
+            continue_on_error: true,
+        };
+
+        for working_directory in [None, Some("core/"), Some("/tmp")] {
+            let step =
+                Step::from_definition(definition(working_directory), "/3", container.clone());
+            assert_eq!(step.name.as_deref(), Some("Unit tests"));
+            assert!(step.continue_on_error);
+            let _ = step.execute().await;
+        }
+
+        let calls = container.exec_calls.lock().unwrap();
+        let workdirs: Vec<_> = calls.iter().map(|call| call.1.as_deref()).collect();
+        assert_eq!(workdirs, vec![Some("/3"), Some("/3/core"), Some("/tmp")]);
+    }
 }
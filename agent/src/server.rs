@@ -1,14 +1,22 @@
+use crate::models::{
+    cache::Cache,
+    checkout::Checkout,
+    container::service::Service,
+    error::Error::ActionTimeoutError,
+    output_pipe::{OutputPipe, Pipe},
+    step::StepDefinition,
+};
 use crate::proto::{
     action_service_server::ActionService as ActionServiceGrpc, ActionRequest, ActionResponseStream,
+    ActionStatus, CancelActionRequest, CancelActionResponse,
 };
 use crate::services::action_service::ActionService;
-use futures_util::{Stream, StreamExt};
-use std::pin::Pin;
+use futures_util::Stream;
+use std::{pin::Pin, time::Duration};
 use tokio::sync::mpsc::unbounded_channel;
-use tokio::sync::oneshot;
 use tokio_stream::wrappers::UnboundedReceiverStream;
 use tonic::{async_trait, Request, Response, Status};
-use tracing::info;
+use tracing::{error, info};
 
 pub struct ActionsLauncher {
     pub action_service: ActionService,
@@ -24,11 +32,11 @@ impl ActionServiceGrpc for ActionsLauncher {
         request: Request<ActionRequest>,
     ) -> Result<Response<Self::ExecutionActionStream>, Status> {
         // Create two channels:
-        // 1. For normal log messages
-        let (log_tx, log_rx) = unbounded_channel::<Result<ActionResponseStream, Status>>();
+        // 1. For the log messages produced by the action
+        let (log_tx, mut log_rx) = unbounded_channel::<Result<ActionResponseStream, Status>>();
 
-        // 2. For signaling completion
-        let (done_tx, done_rx) = oneshot::channel::<()>();
+        // 2. For the response stream, closed once the action is executed
+        let (stream_tx, stream_rx) = unbounded_channel::<Result<ActionResponseStream, Status>>();
 
         let request_body = request.into_inner();
         let context = request_body
@@ -38,30 +46,128 @@ impl ActionServiceGrpc for ActionsLauncher {
             .container_image
             .ok_or(Status::invalid_argument("Container image is missing"))?;
 
-        let mut action = self
+        // Older schedulers do not say which revision to check out
+        let checkout = match request_body.checkout {
+            Some(checkout) => {
+                Checkout::try_from(checkout).map_err(|e| Status::invalid_argument(e.to_string()))?
+            }
+            None => Checkout::default(),
+        };
+
+        // Registered first so that a cancellation requested while the action
+        // is set up is kept, the action is stopped once it is created
+        let action_id = request_body.action_id;
+        let cancellations = self.action_service.cancellations.clone();
+        let mut cancelled = cancellations.register(action_id);
+
+        let created = self
             .action_service
             .create(
                 container_image,
-                request_body.commands,
+                request_body.env,
+                request_body
+                    .services
+                    .into_iter()
+                    .map(Service::from)
+                    .collect(),
+                request_body.shell,
+                // Older schedulers only send the bare commands
+                if request_body.steps.is_empty() {
+                    request_body
+                        .commands
+                        .into_iter()
+                        .map(StepDefinition::from)
+                        .collect()
+                } else {
+                    request_body
+                        .steps
+                        .into_iter()
+                        .map(StepDefinition::from)
+                        .collect()
+                },
                 log_tx.clone(),
                 request_body.repo_url,
+                checkout,
                 request_body.action_id,
+                // The checkout counts towards the timeout of the action
+                request_body.timeout_seconds.map(Duration::from_secs),
             )
-            .await
-            .map_err(|_| Status::failed_precondition("Failed to create action"))?;
+            .await;
+        let mut action = match created {
+            Ok(action) => action,
+            // A checkout that overruns the timeout times the action out
+            Err(ActionTimeoutError(duration)) => {
+                cancellations.release(action_id);
+                OutputPipe::new(action_id, stream_tx).output_log(
+                    format!("Action timed out after {}s", duration.as_secs()),
+                    ActionStatus::TimedOut as i32,
+                    None,
+                );
+                let stream = UnboundedReceiverStream::new(stream_rx);
+                return Ok(Response::new(Box::pin(stream)));
+            }
+            Err(e) => {
+                cancellations.release(action_id);
+                return Err(Status::failed_precondition(format!(
+                    "Failed to create action: {}",
+                    e
+                )));
+            }
+        }
+        .with_artifacts(request_body.artifacts)
+        .with_cache(
+            request_body.cache.map(Cache::from),
+            self.action_service.cache_store.clone(),
+        );
 
-        // Spawn a task to execute the action and signal completion
+        // Spawn a task to execute the action and forward its logs
         tokio::spawn(async move {
-            let _ = action.execute().await;
+            let was_cancelled = {
+                let execution = action.execute();
+                tokio::pin!(execution);
+                loop {
+                    tokio::select! {
+                        Some(log) = log_rx.recv() => {
+                            let _ = stream_tx.send(log);
+                        }
+                        _ = &mut execution => break false,
+                        // Dropping the execution stops the steps from being awaited
+                        Ok(()) = &mut cancelled => break true,
+                    }
+                }
+            };
+            cancellations.release(action_id);
+            if was_cancelled {
+                info!("Cancelling action {}", action_id);
+                if let Err(e) = action.cancel().await {
+                    error!("Failed to remove container of action {}: {}", action_id, e);
+                }
+            }
+            // Flush the messages sent right before the action returned, such as
+            // its final result, so that they are not lost when the stream closes
+            while let Ok(log) = log_rx.try_recv() {
+                let _ = stream_tx.send(log);
+            }
             info!("Action executed");
-
-            // Signal completion then drop the sender
-            let _ = done_tx.send(());
         });
 
         // Convert receiver to stream
-        let log_stream = UnboundedReceiverStream::new(log_rx);
-        let stream = log_stream.take_until(done_rx);
+        let stream = UnboundedReceiverStream::new(stream_rx);
         Ok(Response::new(Box::pin(stream)))
     }
+
+    async fn cancel_action(
+        &self,
+        request: Request<CancelActionRequest>,
+    ) -> Result<Response<CancelActionResponse>, Status> {
+        let action_id = request.into_inner().action_id;
+        if !self.action_service.cancellations.cancel(action_id) {
+            return Err(Status::not_found(format!(
+                "Action {} is not running",
+                action_id
+            )));
+        }
+        info!("Cancellation requested for action {}", action_id);
+        Ok(Response::new(CancelActionResponse {}))
+    }
 }
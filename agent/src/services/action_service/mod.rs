@@ -1,15 +1,23 @@
-use std::{collections::HashMap, sync::Arc};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 use bollard::Docker;
-use tokio::sync::mpsc::UnboundedSender;
+use tokio::sync::{mpsc::UnboundedSender, oneshot};
 use tonic::Status;
+use tracing::warn;
 
 use crate::{
     brokers::{action_broker::ActionBroker, state_broker::StateBroker, Broker},
     models::{
         action::Action,
-        container::{Container, ContainerOperations},
+        cache::CacheStore,
+        checkout::Checkout,
+        container::{service::Service, Container, ContainerOperations},
         error::Error,
+        step::StepDefinition,
     },
     proto::ActionResponseStream,
 };
@@ -19,10 +27,46 @@ pub struct ActionService {
     actions: HashMap<u32, Action<Container>>,
     pub action_broker: ActionBroker,
     pub state_broker: Arc<StateBroker>,
+    pub cache_store: Arc<CacheStore>,
+    pub cancellations: Arc<Cancellations>,
+}
+
+/// Requests to stop the running actions, by action ID.
+#[derive(Default)]
+pub struct Cancellations {
+    senders: Mutex<HashMap<u32, oneshot::Sender<()>>>,
+}
+
+impl Cancellations {
+    /// Registers a running action, the returned receiver fires once it is
+    /// cancelled.
+    pub fn register(&self, action_id: u32) -> oneshot::Receiver<()> {
+        let (tx, rx) = oneshot::channel();
+        self.senders.lock().unwrap().insert(action_id, tx);
+        rx
+    }
+
+    /// Forgets an action that is over.
+    pub fn release(&self, action_id: u32) {
+        self.senders.lock().unwrap().remove(&action_id);
+    }
+
+    /// Asks a running action to stop, returns whether it was running.
+    pub fn cancel(&self, action_id: u32) -> bool {
+        match self.senders.lock().unwrap().remove(&action_id) {
+            // The action may have ended in the meantime
+            Some(sender) => sender.send(()).is_ok(),
+            None => false,
+        }
+    }
 }
 
 impl ActionService {
-    pub fn new(docker_client: Arc<Docker>, state_broker: Arc<StateBroker>) -> Self {
+    pub fn new(
+        docker_client: Arc<Docker>,
+        state_broker: Arc<StateBroker>,
+        cache_store: Arc<CacheStore>,
+    ) -> Self {
         let actions = HashMap::new();
         let action_broker = ActionBroker::new();
         Self {
@@ -30,18 +74,28 @@ impl ActionService {
             actions,
             action_broker,
             state_broker,
+            cache_store,
+            cancellations: Arc::new(Cancellations::default()),
         }
     }
 
+    #[allow(clippy::too_many_arguments)]
     pub async fn create(
         &self,
         image: String,
-        commands: Vec<String>,
+        env: HashMap<String, String>,
+        services: Vec<Service>,
+        shell: Vec<String>,
+        commands: Vec<StepDefinition>,
         log_input: UnboundedSender<Result<ActionResponseStream, Status>>,
         repo_url: String,
+        checkout: Checkout,
         action_id: u32,
+        timeout: Option<Duration>,
     ) -> Result<Action<Container>, Error> {
-        let container = Container::new(image, self.docker_client.clone());
+        let container = Container::new(image, env, self.docker_client.clone())
+            .with_services(services)
+            .with_shell(shell);
         container.start().await?;
         let action = Action::new(
             action_id,
@@ -50,8 +104,17 @@ impl ActionService {
             log_input,
             repo_url,
             self.state_broker.clone(),
-        );
-        action.setup_repository().await?;
+        )
+        .with_checkout(checkout)
+        .with_timeout(timeout);
+        if let Err(e) = action.setup_repository().await {
+            // The containers and the network of the action were started above,
+            // nothing else removes them once the action is not created
+            if let Err(removal) = action.cleanup().await {
+                warn!("Failed to clean up action {}: {}", action_id, removal);
+            }
+            return Err(e);
+        }
         self.action_broker
             .create_action_channel
             .send_event(action.clone())?;
@@ -85,3 +148,22 @@ impl ActionService {
             .ok_or(Error::ActionNotFound)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_cancellations_stop_registered_actions_only() {
+        let cancellations = Cancellations::default();
+        let cancelled = cancellations.register(1);
+        let released = cancellations.register(2);
+        cancellations.release(2);
+
+        assert!(cancellations.cancel(1));
+        assert!(cancelled.await.is_ok());
+        assert!(!cancellations.cancel(2));
+        assert!(released.await.is_err());
+        assert!(!cancellations.cancel(3));
+    }
+}
@@ -0,0 +1,3 @@
+-- Names of the actions, within the same pipeline, that must finish before this one is scheduled
+ALTER TABLE
+    "actions" ADD COLUMN "needs" TEXT[] NOT NULL DEFAULT '{}';
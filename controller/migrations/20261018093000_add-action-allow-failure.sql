@@ -0,0 +1,3 @@
+-- When set, a failure of the action does not prevent the actions that need it from running
+ALTER TABLE
+    "actions" ADD COLUMN "allow_failure" BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,3 @@
+-- Environment variables of the action, already merged with the pipeline level ones
+ALTER TABLE
+    "actions" ADD COLUMN "env" JSONB NOT NULL DEFAULT '{}';
@@ -0,0 +1,8 @@
+CREATE TABLE secrets (
+  id BIGSERIAL PRIMARY KEY,
+  repository_url VARCHAR(255) NOT NULL,
+  name VARCHAR(255) NOT NULL,
+  nonce BYTEA NOT NULL,
+  ciphertext BYTEA NOT NULL,
+  UNIQUE (repository_url, name)
+);
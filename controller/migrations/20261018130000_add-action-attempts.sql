@@ -0,0 +1,11 @@
+ALTER TABLE actions ADD COLUMN retry JSONB;
+ALTER TABLE actions ADD COLUMN attempt INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE logs ADD COLUMN attempt INTEGER NOT NULL DEFAULT 1;
+CREATE TABLE action_attempts (
+  id BIGSERIAL PRIMARY KEY,
+  action_id BIGINT NOT NULL REFERENCES actions(id) ON DELETE CASCADE ON UPDATE CASCADE,
+  attempt INTEGER NOT NULL,
+  status VARCHAR(255) NOT NULL,
+  exit_code INTEGER,
+  UNIQUE (action_id, attempt)
+);
@@ -0,0 +1,5 @@
+ALTER TABLE pipelines ADD COLUMN event VARCHAR(255);
+ALTER TABLE pipelines ADD COLUMN branch VARCHAR(255);
+ALTER TABLE pipelines ADD COLUMN tag VARCHAR(255);
+ALTER TABLE pipelines ADD COLUMN message TEXT;
+ALTER TABLE actions ADD COLUMN condition TEXT;
@@ -0,0 +1 @@
+ALTER TABLE actions ADD COLUMN artifacts TEXT[] NOT NULL DEFAULT '{}';
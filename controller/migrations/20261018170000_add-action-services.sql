@@ -0,0 +1,3 @@
+-- Containers started next to the action, by name
+ALTER TABLE
+    "actions" ADD COLUMN "services" JSONB NOT NULL DEFAULT '{}';
@@ -0,0 +1,6 @@
+-- Commands may be multi-line scripts of any length
+ALTER TABLE
+    "commands" ALTER COLUMN "command" TYPE TEXT;
+-- Program and arguments the commands are passed to, the agent default when empty
+ALTER TABLE
+    "actions" ADD COLUMN "shell" TEXT[] NOT NULL DEFAULT '{}';
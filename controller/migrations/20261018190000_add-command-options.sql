@@ -0,0 +1,9 @@
+-- Options of the steps written as objects in the manifest
+ALTER TABLE
+    "commands" ADD COLUMN "name" TEXT;
+ALTER TABLE
+    "commands" ADD COLUMN "working_directory" TEXT;
+ALTER TABLE
+    "commands" ADD COLUMN "env" JSONB NOT NULL DEFAULT '{}';
+ALTER TABLE
+    "commands" ADD COLUMN "continue_on_error" BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,11 @@
+CREATE TABLE steps (
+  id BIGSERIAL PRIMARY KEY,
+  action_id BIGINT NOT NULL REFERENCES actions(id) ON DELETE CASCADE ON UPDATE CASCADE,
+  attempt INTEGER NOT NULL,
+  index INTEGER NOT NULL,
+  name TEXT NOT NULL,
+  exit_code INTEGER,
+  started_at TIMESTAMPTZ NOT NULL,
+  finished_at TIMESTAMPTZ,
+  UNIQUE (action_id, attempt, index)
+);
@@ -0,0 +1,2 @@
+ALTER TABLE pipelines ADD COLUMN rerun_of BIGINT REFERENCES pipelines(id) ON DELETE SET NULL;
+ALTER TABLE actions ADD COLUMN reused_from BIGINT REFERENCES actions(id) ON DELETE SET NULL;
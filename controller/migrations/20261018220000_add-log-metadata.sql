@@ -0,0 +1,15 @@
+ALTER TABLE logs ADD COLUMN seq BIGINT;
+ALTER TABLE logs ADD COLUMN ts TIMESTAMPTZ NOT NULL DEFAULT now();
+ALTER TABLE logs ADD COLUMN stream VARCHAR(16) NOT NULL DEFAULT 'system';
+ALTER TABLE logs ADD COLUMN step_index INTEGER;
+
+-- Existing lines keep their insertion order
+UPDATE logs SET seq = numbered.seq
+FROM (SELECT id, ROW_NUMBER() OVER (PARTITION BY action_id ORDER BY id) AS seq FROM logs) AS numbered
+WHERE logs.id = numbered.id;
+ALTER TABLE logs ALTER COLUMN seq SET NOT NULL;
+CREATE UNIQUE INDEX logs_action_id_seq ON logs (action_id, seq);
+
+-- Last sequence number given to a line of the action
+ALTER TABLE actions ADD COLUMN log_seq BIGINT NOT NULL DEFAULT 0;
+UPDATE actions SET log_seq = (SELECT COALESCE(MAX(seq), 0) FROM logs WHERE logs.action_id = actions.id);
@@ -0,0 +1,13 @@
+ALTER TABLE pipelines ADD COLUMN commit_sha VARCHAR(64);
+ALTER TABLE pipelines ADD COLUMN ref VARCHAR(255);
+ALTER TABLE pipelines ADD COLUMN pr_number INTEGER;
+ALTER TABLE pipelines ADD COLUMN author VARCHAR(255);
+ALTER TABLE pipelines ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT now();
+ALTER TABLE pipelines ADD COLUMN started_at TIMESTAMPTZ;
+ALTER TABLE pipelines ADD COLUMN finished_at TIMESTAMPTZ;
+CREATE INDEX pipelines_created_at ON pipelines (created_at);
+CREATE INDEX pipelines_commit_sha ON pipelines (commit_sha);
+
+ALTER TABLE actions ADD COLUMN created_at TIMESTAMPTZ NOT NULL DEFAULT now();
+ALTER TABLE actions ADD COLUMN started_at TIMESTAMPTZ;
+ALTER TABLE actions ADD COLUMN finished_at TIMESTAMPTZ;
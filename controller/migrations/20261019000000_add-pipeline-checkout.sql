@@ -0,0 +1,2 @@
+ALTER TABLE pipelines ADD COLUMN checkout_depth INTEGER NOT NULL DEFAULT 1;
+ALTER TABLE pipelines ADD COLUMN checkout_submodules BOOLEAN NOT NULL DEFAULT FALSE;
@@ -0,0 +1,8 @@
+CREATE TABLE repository_credentials (
+  id BIGSERIAL PRIMARY KEY,
+  repository_url VARCHAR(255) NOT NULL UNIQUE,
+  kind VARCHAR(32) NOT NULL,
+  username VARCHAR(255),
+  nonce BYTEA NOT NULL,
+  ciphertext BYTEA NOT NULL
+);
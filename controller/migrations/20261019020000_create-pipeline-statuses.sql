@@ -0,0 +1,15 @@
+CREATE INDEX actions_pipeline_id ON actions (pipeline_id);
+CREATE INDEX pipelines_repository_url ON pipelines (repository_url, id);
+
+-- Status of each pipeline with actions, derived from the status of its actions
+CREATE VIEW pipeline_statuses AS
+SELECT pipeline_id,
+    CASE
+        WHEN bool_and(status = 'ACTION_STATUS_PENDING' OR reused_from IS NOT NULL) THEN 'queued'
+        WHEN bool_or(status IN ('ACTION_STATUS_PENDING', 'ACTION_STATUS_SCHEDULED', 'ACTION_STATUS_RUNNING')) THEN 'running'
+        WHEN bool_or(status = 'ACTION_STATUS_CANCELLED') THEN 'cancelled'
+        WHEN bool_or(status IN ('ACTION_STATUS_ERROR', 'ACTION_STATUS_TIMED_OUT') AND NOT allow_failure) THEN 'failed'
+        ELSE 'success'
+    END AS status
+FROM actions
+GROUP BY pipeline_id;
@@ -0,0 +1,3 @@
+ALTER TABLE pipelines ADD COLUMN status VARCHAR(32) NOT NULL DEFAULT 'queued';
+UPDATE pipelines SET status = s.status FROM pipeline_statuses s WHERE s.pipeline_id = pipelines.id;
+CREATE INDEX pipelines_status ON pipelines (status, id);
@@ -0,0 +1,2 @@
+-- Last status of the pipeline reported to the forge of its repository
+ALTER TABLE pipelines ADD COLUMN reported_status VARCHAR(32);
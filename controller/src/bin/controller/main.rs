@@ -2,15 +2,16 @@ use actix_cors::Cors;
 use actix_web::{web::Data, App, HttpServer};
 use clap::Parser;
 use controller::application::app_context::AppContext;
+use controller::application::http::artifact::router::configure as configure_artifact_routes;
 use controller::application::http::pipeline::router::configure as configure_pipeline_routes;
-use controller::config::Config;
 use controller::application::http::release::router::configure as configure_release_routes;
+use controller::application::http::secret::router::configure as configure_secret_routes;
+use controller::application::http::webhook::router::configure as configure_webhook_routes;
+use controller::config::Config;
 use controller::{docs, health};
 use dotenv::dotenv;
 use tracing::info;
 
-
-
 #[actix_web::main]
 async fn main() -> std::io::Result<()> {
     // Load .env file into OS environment variables before parsing clap args.
@@ -20,17 +21,26 @@ async fn main() -> std::io::Result<()> {
     // Parse command line arguments and environment variables into Args struct
     let args = Config::parse();
 
-    // Debug print the provided arguments for verification
-    println!("Parsed args: {:?}", args);
+    // Print the provided arguments for verification, without the keys and tokens
+    println!("Parsed args: {}", args);
+
+    // Statuses are only reported to GitHub with a token
+    let status_reporter = AppContext::status_reporter(&args);
 
     // HTTP server binding address (host:port)
     let addr_in: String = args.http;
 
     // Initialize application context with database and gRPC service configurations
-    let app_context: AppContext =
-        AppContext::initialize(&args.database_url, &args.grpc, &args.release_agent)
-            .await
-            .expect("REASON");
+    let app_context: AppContext = AppContext::initialize(
+        &args.database_url,
+        &args.grpc,
+        &args.release_agent,
+        args.secrets_key.as_deref(),
+        &args.artifacts_dir,
+        status_reporter,
+    )
+    .await
+    .expect("REASON");
 
     // Initialize tracing subscriber for logging
     tracing_subscriber::fmt::init();
@@ -53,6 +63,9 @@ async fn main() -> std::io::Result<()> {
             .app_data(Data::new(app_context.clone()))
             .configure(configure_pipeline_routes)
             .configure(configure_release_routes)
+            .configure(configure_secret_routes)
+            .configure(configure_webhook_routes)
+            .configure(configure_artifact_routes)
             // Add documentation and health check endpoints
             .service(docs::doc)
             .service(docs::openapi)
@@ -4,12 +4,16 @@ pub mod ports;
 pub mod services;
 
 use crate::application::app_context::AppContext;
+use crate::application::http::artifact::router::configure as configure_artifact_routes;
 use crate::application::http::pipeline::router::configure as configure_pipeline_routes;
 use crate::application::http::release::router::configure as configure_release_routes;
+use crate::application::http::secret::router::configure as configure_secret_routes;
+use crate::application::http::webhook::router::configure as configure_webhook_routes;
 
 use crate::config::Config;
 use crate::domain::command::entities::command::CommandError;
 use crate::domain::scheduler::entities::scheduler::SchedulerError;
+use crate::domain::secrets::entities::SecretError;
 use crate::parser::pipe_parser::ParsingError;
 use crate::{docs, health};
 use actix_cors::Cors;
@@ -32,6 +36,7 @@ pub enum AppError {
     SchedulerConnectionError,
     GrpcConnectionError(tonic::transport::Error),
     ReleaseConnectionError(tonic::transport::Error),
+    SecretError(SecretError),
 }
 
 impl std::fmt::Display for AppError {
@@ -48,6 +53,7 @@ impl std::fmt::Display for AppError {
             AppError::ReleaseConnectionError(error) => {
                 write!(f, "Release connection error: {}", error)
             }
+            AppError::SecretError(error) => write!(f, "Secret store error: {}", error),
         }
     }
 }
@@ -152,6 +158,9 @@ impl App {
             &config.database_url,
             &config.grpc,
             &self.config.release_agent,
+            self.config.secrets_key.as_deref(),
+            &self.config.artifacts_dir,
+            AppContext::status_reporter(&config),
         )
         .await?;
         // Start HTTP server with CORS, logging middleware, and configured routes
@@ -170,6 +179,9 @@ impl App {
                 .app_data(Data::new(app_context.clone()))
                 .configure(configure_pipeline_routes)
                 .configure(configure_release_routes)
+                .configure(configure_secret_routes)
+                .configure(configure_webhook_routes)
+                .configure(configure_artifact_routes)
                 // Add documentation and health check endpoints
                 .service(docs::doc)
                 .service(docs::openapi)
@@ -4,19 +4,37 @@ use tokio::time::{sleep, Duration};
 use tracing::error;
 
 use crate::{
-    application::{services::release_service::ReleaseServiceImpl, AppError},
+    application::{
+        ports::webhook_service::WebhookService,
+        services::{
+            artifact_service::ArtifactServiceImpl,
+            log_hub::LogHub,
+            log_service::LogServiceImpl,
+            release_service::{DefaultReleaseServiceImpl, ReleaseServiceImpl},
+            secret_service::SecretServiceImpl,
+            webhook_service::WebhookServiceImpl,
+        },
+        AppError,
+    },
+    config::Config,
     infrastructure::{
+        crypto::aes_gcm_cipher::AesGcmSecretCipher,
         db::postgres::Postgres,
         grpc::{
             grpc_release_agent_client::GrpcReleaseAgentClient,
             grpc_scheduler_client::GrpcSchedulerClient,
         },
+        reporting::github_status_reporter::GithubStatusReporter,
         repositories::{
             action_repository::PostgresActionRepository,
             command_repository::PostgresCommandRepository, log_repository::PostgresLogRepository,
             pipeline_repository::PostgresPipelineRepository,
             release_repository::PostgresReleaseRepository,
+            secret_repository::PostgresSecretRepository,
+            webhook_repository::PostgresWebhookRepository,
         },
+        storage::local_artifact_storage::LocalArtifactStorage,
+        webhooks::http_webhook_sender::HttpWebhookSender,
     },
 };
 
@@ -42,6 +60,14 @@ pub struct AppContext {
                 >,
                 GrpcSchedulerClient,
                 PostgresPipelineRepository,
+                SecretServiceImpl<PostgresSecretRepository, AesGcmSecretCipher>,
+                ArtifactServiceImpl<LocalArtifactStorage>,
+                WebhookServiceImpl<
+                    PostgresWebhookRepository,
+                    AesGcmSecretCipher,
+                    HttpWebhookSender,
+                >,
+                GithubStatusReporter,
             >,
         >,
     >,
@@ -49,26 +75,55 @@ pub struct AppContext {
         ActionServiceImpl<PostgresActionRepository, CommandServiceImpl<PostgresCommandRepository>>,
     >,
     pub scheduler_service: Arc<
-        Mutex<
-            SchedulerServiceImpl<
-                ActionServiceImpl<
-                    PostgresActionRepository,
-                    CommandServiceImpl<PostgresCommandRepository>,
-                >,
-                GrpcSchedulerClient,
-                PostgresPipelineRepository,
+        SchedulerServiceImpl<
+            ActionServiceImpl<
+                PostgresActionRepository,
+                CommandServiceImpl<PostgresCommandRepository>,
             >,
+            GrpcSchedulerClient,
+            PostgresPipelineRepository,
+            SecretServiceImpl<PostgresSecretRepository, AesGcmSecretCipher>,
+            ArtifactServiceImpl<LocalArtifactStorage>,
+            WebhookServiceImpl<PostgresWebhookRepository, AesGcmSecretCipher, HttpWebhookSender>,
+            GithubStatusReporter,
         >,
     >,
-    pub release_service: Arc<ReleaseServiceImpl<GrpcReleaseAgentClient, PostgresReleaseRepository>>,
+    pub release_service: Arc<DefaultReleaseServiceImpl>,
+    pub secret_service: Arc<SecretServiceImpl<PostgresSecretRepository, AesGcmSecretCipher>>,
+    pub artifact_service: Arc<ArtifactServiceImpl<LocalArtifactStorage>>,
+    pub log_service: Arc<LogServiceImpl<PostgresLogRepository>>,
+    pub webhook_service:
+        Arc<WebhookServiceImpl<PostgresWebhookRepository, AesGcmSecretCipher, HttpWebhookSender>>,
 }
 
 impl AppContext {
+    /// Reporter of the pipeline statuses on GitHub, when a token is set.
+    pub fn status_reporter(config: &Config) -> Option<GithubStatusReporter> {
+        config.github_token.as_ref().map(|token| {
+            GithubStatusReporter::new(
+                config.github_api_url.clone(),
+                config.github_host.clone(),
+                token.clone(),
+                config.web_url.clone(),
+            )
+        })
+    }
+
     pub async fn initialize(
         database_url: &str,
         grpc_url: &str,
         release_agent_url: &str,
+        secrets_key: Option<&str>,
+        artifacts_dir: &str,
+        status_reporter: Option<GithubStatusReporter>,
     ) -> Result<Self, AppError> {
+        // Fail early on a bad master key rather than on the first secret access.
+        // Without a key, secrets cannot be stored nor resolved
+        let secret_cipher = Arc::new(match secrets_key {
+            Some(key) => AesGcmSecretCipher::new(key).map_err(AppError::SecretError)?,
+            None => AesGcmSecretCipher::without_key(),
+        });
+
         // Initialize Postgres connection pool using provided database URL
         let postgres = Postgres::new(database_url).await?;
         let postgres = Arc::new(postgres);
@@ -139,15 +194,50 @@ impl AppContext {
 
         let log_repository = Arc::new(PostgresLogRepository::new(postgres.clone()));
 
-        let scheduler_service = Arc::new(Mutex::new(SchedulerServiceImpl::new(
+        let secret_repository = Arc::new(PostgresSecretRepository::new(postgres.clone()));
+
+        let secret_service = Arc::new(SecretServiceImpl::new(
+            secret_repository,
+            secret_cipher.clone(),
+        ));
+
+        let webhook_repository = Arc::new(PostgresWebhookRepository::new(postgres.clone()));
+
+        let webhook_service = Arc::new(WebhookServiceImpl::new(
+            webhook_repository,
+            secret_cipher,
+            Arc::new(HttpWebhookSender::new()),
+        ));
+
+        // The retries of the deliveries stop with the controller
+        webhook_service.resume_deliveries().await;
+
+        let artifact_storage = Arc::new(LocalArtifactStorage::new(artifacts_dir));
+
+        let artifact_service = Arc::new(ArtifactServiceImpl::new(artifact_storage));
+
+        let log_hub = Arc::new(LogHub::new());
+
+        let log_service = Arc::new(LogServiceImpl::new(log_repository.clone(), log_hub.clone()));
+
+        // Not behind a lock: pipelines run concurrently and can be cancelled
+        // while they run
+        let scheduler_service = Arc::new(SchedulerServiceImpl::new(
             action_service.clone(),
             scheduler_client,
             pipeline_repository.clone(),
-        )));
+            secret_service.clone(),
+            artifact_service.clone(),
+            log_hub,
+            webhook_service.clone(),
+            status_reporter.map(Arc::new),
+        ));
 
         let release_service = Arc::new(ReleaseServiceImpl::new(
             release_agent_client,
             release_repository,
+            secret_service.clone(),
+            webhook_service.clone(),
         ));
 
         let pipeline_service = Arc::new(PipelineServiceImpl::new(
@@ -162,6 +252,10 @@ impl AppContext {
             action_service,
             scheduler_service,
             release_service,
+            secret_service,
+            artifact_service,
+            log_service,
+            webhook_service,
         })
     }
 }
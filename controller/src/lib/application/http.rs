@@ -1,2 +1,5 @@
+pub mod artifact;
 pub mod pipeline;
 pub mod release;
+pub mod secret;
+pub mod webhook;
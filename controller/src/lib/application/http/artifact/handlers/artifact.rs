@@ -0,0 +1,71 @@
+use actix_web::{get, http::header, web, HttpResponse, Responder};
+use futures::StreamExt;
+use serde::Serialize;
+use tracing::error;
+
+use crate::{
+    application::{
+        app_context::AppContext,
+        ports::{action_service::ActionService, artifact_service::ArtifactService},
+    },
+    domain::{action::entities::action::ActionError, artifacts::entities::ArtifactError},
+};
+
+#[derive(Debug, Serialize)]
+pub struct ArtifactResponse {
+    pub status: String,
+    pub message: String,
+}
+
+fn not_found(message: &str) -> HttpResponse {
+    HttpResponse::NotFound().json(ArtifactResponse {
+        status: "error".to_string(),
+        message: message.to_string(),
+    })
+}
+
+fn internal_error() -> HttpResponse {
+    HttpResponse::InternalServerError().json(ArtifactResponse {
+        status: "error".to_string(),
+        message: "Internal server error".to_string(),
+    })
+}
+
+/// Downloads the artifacts of the last attempt of an action as a tar archive.
+#[get("/pipeline/{id}/actions/{action_id}/artifacts")]
+pub async fn get_artifacts(
+    path: web::Path<(i64, i64)>,
+    ctx: web::Data<AppContext>,
+) -> impl Responder {
+    let (pipeline_id, action_id) = path.into_inner();
+
+    let action = match ctx.action_service.find_by_id(action_id).await {
+        Ok(action) if action.pipeline_id == pipeline_id => action,
+        Ok(_) | Err(ActionError::DatabaseError(sqlx::Error::RowNotFound)) => {
+            return not_found("Action not found");
+        }
+        Err(e) => {
+            error!("Failed to find action {}: {}", action_id, e);
+            return internal_error();
+        }
+    };
+
+    match ctx
+        .artifact_service
+        .open_archive(action.reused_from.unwrap_or(action.id), action.attempt)
+        .await
+    {
+        Ok(archive) => HttpResponse::Ok()
+            .content_type("application/x-tar")
+            .insert_header((
+                header::CONTENT_DISPOSITION,
+                format!("attachment; filename=\"artifacts-{}.tar\"", action.id),
+            ))
+            .streaming(archive.map(|chunk| chunk.map(web::Bytes::from))),
+        Err(ArtifactError::NotFound) => not_found("Artifacts not found"),
+        Err(e) => {
+            error!("Failed to open artifacts of action {}: {}", action_id, e);
+            internal_error()
+        }
+    }
+}
@@ -0,0 +1,6 @@
+use crate::application::http::artifact::handlers::artifact::get_artifacts;
+use actix_web::web::ServiceConfig;
+
+pub fn configure(cfg: &mut ServiceConfig) {
+    cfg.service(get_artifacts);
+}
@@ -0,0 +1,369 @@
+use std::{sync::Arc, time::Duration};
+
+use actix_web::{get, http::header, web, HttpRequest, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tracing::{error, info};
+
+use crate::application::app_context::AppContext;
+use crate::application::ports::{action_service::ActionService, log_service::LogService};
+use crate::domain::action::entities::action::{Action, ActionError};
+use crate::domain::log::entities::log::{Log, LogFilter};
+
+/// Interval of the comments keeping an idle stream open, and of the checks
+/// of actions that end without being run.
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+
+/// Lines read from the database at once when replaying or downloading logs.
+const LOG_BATCH: i64 = 1000;
+
+/// Largest page the log endpoint returns.
+const MAX_LOG_PAGE: i64 = 10_000;
+
+#[derive(Deserialize)]
+struct LogStreamQueryParams {
+    offset: Option<i64>,
+}
+
+#[derive(Deserialize)]
+struct LogQueryParams {
+    after_seq: Option<i64>,
+    limit: Option<i64>,
+    grep: Option<String>,
+}
+
+/// A page of the logs of an action.
+#[derive(Serialize)]
+pub struct LogPage {
+    pub logs: Vec<Log>,
+    /// Where the next page starts, when this one is full
+    pub next_after_seq: Option<i64>,
+}
+
+impl LogPage {
+    pub fn new(logs: Vec<Log>, limit: i64) -> Self {
+        let next_after_seq = match logs.last() {
+            Some(last) if logs.len() as i64 >= limit => Some(last.seq),
+            _ => None,
+        };
+        Self {
+            logs,
+            next_after_seq,
+        }
+    }
+}
+
+/// Formats the line `seq` of the logs of an action as a server-sent event. A
+/// line spanning several lines is sent as several `data` fields.
+pub fn sse_event(seq: i64, data: &str) -> String {
+    let mut event = format!("id: {}\nevent: log\n", seq);
+    for line in data.split('\n') {
+        event.push_str("data: ");
+        event.push_str(line.strip_suffix('\r').unwrap_or(line));
+        event.push('\n');
+    }
+    event.push('\n');
+    event
+}
+
+/// Position of a client in the logs of an action.
+pub struct LogCursor {
+    last_seq: i64,
+}
+
+impl LogCursor {
+    /// Starts right after the line `after_seq`, 0 being before the first line.
+    pub fn new(after_seq: i64) -> Self {
+        Self {
+            last_seq: after_seq,
+        }
+    }
+
+    /// Moves past a line, returning its event unless it was sent already.
+    pub fn advance(&mut self, log: &Log) -> Option<String> {
+        if log.seq <= self.last_seq {
+            return None;
+        }
+        self.last_seq = log.seq;
+        Some(sse_event(log.seq, &log.data))
+    }
+
+    /// Sequence number of the last line moved past.
+    pub fn last_seq(&self) -> i64 {
+        self.last_seq
+    }
+}
+
+/// Finds an action of a pipeline, or the response to send when there is none.
+async fn find_action(
+    ctx: &AppContext,
+    pipeline_id: i64,
+    action_id: i64,
+) -> Result<Action, HttpResponse> {
+    match ctx.action_service.find_by_id(action_id).await {
+        Ok(action) if action.pipeline_id == pipeline_id => Ok(action),
+        Ok(_) | Err(ActionError::DatabaseError(sqlx::Error::RowNotFound)) => {
+            Err(HttpResponse::NotFound().finish())
+        }
+        Err(e) => {
+            error!("Failed to find action {}: {}", action_id, e);
+            Err(HttpResponse::InternalServerError().finish())
+        }
+    }
+}
+
+/// Returns a page of the logs of an action: at most `limit` lines after the
+/// line `after_seq`, only those containing `grep` when given.
+#[get("/pipeline/{id}/actions/{action_id}/logs")]
+pub async fn get_logs(
+    path: web::Path<(i64, i64)>,
+    ctx: web::Data<AppContext>,
+    query: web::Query<LogQueryParams>,
+) -> impl Responder {
+    let (pipeline_id, action_id) = path.into_inner();
+    let action = match find_action(&ctx, pipeline_id, action_id).await {
+        Ok(action) => action,
+        Err(response) => return response,
+    };
+
+    let query = query.into_inner();
+    let filter = LogFilter {
+        after_seq: query.after_seq.unwrap_or(0).max(0),
+        limit: query.limit.unwrap_or(LOG_BATCH).clamp(1, MAX_LOG_PAGE),
+        grep: query.grep.filter(|grep| !grep.is_empty()),
+    };
+    let source_id = action.reused_from.unwrap_or(action.id);
+    match ctx.log_service.find_page(source_id, &filter).await {
+        Ok(logs) => HttpResponse::Ok().json(LogPage::new(logs, filter.limit)),
+        Err(e) => {
+            error!("Failed to fetch logs of action {}: {}", action_id, e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+/// Downloads the whole logs of an action as plain text, one line per log line.
+#[get("/pipeline/{id}/actions/{action_id}/logs/raw")]
+pub async fn download_logs(
+    path: web::Path<(i64, i64)>,
+    ctx: web::Data<AppContext>,
+) -> impl Responder {
+    let (pipeline_id, action_id) = path.into_inner();
+    let action = match find_action(&ctx, pipeline_id, action_id).await {
+        Ok(action) => action,
+        Err(response) => return response,
+    };
+
+    let ctx = ctx.into_inner();
+    let source_id = action.reused_from.unwrap_or(action.id);
+    let stream = async_stream::stream! {
+        let mut filter = LogFilter {
+            after_seq: 0,
+            limit: LOG_BATCH,
+            grep: None,
+        };
+        loop {
+            let logs = match ctx.log_service.find_page(source_id, &filter).await {
+                Ok(logs) => logs,
+                Err(e) => {
+                    error!("Failed to fetch logs of action {}: {}", action_id, e);
+                    yield Err(actix_web::error::ErrorInternalServerError(e));
+                    break;
+                }
+            };
+            let mut chunk = String::new();
+            for log in &logs {
+                chunk.push_str(&log.data);
+                chunk.push('\n');
+            }
+            yield Ok::<_, actix_web::Error>(web::Bytes::from(chunk));
+            match logs.last() {
+                Some(last) if logs.len() as i64 == filter.limit => filter.after_seq = last.seq,
+                _ => break,
+            }
+        }
+    };
+
+    HttpResponse::Ok()
+        .content_type("text/plain; charset=utf-8")
+        .insert_header((
+            header::CONTENT_DISPOSITION,
+            format!("attachment; filename=\"action-{}.log\"", action_id),
+        ))
+        .streaming(stream)
+}
+
+/// Follows the lines of an action for a stream, releasing the subscription
+/// once the stream is dropped, when it ends as well as when its client goes
+/// away.
+pub struct Subscription<L: LogService + ?Sized> {
+    log_service: Arc<L>,
+    action_id: i64,
+    receiver: Option<broadcast::Receiver<Log>>,
+}
+
+impl<L: LogService + ?Sized> Subscription<L> {
+    pub fn new(log_service: Arc<L>, action_id: i64) -> Self {
+        let receiver = Some(log_service.subscribe(action_id));
+        Self {
+            log_service,
+            action_id,
+            receiver,
+        }
+    }
+
+    pub async fn recv(&mut self) -> Result<Log, RecvError> {
+        match &mut self.receiver {
+            Some(receiver) => receiver.recv().await,
+            None => Err(RecvError::Closed),
+        }
+    }
+}
+
+impl<L: LogService + ?Sized> Drop for Subscription<L> {
+    fn drop(&mut self) {
+        // The receiver must be gone for the action to be released
+        drop(self.receiver.take());
+        self.log_service.release(self.action_id);
+    }
+}
+
+/// Streams the logs of an action as server-sent events: the stored lines
+/// after the line `offset` (or after the `Last-Event-ID` the client got last),
+/// then the new lines as they are received, until the action finishes.
+#[get("/pipeline/{id}/actions/{action_id}/logs/stream")]
+pub async fn stream_logs(
+    path: web::Path<(i64, i64)>,
+    ctx: web::Data<AppContext>,
+    query: web::Query<LogStreamQueryParams>,
+    req: HttpRequest,
+) -> impl Responder {
+    let (pipeline_id, action_id) = path.into_inner();
+    let action = match find_action(&ctx, pipeline_id, action_id).await {
+        Ok(action) => action,
+        Err(response) => return response,
+    };
+
+    // The id of an event is the sequence number of its line, the stream
+    // resumes right after it
+    let last_event_id = req
+        .headers()
+        .get("Last-Event-ID")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse::<i64>().ok());
+    let after_seq = last_event_id.or(query.offset).unwrap_or(0).max(0);
+    info!(
+        "Streaming logs of action {} after line {}",
+        action_id, after_seq
+    );
+
+    // Subscribe before reading the stored lines so that none is missed, an
+    // action reusing an earlier result has nothing new to send
+    let ctx = ctx.into_inner();
+    let source_id = action.reused_from.unwrap_or(action.id);
+    let subscription = match action.reused_from {
+        Some(_) => None,
+        None => Some(Subscription::new(ctx.log_service.clone(), action_id)),
+    };
+    let mut filter = LogFilter {
+        after_seq,
+        limit: LOG_BATCH,
+        grep: None,
+    };
+    let first = match ctx.log_service.find_page(source_id, &filter).await {
+        Ok(first) => first,
+        Err(e) => {
+            error!("Failed to fetch logs of action {}: {}", action_id, e);
+            return HttpResponse::InternalServerError().finish();
+        }
+    };
+
+    let stream = async_stream::stream! {
+        let mut cursor = LogCursor::new(after_seq);
+        let mut page = first;
+        loop {
+            for log in &page {
+                if let Some(event) = cursor.advance(log) {
+                    yield Ok::<_, actix_web::Error>(web::Bytes::from(event));
+                }
+            }
+            if (page.len() as i64) < LOG_BATCH {
+                break;
+            }
+            filter.after_seq = cursor.last_seq();
+            page = match ctx.log_service.find_page(source_id, &filter).await {
+                Ok(page) => page,
+                Err(e) => {
+                    error!("Failed to fetch logs of action {}: {}", action_id, e);
+                    break;
+                }
+            };
+        }
+
+        if let Some(mut subscription) = subscription {
+            let mut keep_alive = tokio::time::interval(KEEP_ALIVE);
+            keep_alive.tick().await;
+            loop {
+                // Lines are read back from the database when some were
+                // missed, and once the action is over
+                let catch_up = tokio::select! {
+                    received = subscription.recv() => match received {
+                        Ok(log) => {
+                            if let Some(event) = cursor.advance(&log) {
+                                yield Ok(web::Bytes::from(event));
+                            }
+                            continue;
+                        }
+                        Err(RecvError::Lagged(_)) => false,
+                        Err(RecvError::Closed) => true,
+                    },
+                    _ = keep_alive.tick() => {
+                        yield Ok(web::Bytes::from_static(b": keep-alive\n\n"));
+                        // An action cancelled or skipped before it ran is
+                        // never closed
+                        let finished = match ctx.action_service.find_by_id(action_id).await {
+                            Ok(action) => action.status.is_terminal(),
+                            Err(_) => true,
+                        };
+                        if !finished || ctx.log_service.is_running(action_id) {
+                            continue;
+                        }
+                        true
+                    }
+                };
+
+                let mut failed = false;
+                loop {
+                    filter.after_seq = cursor.last_seq();
+                    match ctx.log_service.find_page(action_id, &filter).await {
+                        Ok(missed) => {
+                            for log in &missed {
+                                if let Some(event) = cursor.advance(log) {
+                                    yield Ok(web::Bytes::from(event));
+                                }
+                            }
+                            if (missed.len() as i64) < LOG_BATCH {
+                                break;
+                            }
+                        }
+                        Err(e) => {
+                            error!("Failed to fetch logs of action {}: {}", action_id, e);
+                            failed = true;
+                            break;
+                        }
+                    }
+                }
+                if catch_up || failed {
+                    break;
+                }
+            }
+        }
+
+        yield Ok(web::Bytes::from_static(b"event: end\ndata:\n\n"));
+    };
+
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        .streaming(stream)
+}
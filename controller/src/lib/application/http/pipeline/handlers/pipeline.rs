@@ -2,6 +2,7 @@ use actix_multipart::form::{tempfile::TempFile, text::Text as MpText, MultipartF
 use actix_web::{get, post, web, HttpResponse, Responder};
 use serde::Deserialize;
 use std::io::Read;
+use time::OffsetDateTime;
 use tracing::{error, info};
 
 use crate::application::app_context::AppContext;
@@ -11,15 +12,31 @@ use crate::parser::pipe_parser::{
 };
 
 use crate::domain::pipeline::entities::pipeline::{
-    ActionManifest as DomainActionManifest, ActionsMap, Configuration,
-    ManifestPipeline as DomainManifestPipeline, PipelineError,
+    is_commit_hash, ActionManifest as DomainActionManifest, ActionsMap, Configuration,
+    ManifestPipeline as DomainManifestPipeline, PipelineError, PipelineFilter, PipelineStatus,
+    TriggerContext,
 };
 
+/// Pipelines listed at once when the request sets no limit.
+const PIPELINE_PAGE: usize = 50;
+
+/// Upper bound of the limit of a page of pipelines.
+const MAX_PIPELINE_PAGE: usize = 200;
+
 #[derive(Debug, MultipartForm)]
 struct UploadPipelineForm {
     #[multipart(rename = "body")]
     file: TempFile,
     repo_url: MpText<String>,
+    event: Option<MpText<String>>,
+    branch: Option<MpText<String>>,
+    tag: Option<MpText<String>>,
+    message: Option<MpText<String>>,
+    commit_sha: Option<MpText<String>>,
+    #[multipart(rename = "ref")]
+    git_ref: Option<MpText<String>>,
+    pr_number: Option<MpText<i32>>,
+    author: Option<MpText<String>>,
 }
 
 #[derive(Deserialize)]
@@ -32,14 +49,56 @@ struct PipelineQueryParams {
     verbose: Option<bool>,
 }
 
+#[derive(Deserialize)]
+struct PipelineListQueryParams {
+    repo: Option<String>,
+    commit_sha: Option<String>,
+    #[serde(rename = "ref")]
+    git_ref: Option<String>,
+    branch: Option<String>,
+    tag: Option<String>,
+    event: Option<String>,
+    author: Option<String>,
+    pr_number: Option<i32>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    since: Option<OffsetDateTime>,
+    #[serde(default, with = "time::serde::rfc3339::option")]
+    until: Option<OffsetDateTime>,
+    status: Option<PipelineStatus>,
+    limit: Option<usize>,
+    /// `next_cursor` of the previous page
+    cursor: Option<i64>,
+}
+
 #[get("/pipeline")]
 pub async fn get_pipelines(
     ctx: web::Data<AppContext>,
-    query: web::Query<PipelineQueryParams>,
+    query: web::Query<PipelineListQueryParams>,
 ) -> impl Responder {
-    let verbose = query.verbose.unwrap_or(false);
-    match ctx.pipeline_service.find_all(verbose).await {
-        Ok(pipelines) => HttpResponse::Ok().json(pipelines),
+    let query = query.into_inner();
+    let filter = PipelineFilter {
+        repository_url: query.repo,
+        commit_sha: query.commit_sha,
+        git_ref: query.git_ref,
+        branch: query.branch,
+        tag: query.tag,
+        event: query.event,
+        author: query.author,
+        pr_number: query.pr_number,
+        status: query.status,
+        since: query.since,
+        until: query.until,
+    };
+    let limit = query
+        .limit
+        .unwrap_or(PIPELINE_PAGE)
+        .clamp(1, MAX_PIPELINE_PAGE);
+    match ctx
+        .pipeline_service
+        .find_page(&filter, query.cursor, limit)
+        .await
+    {
+        Ok(page) => HttpResponse::Ok().json(page),
         Err(e) => {
             error!("Error fetching pipelines: {:?}", e);
             HttpResponse::InternalServerError().finish()
@@ -90,6 +149,23 @@ pub async fn create_pipeline(
         form.repo_url.as_str()
     );
     let repo_url = form.repo_url.to_string();
+    if form
+        .commit_sha
+        .as_ref()
+        .is_some_and(|sha| !is_commit_hash(sha))
+    {
+        return HttpResponse::UnprocessableEntity().body("Invalid commit_sha");
+    }
+    let trigger = TriggerContext {
+        event: form.event.map(MpText::into_inner),
+        branch: form.branch.map(MpText::into_inner),
+        tag: form.tag.map(MpText::into_inner),
+        message: form.message.map(MpText::into_inner),
+        commit_sha: form.commit_sha.map(MpText::into_inner),
+        git_ref: form.git_ref.map(MpText::into_inner),
+        pr_number: form.pr_number.map(MpText::into_inner),
+        author: form.author.map(MpText::into_inner),
+    };
     let mut fd = form.file.file;
     let mut buffer = String::new();
     if let Err(e) = fd.read_to_string(&mut buffer) {
@@ -118,12 +194,27 @@ pub async fn create_pipeline(
                 configuration: Configuration {
                     container: action.configuration_version,
                 },
+                needs: action.needs,
+                allow_failure: action.allow_failure,
+                env: action.env,
+                matrix: action.matrix,
+                timeout: action.timeout,
+                retry: action.retry,
+                condition: action.condition,
+                artifacts: action.artifacts,
+                cache: action.cache,
+                services: action.services,
+                shell: action.shell,
             };
             (action.name, domain_action)
         })
         .collect();
     let domain_manifest = DomainManifestPipeline {
         name: parser_manifest.name.clone(),
+        env: parser_manifest.env,
+        timeout: parser_manifest.timeout,
+        shell: parser_manifest.shell,
+        checkout: parser_manifest.checkout,
         actions: ActionsMap {
             actions: actions_map,
         },
@@ -131,7 +222,7 @@ pub async fn create_pipeline(
 
     match ctx
         .pipeline_service
-        .create_manifest_pipeline(domain_manifest, repo_url)
+        .create_manifest_pipeline(domain_manifest, repo_url, trigger)
         .await
     {
         Ok(p) => HttpResponse::Ok().json(p),
@@ -141,3 +232,83 @@ pub async fn create_pipeline(
         }
     }
 }
+
+#[post("/pipeline/{id}/cancel")]
+pub async fn cancel_pipeline(
+    path: web::Path<PipelineByIDQuery>,
+    ctx: web::Data<AppContext>,
+) -> impl Responder {
+    let id = path.id;
+    info!("Cancelling pipeline with id: {}", id);
+    match ctx.pipeline_service.cancel_pipeline(id).await {
+        Ok(pipeline) => HttpResponse::Ok().json(pipeline),
+        Err(PipelineError::NotFound) => HttpResponse::NotFound().finish(),
+        Err(PipelineError::AlreadyFinished) => {
+            HttpResponse::Conflict().body("Pipeline has already finished")
+        }
+        Err(e) => {
+            error!("Error cancelling pipeline {}: {:?}", id, e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[post("/pipeline/{id}/actions/{action_id}/cancel")]
+pub async fn cancel_action(
+    path: web::Path<(i64, i64)>,
+    ctx: web::Data<AppContext>,
+) -> impl Responder {
+    let (pipeline_id, action_id) = path.into_inner();
+    info!(
+        "Cancelling action {} of pipeline {}",
+        action_id, pipeline_id
+    );
+    match ctx
+        .pipeline_service
+        .cancel_action(pipeline_id, action_id)
+        .await
+    {
+        Ok(action) => HttpResponse::Ok().json(action),
+        Err(PipelineError::ActionNotFound) => HttpResponse::NotFound().finish(),
+        Err(PipelineError::AlreadyFinished) => {
+            HttpResponse::Conflict().body("Action has already finished")
+        }
+        Err(e) => {
+            error!(
+                "Error cancelling action {} of pipeline {}: {:?}",
+                action_id, pipeline_id, e
+            );
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RerunQueryParams {
+    only_failed: Option<bool>,
+}
+
+#[post("/pipeline/{id}/rerun")]
+pub async fn rerun_pipeline(
+    path: web::Path<PipelineByIDQuery>,
+    ctx: web::Data<AppContext>,
+    query: web::Query<RerunQueryParams>,
+) -> impl Responder {
+    let id = path.id;
+    let only_failed = query.only_failed.unwrap_or(false);
+    info!("Re-running pipeline {}, only failed: {}", id, only_failed);
+    match ctx.pipeline_service.rerun_pipeline(id, only_failed).await {
+        Ok(pipeline) => HttpResponse::Ok().json(pipeline),
+        Err(PipelineError::NotFound) => HttpResponse::NotFound().finish(),
+        Err(PipelineError::StillRunning) => {
+            HttpResponse::Conflict().body("Pipeline is still running")
+        }
+        Err(PipelineError::NothingToRerun) => {
+            HttpResponse::Conflict().body("Pipeline has no failed action")
+        }
+        Err(e) => {
+            error!("Error re-running pipeline {}: {:?}", id, e);
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
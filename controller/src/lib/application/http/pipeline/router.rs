@@ -1,10 +1,17 @@
-use actix_web::web::ServiceConfig;
+use crate::application::http::pipeline::handlers::logs::{download_logs, get_logs, stream_logs};
 use crate::application::http::pipeline::handlers::pipeline::{
-    create_pipeline, get_pipelines, get_pipeline,
+    cancel_action, cancel_pipeline, create_pipeline, get_pipeline, get_pipelines, rerun_pipeline,
 };
+use actix_web::web::ServiceConfig;
 
 pub fn configure(cfg: &mut ServiceConfig) {
     cfg.service(get_pipelines)
-       .service(get_pipeline)
-       .service(create_pipeline);
-}
\ No newline at end of file
+        .service(get_pipeline)
+        .service(create_pipeline)
+        .service(cancel_pipeline)
+        .service(cancel_action)
+        .service(rerun_pipeline)
+        .service(get_logs)
+        .service(download_logs)
+        .service(stream_logs);
+}
@@ -0,0 +1,185 @@
+use actix_web::{delete, get, post, web, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use crate::{
+    application::{app_context::AppContext, ports::secret_service::SecretService},
+    domain::secrets::entities::{CredentialsKind, RepositoryCredentials, Secret, SecretError},
+};
+
+#[derive(Debug, Deserialize)]
+pub struct SecretRequest {
+    pub repo_url: String,
+    pub name: String,
+    pub value: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SecretQuery {
+    pub repo_url: String,
+}
+
+#[derive(Deserialize)]
+pub struct CredentialsRequest {
+    pub repo_url: String,
+    pub kind: CredentialsKind,
+    /// Only for an HTTPS token
+    pub username: Option<String>,
+    /// Private key or token
+    pub value: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SecretResponse {
+    pub status: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SecretsResponse {
+    pub status: String,
+    pub secrets: Vec<Secret>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CredentialsResponse {
+    pub status: String,
+    pub credentials: RepositoryCredentials,
+}
+
+fn error_response(e: SecretError) -> HttpResponse {
+    let body = SecretResponse {
+        status: "error".to_string(),
+        message: e.to_string(),
+    };
+    match e {
+        SecretError::NotFound => HttpResponse::NotFound().json(body),
+        SecretError::InvalidName(_) | SecretError::InvalidCredentials(_) => {
+            HttpResponse::UnprocessableEntity().json(body)
+        }
+        SecretError::MissingKey => HttpResponse::ServiceUnavailable().json(body),
+        _ => {
+            error!("Secret store error: {}", e);
+            HttpResponse::InternalServerError().json(SecretResponse {
+                status: "error".to_string(),
+                message: "Internal server error".to_string(),
+            })
+        }
+    }
+}
+
+#[post("/secrets")]
+pub async fn create_secret(
+    secret_data: web::Json<SecretRequest>,
+    ctx: web::Data<AppContext>,
+) -> impl Responder {
+    info!(
+        "Received secret {} for repo {}",
+        secret_data.name, secret_data.repo_url
+    );
+
+    match ctx
+        .secret_service
+        .create_secret(&secret_data.repo_url, &secret_data.name, &secret_data.value)
+        .await
+    {
+        Ok(secret) => HttpResponse::Created().json(SecretResponse {
+            status: "success".to_string(),
+            message: format!(
+                "Secret {} stored for repository {}",
+                secret.name, secret.repository_url
+            ),
+        }),
+        Err(e) => error_response(e),
+    }
+}
+
+#[get("/secrets")]
+pub async fn list_secrets(
+    query: web::Query<SecretQuery>,
+    ctx: web::Data<AppContext>,
+) -> impl Responder {
+    match ctx.secret_service.list_secrets(&query.repo_url).await {
+        Ok(secrets) => HttpResponse::Ok().json(SecretsResponse {
+            status: "success".to_string(),
+            secrets,
+        }),
+        Err(e) => error_response(e),
+    }
+}
+
+#[delete("/secrets/{name}")]
+pub async fn delete_secret(
+    name: web::Path<String>,
+    query: web::Query<SecretQuery>,
+    ctx: web::Data<AppContext>,
+) -> impl Responder {
+    match ctx
+        .secret_service
+        .delete_secret(&query.repo_url, &name)
+        .await
+    {
+        Ok(()) => HttpResponse::Ok().json(SecretResponse {
+            status: "success".to_string(),
+            message: format!("Secret {} deleted", name),
+        }),
+        Err(e) => error_response(e),
+    }
+}
+
+#[post("/credentials")]
+pub async fn set_credentials(
+    credentials: web::Json<CredentialsRequest>,
+    ctx: web::Data<AppContext>,
+) -> impl Responder {
+    info!(
+        "Received {} credentials for repo {}",
+        credentials.kind.as_str(),
+        credentials.repo_url
+    );
+
+    match ctx
+        .secret_service
+        .set_credentials(
+            &credentials.repo_url,
+            credentials.kind,
+            credentials.username.as_deref(),
+            &credentials.value,
+        )
+        .await
+    {
+        Ok(credentials) => HttpResponse::Created().json(CredentialsResponse {
+            status: "success".to_string(),
+            credentials,
+        }),
+        Err(e) => error_response(e),
+    }
+}
+
+#[get("/credentials")]
+pub async fn get_credentials(
+    query: web::Query<SecretQuery>,
+    ctx: web::Data<AppContext>,
+) -> impl Responder {
+    match ctx.secret_service.find_credentials(&query.repo_url).await {
+        Ok(credentials) => HttpResponse::Ok().json(CredentialsResponse {
+            status: "success".to_string(),
+            credentials,
+        }),
+        Err(e) => error_response(e),
+    }
+}
+
+#[delete("/credentials")]
+pub async fn delete_credentials(
+    query: web::Query<SecretQuery>,
+    ctx: web::Data<AppContext>,
+) -> impl Responder {
+    match ctx.secret_service.delete_credentials(&query.repo_url).await {
+        Ok(()) => HttpResponse::Ok().json(SecretResponse {
+            status: "success".to_string(),
+            message: format!("Credentials of repository {} deleted", query.repo_url),
+        }),
+        Err(e) => error_response(e),
+    }
+}
@@ -0,0 +1,14 @@
+use crate::application::http::secret::handlers::secret::{
+    create_secret, delete_credentials, delete_secret, get_credentials, list_secrets,
+    set_credentials,
+};
+use actix_web::web::ServiceConfig;
+
+pub fn configure(cfg: &mut ServiceConfig) {
+    cfg.service(create_secret)
+        .service(list_secrets)
+        .service(delete_secret)
+        .service(set_credentials)
+        .service(get_credentials)
+        .service(delete_credentials);
+}
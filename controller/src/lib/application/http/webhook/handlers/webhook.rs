@@ -0,0 +1,155 @@
+use actix_web::{delete, get, post, web, HttpResponse, Responder};
+use serde::{Deserialize, Serialize};
+use tracing::{error, info};
+
+use crate::{
+    application::{app_context::AppContext, ports::webhook_service::WebhookService},
+    domain::{
+        secrets::entities::SecretError,
+        webhooks::entities::{Webhook, WebhookDelivery, WebhookError, WebhookEvent},
+    },
+};
+
+#[derive(Deserialize)]
+pub struct WebhookRequest {
+    pub repo_url: String,
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    /// Key of the signature of the payloads
+    pub secret: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookQuery {
+    pub repo_url: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WebhookMessage {
+    pub status: String,
+    pub message: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WebhookResponse {
+    pub status: String,
+    pub webhook: Webhook,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WebhooksResponse {
+    pub status: String,
+    pub webhooks: Vec<Webhook>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeliveryResponse {
+    pub status: String,
+    pub delivery: WebhookDelivery,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeliveriesResponse {
+    pub status: String,
+    pub deliveries: Vec<WebhookDelivery>,
+}
+
+fn error_response(e: WebhookError) -> HttpResponse {
+    let body = WebhookMessage {
+        status: "error".to_string(),
+        message: e.to_string(),
+    };
+    match e {
+        WebhookError::NotFound => HttpResponse::NotFound().json(body),
+        WebhookError::InvalidWebhook(_) | WebhookError::InvalidEvent(_) => {
+            HttpResponse::UnprocessableEntity().json(body)
+        }
+        WebhookError::SecretError(SecretError::MissingKey) => {
+            HttpResponse::ServiceUnavailable().json(body)
+        }
+        _ => {
+            error!("Webhook error: {}", e);
+            HttpResponse::InternalServerError().json(WebhookMessage {
+                status: "error".to_string(),
+                message: "Internal server error".to_string(),
+            })
+        }
+    }
+}
+
+#[post("/webhooks")]
+pub async fn create_webhook(
+    webhook: web::Json<WebhookRequest>,
+    ctx: web::Data<AppContext>,
+) -> impl Responder {
+    info!(
+        "Received webhook {} for repo {}",
+        webhook.url, webhook.repo_url
+    );
+
+    let webhook = webhook.into_inner();
+    match ctx
+        .webhook_service
+        .create_webhook(
+            &webhook.repo_url,
+            &webhook.url,
+            webhook.events,
+            &webhook.secret,
+        )
+        .await
+    {
+        Ok(webhook) => HttpResponse::Created().json(WebhookResponse {
+            status: "success".to_string(),
+            webhook,
+        }),
+        Err(e) => error_response(e),
+    }
+}
+
+#[get("/webhooks")]
+pub async fn list_webhooks(
+    query: web::Query<WebhookQuery>,
+    ctx: web::Data<AppContext>,
+) -> impl Responder {
+    match ctx.webhook_service.list_webhooks(&query.repo_url).await {
+        Ok(webhooks) => HttpResponse::Ok().json(WebhooksResponse {
+            status: "success".to_string(),
+            webhooks,
+        }),
+        Err(e) => error_response(e),
+    }
+}
+
+#[delete("/webhooks/{id}")]
+pub async fn delete_webhook(id: web::Path<i64>, ctx: web::Data<AppContext>) -> impl Responder {
+    match ctx.webhook_service.delete_webhook(*id).await {
+        Ok(()) => HttpResponse::Ok().json(WebhookMessage {
+            status: "success".to_string(),
+            message: format!("Webhook {} deleted", id),
+        }),
+        Err(e) => error_response(e),
+    }
+}
+
+#[get("/webhooks/{id}/deliveries")]
+pub async fn list_deliveries(id: web::Path<i64>, ctx: web::Data<AppContext>) -> impl Responder {
+    match ctx.webhook_service.list_deliveries(*id).await {
+        Ok(deliveries) => HttpResponse::Ok().json(DeliveriesResponse {
+            status: "success".to_string(),
+            deliveries,
+        }),
+        Err(e) => error_response(e),
+    }
+}
+
+#[post("/webhooks/{id}/deliveries/{delivery_id}/redeliver")]
+pub async fn redeliver(path: web::Path<(i64, i64)>, ctx: web::Data<AppContext>) -> impl Responder {
+    let (id, delivery_id) = path.into_inner();
+    match ctx.webhook_service.redeliver(id, delivery_id).await {
+        Ok(delivery) => HttpResponse::Accepted().json(DeliveryResponse {
+            status: "success".to_string(),
+            delivery,
+        }),
+        Err(e) => error_response(e),
+    }
+}
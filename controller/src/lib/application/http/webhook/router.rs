@@ -0,0 +1,12 @@
+use crate::application::http::webhook::handlers::webhook::{
+    create_webhook, delete_webhook, list_deliveries, list_webhooks, redeliver,
+};
+use actix_web::web::ServiceConfig;
+
+pub fn configure(cfg: &mut ServiceConfig) {
+    cfg.service(create_webhook)
+        .service(list_webhooks)
+        .service(delete_webhook)
+        .service(list_deliveries)
+        .service(redeliver);
+}
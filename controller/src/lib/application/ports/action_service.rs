@@ -1,24 +1,39 @@
 use async_trait::async_trait;
 
-use crate::domain::action::entities::action::{Action, ActionError, ActionType};
+use crate::domain::action::entities::action::{
+    Action, ActionAttempt, ActionError, ActionStep, NewAction, StepEvent,
+};
+use crate::domain::command::entities::command::Step;
+use crate::domain::log::entities::log::{Log, NewLog};
 
 #[async_trait]
 pub trait ActionService: Send + Sync {
     async fn create(
         &self,
-        pipeline_id: i64,
-        name: String,
-        container_uri: String,
-        r#type: ActionType,
-        status: String,
-        commands: Option<Vec<String>>,
+        action: NewAction,
+        commands: Option<Vec<Step>>,
     ) -> Result<Action, ActionError>;
     async fn find_by_id(&self, action_id: i64) -> Result<Action, ActionError>;
     async fn find_by_pipeline_id(&self, pipeline_id: i64) -> Result<Vec<Action>, ActionError>;
-    async fn update_status(
+    async fn find_by_pipeline_ids(&self, pipeline_ids: &[i64]) -> Result<Vec<Action>, ActionError>;
+    async fn update_status(&self, action_id: i64, status: &String) -> Result<Action, ActionError>;
+    async fn append_log(&self, action_id: i64, log: NewLog) -> Result<Log, ActionError>;
+    async fn start_attempt(&self, action_id: i64, attempt: i32) -> Result<(), ActionError>;
+    /// Schedules a failed action again for the given attempt.
+    async fn retry(&self, action_id: i64, attempt: i32) -> Result<Action, ActionError>;
+    async fn finish_attempt(
         &self,
         action_id: i64,
-        status: &String,
-    ) -> Result<Action, ActionError>;
-    async fn append_log(&self, action_id: i64, log: String) -> Result<(), ActionError>;
+        attempt: i32,
+        status: &str,
+        exit_code: Option<i32>,
+    ) -> Result<(), ActionError>;
+    async fn find_attempts(&self, action_id: i64) -> Result<Vec<ActionAttempt>, ActionError>;
+    async fn record_step(
+        &self,
+        action_id: i64,
+        attempt: i32,
+        event: &StepEvent,
+    ) -> Result<(), ActionError>;
+    async fn find_steps(&self, action_id: i64) -> Result<Vec<ActionStep>, ActionError>;
 }
@@ -0,0 +1,26 @@
+use async_trait::async_trait;
+
+use crate::domain::artifacts::entities::{ArtifactError, ArtifactStream};
+
+#[async_trait]
+pub trait ArtifactService: Send + Sync {
+    /// Appends a chunk received from the agent to the artifacts of an action attempt.
+    async fn store_chunk(
+        &self,
+        action_id: i64,
+        attempt: i32,
+        chunk: &[u8],
+    ) -> Result<(), ArtifactError>;
+    /// Makes the archive of an action attempt available, once the agent
+    /// reported that it sent it completely.
+    async fn commit_archive(&self, action_id: i64, attempt: i32) -> Result<(), ArtifactError>;
+    /// Drops the archive of an action attempt that was not committed, such as
+    /// one cut short by a timeout or a cancellation.
+    async fn discard_archive(&self, action_id: i64, attempt: i32) -> Result<(), ArtifactError>;
+    /// Streams the artifacts archive of an action attempt.
+    async fn open_archive(
+        &self,
+        action_id: i64,
+        attempt: i32,
+    ) -> Result<ArtifactStream, ArtifactError>;
+}
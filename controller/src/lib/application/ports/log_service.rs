@@ -0,0 +1,16 @@
+use async_trait::async_trait;
+use tokio::sync::broadcast;
+
+use crate::domain::log::entities::log::{Log, LogError, LogFilter};
+
+#[async_trait]
+pub trait LogService: Send + Sync {
+    /// A page of the stored lines of an action, in order.
+    async fn find_page(&self, action_id: i64, filter: &LogFilter) -> Result<Vec<Log>, LogError>;
+    /// Receives the lines of an action as they are stored.
+    fn subscribe(&self, action_id: i64) -> broadcast::Receiver<Log>;
+    /// Whether the action is running and may still produce lines.
+    fn is_running(&self, action_id: i64) -> bool;
+    /// Called once a subscriber of an action is dropped.
+    fn release(&self, action_id: i64);
+}
@@ -1,20 +1,49 @@
 use async_trait::async_trait;
 
-use crate::domain::pipeline::entities::pipeline::{ManifestPipeline, Pipeline, PipelineError};
+use crate::domain::action::entities::action::Action;
+use crate::domain::pipeline::entities::pipeline::{
+    CheckoutOptions, ManifestPipeline, Pipeline, PipelineError, PipelineFilter, PipelinePage,
+    TriggerContext,
+};
 
 #[async_trait]
 pub trait PipelineService: Send + Sync {
-    async fn find_all(&self, verbose: bool) -> Result<Vec<Pipeline>, PipelineError>;
+    /// Returns a page of at most `limit` pipelines with their actions, but
+    /// without their logs.
+    async fn find_page(
+        &self,
+        filter: &PipelineFilter,
+        cursor: Option<i64>,
+        limit: usize,
+    ) -> Result<PipelinePage, PipelineError>;
     async fn find_by_id(&self, pipeline_id: i64) -> Result<Pipeline, PipelineError>;
     async fn create_pipeline(
         &self,
         repository_url: String,
         name: String,
+        trigger: TriggerContext,
+        checkout: CheckoutOptions,
     ) -> Result<Pipeline, PipelineError>;
     async fn create_manifest_pipeline(
         &self,
         manifest: ManifestPipeline,
         repository_url: String,
+        trigger: TriggerContext,
     ) -> Result<Pipeline, PipelineError>;
     async fn add_verbose_details(&self, pipeline: &mut Pipeline) -> Result<(), PipelineError>;
+    /// Cancels every action of the pipeline that has not finished yet.
+    async fn cancel_pipeline(&self, pipeline_id: i64) -> Result<Pipeline, PipelineError>;
+    /// Creates a new run of a pipeline from its stored actions. With
+    /// `only_failed`, the actions that completed keep their result and only
+    /// the others run again.
+    async fn rerun_pipeline(
+        &self,
+        pipeline_id: i64,
+        only_failed: bool,
+    ) -> Result<Pipeline, PipelineError>;
+    async fn cancel_action(
+        &self,
+        pipeline_id: i64,
+        action_id: i64,
+    ) -> Result<Action, PipelineError>;
 }
@@ -5,4 +5,7 @@ use crate::domain::scheduler::entities::scheduler::SchedulerError;
 #[async_trait]
 pub trait SchedulerService: Send + Sync {
     async fn execute_pipeline(&self, pipeline_id: i64) -> Result<(), SchedulerError>;
+    /// Stops an action, on its agent when it was dispatched already. Returns
+    /// `false` when the action had finished and there was nothing to cancel.
+    async fn cancel_action(&self, action_id: i64) -> Result<bool, SchedulerError>;
 }
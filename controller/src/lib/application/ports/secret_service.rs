@@ -0,0 +1,42 @@
+use std::collections::HashMap;
+
+use crate::domain::secrets::entities::{
+    Credentials, CredentialsKind, RepositoryCredentials, Secret, SecretError,
+};
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait SecretService: Send + Sync {
+    async fn create_secret(
+        &self,
+        repository_url: &str,
+        name: &str,
+        value: &str,
+    ) -> Result<Secret, SecretError>;
+    async fn list_secrets(&self, repository_url: &str) -> Result<Vec<Secret>, SecretError>;
+    async fn delete_secret(&self, repository_url: &str, name: &str) -> Result<(), SecretError>;
+    /// Decrypts every secret of a repository, keyed by name.
+    async fn reveal_secrets(
+        &self,
+        repository_url: &str,
+    ) -> Result<HashMap<String, String>, SecretError>;
+    /// Sets the credentials the repository is fetched with. `value` is the
+    /// private key or the token, `username` only goes with a token.
+    async fn set_credentials(
+        &self,
+        repository_url: &str,
+        kind: CredentialsKind,
+        username: Option<&str>,
+        value: &str,
+    ) -> Result<RepositoryCredentials, SecretError>;
+    async fn find_credentials(
+        &self,
+        repository_url: &str,
+    ) -> Result<RepositoryCredentials, SecretError>;
+    async fn delete_credentials(&self, repository_url: &str) -> Result<(), SecretError>;
+    /// Decrypts the credentials of a repository, `None` for a public one.
+    async fn reveal_credentials(
+        &self,
+        repository_url: &str,
+    ) -> Result<Option<Credentials>, SecretError>;
+}
@@ -0,0 +1,32 @@
+use serde_json::Value;
+
+use crate::domain::webhooks::entities::{Webhook, WebhookDelivery, WebhookError, WebhookEvent};
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait WebhookService: Send + Sync {
+    /// Subscribes `url` to the events of a repository. The payloads are
+    /// signed with `secret`.
+    async fn create_webhook(
+        &self,
+        repository_url: &str,
+        url: &str,
+        events: Vec<WebhookEvent>,
+        secret: &str,
+    ) -> Result<Webhook, WebhookError>;
+    async fn list_webhooks(&self, repository_url: &str) -> Result<Vec<Webhook>, WebhookError>;
+    async fn delete_webhook(&self, id: i64) -> Result<(), WebhookError>;
+    async fn list_deliveries(&self, webhook_id: i64) -> Result<Vec<WebhookDelivery>, WebhookError>;
+    /// Sends the payload of a delivery again, as a new delivery.
+    async fn redeliver(
+        &self,
+        webhook_id: i64,
+        delivery_id: i64,
+    ) -> Result<WebhookDelivery, WebhookError>;
+    /// Notifies the webhooks of a repository subscribed to `event`. The
+    /// deliveries are sent in the background, and failures are only logged.
+    async fn notify(&self, repository_url: &str, event: WebhookEvent, payload: Value);
+    /// Sends the deliveries left pending by a previous run of the controller,
+    /// resuming their retries where they stopped.
+    async fn resume_deliveries(&self);
+}
@@ -1,5 +1,10 @@
 pub mod action_service;
+pub mod artifact_service;
 pub mod command_service;
+pub mod log_hub;
+pub mod log_service;
 pub mod pipeline_service;
 pub mod release_service;
 pub mod scheduler_service_impl;
+pub mod secret_service;
+pub mod webhook_service;
@@ -2,10 +2,13 @@ use crate::{
     application::ports::{action_service::ActionService, command_service::CommandService},
     domain::{
         action::{
-            entities::action::{Action, ActionError, ActionType},
+            entities::action::{
+                Action, ActionAttempt, ActionError, ActionStep, NewAction, StepEvent,
+            },
             ports::action_repository::ActionRepository,
         },
-        command::entities::command::CommandError,
+        command::entities::command::{CommandError, Step},
+        log::entities::log::{Log, NewLog},
     },
     infrastructure::repositories::action_repository::PostgresActionRepository,
 };
@@ -47,22 +50,15 @@ where
 {
     async fn create(
         &self,
-        pipeline_id: i64,
-        name: String,
-        container_uri: String,
-        r#type: ActionType,
-        status: String,
-        commands: Option<Vec<String>>,
+        action: NewAction,
+        commands: Option<Vec<Step>>,
     ) -> Result<Action, ActionError> {
-        let created_action = self
-            .repository
-            .create(pipeline_id, name, container_uri, r#type, status)
-            .await?;
+        let created_action = self.repository.create(action).await?;
 
         if let Some(cmds) = commands {
-            for cmd in cmds {
+            for step in cmds {
                 self.command_service
-                    .create(created_action.id, cmd)
+                    .create(created_action.id, step)
                     .await
                     .map_err(|e| match e {
                         CommandError::DatabaseError(pg) => ActionError::DatabaseError(pg),
@@ -82,11 +78,52 @@ where
         self.repository.find_by_pipeline_id(pipeline_id).await
     }
 
+    async fn find_by_pipeline_ids(&self, pipeline_ids: &[i64]) -> Result<Vec<Action>, ActionError> {
+        self.repository.find_by_pipeline_ids(pipeline_ids).await
+    }
+
     async fn update_status(&self, action_id: i64, status: &String) -> Result<Action, ActionError> {
         self.repository.update_status(action_id, status).await
     }
 
-    async fn append_log(&self, action_id: i64, log: String) -> Result<(), ActionError> {
+    async fn append_log(&self, action_id: i64, log: NewLog) -> Result<Log, ActionError> {
         self.repository.append_log(action_id, log).await
     }
+
+    async fn start_attempt(&self, action_id: i64, attempt: i32) -> Result<(), ActionError> {
+        self.repository.start_attempt(action_id, attempt).await
+    }
+
+    async fn retry(&self, action_id: i64, attempt: i32) -> Result<Action, ActionError> {
+        self.repository.retry(action_id, attempt).await
+    }
+
+    async fn finish_attempt(
+        &self,
+        action_id: i64,
+        attempt: i32,
+        status: &str,
+        exit_code: Option<i32>,
+    ) -> Result<(), ActionError> {
+        self.repository
+            .finish_attempt(action_id, attempt, status, exit_code)
+            .await
+    }
+
+    async fn find_attempts(&self, action_id: i64) -> Result<Vec<ActionAttempt>, ActionError> {
+        self.repository.find_attempts(action_id).await
+    }
+
+    async fn record_step(
+        &self,
+        action_id: i64,
+        attempt: i32,
+        event: &StepEvent,
+    ) -> Result<(), ActionError> {
+        self.repository.record_step(action_id, attempt, event).await
+    }
+
+    async fn find_steps(&self, action_id: i64) -> Result<Vec<ActionStep>, ActionError> {
+        self.repository.find_steps(action_id).await
+    }
 }
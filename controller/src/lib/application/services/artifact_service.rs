@@ -0,0 +1,60 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::{
+    application::ports::artifact_service::ArtifactService,
+    domain::artifacts::{
+        entities::{ArtifactError, ArtifactStream},
+        ports::ArtifactStorage,
+    },
+    infrastructure::storage::local_artifact_storage::LocalArtifactStorage,
+};
+
+pub type DefaultArtifactServiceImpl = ArtifactServiceImpl<LocalArtifactStorage>;
+
+pub struct ArtifactServiceImpl<S>
+where
+    S: ArtifactStorage + Send + Sync,
+{
+    storage: Arc<S>,
+}
+
+impl<S> ArtifactServiceImpl<S>
+where
+    S: ArtifactStorage + Send + Sync,
+{
+    pub fn new(storage: Arc<S>) -> Self {
+        Self { storage }
+    }
+}
+
+#[async_trait]
+impl<S> ArtifactService for ArtifactServiceImpl<S>
+where
+    S: ArtifactStorage + Send + Sync,
+{
+    async fn store_chunk(
+        &self,
+        action_id: i64,
+        attempt: i32,
+        chunk: &[u8],
+    ) -> Result<(), ArtifactError> {
+        self.storage.append(action_id, attempt, chunk).await
+    }
+
+    async fn commit_archive(&self, action_id: i64, attempt: i32) -> Result<(), ArtifactError> {
+        self.storage.commit(action_id, attempt).await
+    }
+
+    async fn discard_archive(&self, action_id: i64, attempt: i32) -> Result<(), ArtifactError> {
+        self.storage.discard(action_id, attempt).await
+    }
+
+    async fn open_archive(
+        &self,
+        action_id: i64,
+        attempt: i32,
+    ) -> Result<ArtifactStream, ArtifactError> {
+        self.storage.open(action_id, attempt).await
+    }
+}
@@ -0,0 +1,84 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+
+use tokio::sync::broadcast;
+
+use crate::domain::log::entities::log::Log;
+
+/// Number of lines a subscriber may fall behind before it misses some.
+const CAPACITY: usize = 1024;
+
+struct Channel {
+    sender: broadcast::Sender<Log>,
+    /// Whether the action is being run, and may still produce lines
+    open: bool,
+}
+
+/// Broadcasts the log lines of the running actions to the clients following
+/// them, as the scheduler service stores them.
+///
+/// The hub only lives in the memory of the controller: lines stored by
+/// another controller instance are not broadcast.
+#[derive(Default)]
+pub struct LogHub {
+    channels: Mutex<HashMap<i64, Channel>>,
+}
+
+impl LogHub {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn channel(channels: &mut HashMap<i64, Channel>, action_id: i64) -> &mut Channel {
+        channels.entry(action_id).or_insert_with(|| Channel {
+            sender: broadcast::channel(CAPACITY).0,
+            open: false,
+        })
+    }
+
+    /// Marks an action as running, until `close` is called.
+    pub fn open(&self, action_id: i64) {
+        let mut channels = self.channels.lock().unwrap();
+        Self::channel(&mut channels, action_id).open = true;
+    }
+
+    /// Marks an action as done once all of its lines are stored. Its
+    /// subscribers receive the lines left and are then closed.
+    pub fn close(&self, action_id: i64) {
+        self.channels.lock().unwrap().remove(&action_id);
+    }
+
+    /// Whether the action is running and may still produce lines.
+    pub fn is_open(&self, action_id: i64) -> bool {
+        self.channels
+            .lock()
+            .unwrap()
+            .get(&action_id)
+            .is_some_and(|channel| channel.open)
+    }
+
+    /// Follows the lines of an action, which may not have started yet.
+    pub fn subscribe(&self, action_id: i64) -> broadcast::Receiver<Log> {
+        let mut channels = self.channels.lock().unwrap();
+        Self::channel(&mut channels, action_id).sender.subscribe()
+    }
+
+    /// Forgets an action that never ran once nobody follows it anymore.
+    pub fn release(&self, action_id: i64) {
+        let mut channels = self.channels.lock().unwrap();
+        if channels
+            .get(&action_id)
+            .is_some_and(|channel| !channel.open && channel.sender.receiver_count() == 0)
+        {
+            channels.remove(&action_id);
+        }
+    }
+
+    /// Sends a stored line to the subscribers of its action, if any.
+    pub fn publish(&self, log: &Log) {
+        if let Some(channel) = self.channels.lock().unwrap().get(&log.action_id) {
+            // No subscriber left is not an error
+            let _ = channel.sender.send(log.clone());
+        }
+    }
+}
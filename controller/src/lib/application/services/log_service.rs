@@ -0,0 +1,55 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+use tokio::sync::broadcast;
+
+use crate::{
+    application::ports::log_service::LogService,
+    domain::log::{
+        entities::log::{Log, LogError, LogFilter},
+        ports::log_repository::LogRepository,
+    },
+    infrastructure::repositories::log_repository::PostgresLogRepository,
+};
+
+use super::log_hub::LogHub;
+
+pub type DefaultLogServiceImpl = LogServiceImpl<PostgresLogRepository>;
+
+pub struct LogServiceImpl<L>
+where
+    L: LogRepository + Send + Sync,
+{
+    repository: Arc<L>,
+    hub: Arc<LogHub>,
+}
+
+impl<L> LogServiceImpl<L>
+where
+    L: LogRepository + Send + Sync,
+{
+    pub fn new(repository: Arc<L>, hub: Arc<LogHub>) -> Self {
+        Self { repository, hub }
+    }
+}
+
+#[async_trait]
+impl<L> LogService for LogServiceImpl<L>
+where
+    L: LogRepository + Send + Sync,
+{
+    async fn find_page(&self, action_id: i64, filter: &LogFilter) -> Result<Vec<Log>, LogError> {
+        self.repository.find_page(action_id, filter).await
+    }
+
+    fn subscribe(&self, action_id: i64) -> broadcast::Receiver<Log> {
+        self.hub.subscribe(action_id)
+    }
+
+    fn is_running(&self, action_id: i64) -> bool {
+        self.hub.is_open(action_id)
+    }
+
+    fn release(&self, action_id: i64) {
+        self.hub.release(action_id)
+    }
+}
@@ -1,7 +1,6 @@
-use std::sync::Arc;
+use std::{collections::HashMap, sync::Arc};
 
 use async_trait::async_trait;
-use futures::lock::Mutex;
 use tracing::error;
 
 use crate::{
@@ -10,10 +9,17 @@ use crate::{
         scheduler_service::SchedulerService,
     },
     domain::{
-        action::entities::action::{ActionStatus, ActionType},
+        action::entities::action::{
+            Action, ActionError, ActionStatus, ActionStep, ActionType, Cache, NewAction, Service,
+        },
+        command::entities::command::Step,
         log::ports::log_repository::LogRepository,
         pipeline::{
-            entities::pipeline::{ManifestPipeline, Pipeline, PipelineError},
+            entities::pipeline::{
+                matrix_action_name, substitute_matrix, CheckoutOptions, ManifestPipeline,
+                MatrixCombination, Pipeline, PipelineError, PipelineFilter, PipelinePage,
+                TriggerContext,
+            },
             ports::pipeline_repository::PipelineRepository,
         },
     },
@@ -26,6 +32,9 @@ use super::{
     action_service::DefaultActionServiceImpl, scheduler_service_impl::DefaultSchedulerServiceImpl,
 };
 
+/// Lines of the logs of each action shown with the details of a pipeline.
+const VERBOSE_LOG_LINES: i64 = 1000;
+
 pub type DefaultPipelineServiceImpl = PipelineServiceImpl<
     PostgresPipelineRepository,
     PostgresLogRepository,
@@ -43,7 +52,7 @@ where
     repository: Arc<R>,
     logs_repository: Arc<L>,
     action_service: Arc<A>,
-    scheduler_service: Arc<Mutex<S>>,
+    scheduler_service: Arc<S>,
 }
 
 impl<R, L, A, S> PipelineServiceImpl<R, L, A, S>
@@ -57,7 +66,7 @@ where
         repository: Arc<R>,
         logs_repository: Arc<L>,
         action_service: Arc<A>,
-        scheduler_service: Arc<Mutex<S>>,
+        scheduler_service: Arc<S>,
     ) -> Self {
         Self {
             repository,
@@ -66,6 +75,65 @@ where
             scheduler_service,
         }
     }
+
+    /// Runs the actions of a pipeline in the background.
+    fn dispatch(&self, pipeline_id: i64) {
+        let scheduler = self.scheduler_service.clone();
+        tokio::spawn(async move {
+            if let Err(err) = scheduler.execute_pipeline(pipeline_id).await {
+                error!(
+                    "Error gRPC scheduling client on pipeline {}: {:?}",
+                    pipeline_id, err
+                );
+            }
+        });
+    }
+
+    /// Fills the logs and steps of the current attempt of an action, and the
+    /// record of every attempt with its own logs and steps. An action reusing
+    /// the result of an earlier run shows the logs of that run. Only the last
+    /// `VERBOSE_LOG_LINES` lines are attached, the whole log is paginated by
+    /// the log endpoints.
+    async fn attach_logs(&self, action: &mut Action) -> Result<(), PipelineError> {
+        let source_id = action.reused_from.unwrap_or(action.id);
+        let rows = self
+            .logs_repository
+            .find_tail(source_id, VERBOSE_LOG_LINES)
+            .await
+            .map_err(|e| PipelineError::CreateError(format!("Error fetching logs: {}", e)))?;
+        let mut attempts = self
+            .action_service
+            .find_attempts(source_id)
+            .await
+            .map_err(|e| PipelineError::CreateError(format!("Error fetching attempts: {}", e)))?;
+        let steps = self
+            .action_service
+            .find_steps(source_id)
+            .await
+            .map_err(|e| PipelineError::CreateError(format!("Error fetching steps: {}", e)))?;
+
+        let mut lines: HashMap<i32, Vec<String>> = HashMap::new();
+        for lg in rows {
+            lines.entry(lg.attempt).or_default().push(lg.data);
+        }
+        let mut attempt_steps: HashMap<i32, Vec<ActionStep>> = HashMap::new();
+        for step in steps {
+            attempt_steps.entry(step.attempt).or_default().push(step);
+        }
+        action.logs = Some(lines.get(&action.attempt).cloned().unwrap_or_default());
+        action.steps = Some(
+            attempt_steps
+                .get(&action.attempt)
+                .cloned()
+                .unwrap_or_default(),
+        );
+        for attempt in &mut attempts {
+            attempt.logs = Some(lines.remove(&attempt.attempt).unwrap_or_default());
+            attempt.steps = Some(attempt_steps.remove(&attempt.attempt).unwrap_or_default());
+        }
+        action.attempts = Some(attempts);
+        Ok(())
+    }
 }
 
 #[async_trait]
@@ -76,52 +144,50 @@ where
     A: ActionService + Send + Sync,
     S: SchedulerService + Send + Sync + 'static,
 {
-    async fn find_all(&self, verbose: bool) -> Result<Vec<Pipeline>, PipelineError> {
-        let mut pipelines = self.repository.find_all().await?;
-
-        for pipeline in &mut pipelines {
-            let mut actions = self
-                .action_service
-                .find_by_pipeline_id(pipeline.id)
-                .await
-                .map_err(|e| {
-                    PipelineError::CreateError(format!("Failed to find actions: {}", e))
-                })?;
-
-            if verbose {
-                for act in &mut actions {
-                    let db_rows = self
-                        .logs_repository
-                        .find_by_action_id(act.id)
-                        .await
-                        .map_err(|e| {
-                            PipelineError::CreateError(format!("Error fetching logs: {}", e))
-                        })?;
-
-                    let mut lines = Vec::with_capacity(db_rows.len());
-                    for lg in db_rows {
-                        lines.push(lg.data);
-                    }
-                    act.logs = Some(lines);
-                }
-            } else {
-                for act in &mut actions {
-                    act.logs = None;
-                }
-            }
+    async fn find_page(
+        &self,
+        filter: &PipelineFilter,
+        cursor: Option<i64>,
+        limit: usize,
+    ) -> Result<PipelinePage, PipelineError> {
+        // One more pipeline than asked tells whether there is a next page
+        let pipelines = self
+            .repository
+            .find_page(filter, cursor, limit as i64 + 1)
+            .await?;
+        let mut page = PipelinePage::new(pipelines, limit);
 
-            pipeline.actions = actions;
+        // The actions of the whole page are loaded at once
+        let ids: Vec<i64> = page.pipelines.iter().map(|pipeline| pipeline.id).collect();
+        let actions = self
+            .action_service
+            .find_by_pipeline_ids(&ids)
+            .await
+            .map_err(|e| PipelineError::CreateError(format!("Failed to find actions: {}", e)))?;
+        let mut by_pipeline: HashMap<i64, Vec<Action>> = HashMap::new();
+        for action in actions {
+            by_pipeline
+                .entry(action.pipeline_id)
+                .or_default()
+                .push(action);
+        }
+        for pipeline in &mut page.pipelines {
+            pipeline.actions = by_pipeline.remove(&pipeline.id).unwrap_or_default();
         }
 
-        Ok(pipelines)
+        Ok(page)
     }
 
     async fn create_pipeline(
         &self,
         repository_url: String,
         name: String,
+        trigger: TriggerContext,
+        checkout: CheckoutOptions,
     ) -> Result<Pipeline, PipelineError> {
-        self.repository.create(repository_url, name).await
+        self.repository
+            .create(repository_url, name, trigger, checkout)
+            .await
     }
 
     async fn find_by_id(&self, pipeline_id: i64) -> Result<Pipeline, PipelineError> {
@@ -145,56 +211,280 @@ where
         &self,
         manifest: ManifestPipeline,
         repository_url: String,
+        trigger: TriggerContext,
     ) -> Result<Pipeline, PipelineError> {
-        let mut pipeline = self.create_pipeline(repository_url, manifest.name).await?;
+        let mut pipeline = self
+            .create_pipeline(repository_url, manifest.name, trigger, manifest.checkout)
+            .await?;
+
+        // A matrix action runs once per combination: its dependents need all
+        // of the generated actions
+        let runs: HashMap<&String, Vec<(String, Option<MatrixCombination>)>> = manifest
+            .actions
+            .actions
+            .iter()
+            .map(|(action_name, action_data)| {
+                let names = match &action_data.matrix {
+                    Some(matrix) => matrix
+                        .combinations()
+                        .into_iter()
+                        .map(|combination| {
+                            (
+                                matrix_action_name(action_name, &combination),
+                                Some(combination),
+                            )
+                        })
+                        .collect(),
+                    None => vec![(action_name.clone(), None)],
+                };
+                (action_name, names)
+            })
+            .collect();
 
         let mut created_actions = Vec::new();
         for (action_name, action_data) in manifest.actions.actions.iter() {
-            let action = self
-                .action_service
-                .create(
-                    pipeline.id,
-                    action_name.to_owned(),
-                    action_data.configuration.container.clone(),
-                    ActionType::Container,
-                    ActionStatus::Pending.as_proto_name().to_string(),
-                    Some(action_data.commands.clone()),
-                )
-                .await
-                .map_err(|e| PipelineError::CreateError(format!("Error creating action: {}", e)))?;
-            created_actions.push(action);
-        }
+            let needs: Vec<String> = action_data
+                .needs
+                .iter()
+                .flat_map(|need| match runs.get(need) {
+                    Some(needed) => needed.iter().map(|(name, _)| name.clone()).collect(),
+                    None => vec![need.clone()],
+                })
+                .collect();
 
-        pipeline.actions = created_actions.clone();
+            for (run_name, combination) in &runs[action_name] {
+                let substitute = |value: &String| match combination {
+                    Some(combination) => substitute_matrix(value, combination),
+                    None => value.clone(),
+                };
 
-        let scheduler = self.scheduler_service.clone();
-        let pipeline_id = pipeline.id;
-        tokio::spawn(async move {
-            if let Err(err) = scheduler.lock().await.execute_pipeline(pipeline_id).await {
-                error!(
-                    "Error gRPC scheduling client on pipeline {}: {:?}",
-                    pipeline_id, err
-                );
+                // Action level variables override the pipeline level ones
+                let mut env = manifest.env.clone();
+                env.extend(action_data.env.clone());
+                let env = env
+                    .into_iter()
+                    .map(|(key, value)| (key, substitute(&value)))
+                    .collect();
+
+                let action = self
+                    .action_service
+                    .create(
+                        NewAction {
+                            pipeline_id: pipeline.id,
+                            name: run_name.clone(),
+                            container_uri: substitute(&action_data.configuration.container),
+                            r#type: ActionType::Container,
+                            status: ActionStatus::Pending.as_proto_name().to_string(),
+                            needs: needs.clone(),
+                            allow_failure: action_data.allow_failure,
+                            env,
+                            // The pipeline timeout is the default of its actions
+                            timeout_seconds: action_data
+                                .timeout
+                                .or(manifest.timeout)
+                                .map(|timeout| timeout as i64),
+                            retry: action_data.retry.clone(),
+                            condition: action_data.condition.clone(),
+                            artifacts: action_data
+                                .artifacts
+                                .iter()
+                                .flat_map(|artifacts| artifacts.paths.iter().map(substitute))
+                                .collect(),
+                            cache: action_data.cache.as_ref().map(|cache| Cache {
+                                key: substitute(&cache.key),
+                                paths: cache.paths.iter().map(substitute).collect(),
+                            }),
+                            services: action_data
+                                .services
+                                .iter()
+                                .map(|(name, service)| {
+                                    let service = Service {
+                                        image: substitute(&service.image),
+                                        env: service
+                                            .env
+                                            .iter()
+                                            .map(|(key, value)| (key.clone(), substitute(value)))
+                                            .collect(),
+                                        healthcheck: service.healthcheck.as_ref().map(substitute),
+                                    };
+                                    (name.clone(), service)
+                                })
+                                .collect(),
+                            // The pipeline shell is the default of its actions
+                            shell: action_data
+                                .shell
+                                .as_ref()
+                                .or(manifest.shell.as_ref())
+                                .map(|shell| shell.iter().map(substitute).collect())
+                                .unwrap_or_default(),
+                            reused_from: None,
+                        },
+                        Some(
+                            action_data
+                                .commands
+                                .iter()
+                                .map(|step| Step {
+                                    name: step.name.as_ref().map(substitute),
+                                    run: substitute(&step.run),
+                                    working_directory: step
+                                        .working_directory
+                                        .as_ref()
+                                        .map(substitute),
+                                    env: step
+                                        .env
+                                        .iter()
+                                        .map(|(key, value)| (key.clone(), substitute(value)))
+                                        .collect(),
+                                    continue_on_error: step.continue_on_error,
+                                })
+                                .collect(),
+                        ),
+                    )
+                    .await
+                    .map_err(|e| {
+                        PipelineError::CreateError(format!("Error creating action: {}", e))
+                    })?;
+                created_actions.push(action);
             }
-        });
+        }
+
+        pipeline.actions = created_actions.clone();
+        self.dispatch(pipeline.id);
 
         Ok(pipeline)
     }
 
     async fn add_verbose_details(&self, pipe: &mut Pipeline) -> Result<(), PipelineError> {
         for act in &mut pipe.actions {
-            let rows = self
-                .logs_repository
-                .find_by_action_id(act.id)
-                .await
-                .map_err(|e| PipelineError::CreateError(format!("fetch logs: {}", e)))?;
+            self.attach_logs(act).await?;
+        }
+        Ok(())
+    }
 
-            let mut lines = Vec::with_capacity(rows.len());
-            for lg in rows {
-                lines.push(lg.data);
+    async fn cancel_pipeline(&self, pipeline_id: i64) -> Result<Pipeline, PipelineError> {
+        let mut pipeline = self.find_by_id(pipeline_id).await?;
+
+        // Pending actions go first so that none of them gets dispatched once
+        // the running ones stop
+        pipeline
+            .actions
+            .sort_by_key(|action| action.status != ActionStatus::Pending);
+        let mut cancelled = false;
+        for action in &pipeline.actions {
+            if action.status.is_terminal() {
+                continue;
             }
-            act.logs = Some(lines);
+            cancelled |= self
+                .scheduler_service
+                .cancel_action(action.id)
+                .await
+                .map_err(|e| {
+                    PipelineError::CreateError(format!("Error cancelling action: {}", e))
+                })?;
         }
-        Ok(())
+        if !cancelled {
+            return Err(PipelineError::AlreadyFinished);
+        }
+
+        self.find_by_id(pipeline_id).await
+    }
+
+    async fn rerun_pipeline(
+        &self,
+        pipeline_id: i64,
+        only_failed: bool,
+    ) -> Result<Pipeline, PipelineError> {
+        let original = self.find_by_id(pipeline_id).await?;
+        if original
+            .actions
+            .iter()
+            .any(|action| !action.status.is_terminal())
+        {
+            return Err(PipelineError::StillRunning);
+        }
+        if original
+            .actions
+            .iter()
+            .all(|action| action.rerun_source(only_failed).is_some())
+        {
+            return Err(PipelineError::NothingToRerun);
+        }
+
+        let actions = original
+            .actions
+            .into_iter()
+            .map(|action| {
+                let reused_from = action.rerun_source(only_failed);
+                (
+                    NewAction {
+                        pipeline_id,
+                        name: action.name,
+                        container_uri: action.container_uri,
+                        r#type: action.r#type,
+                        status: if reused_from.is_some() {
+                            ActionStatus::Completed
+                        } else {
+                            ActionStatus::Pending
+                        }
+                        .as_proto_name()
+                        .to_string(),
+                        needs: action.needs,
+                        allow_failure: action.allow_failure,
+                        env: action.env,
+                        timeout_seconds: action.timeout_seconds,
+                        retry: action.retry,
+                        condition: action.condition,
+                        artifacts: action.artifacts,
+                        cache: action.cache,
+                        services: action.services,
+                        shell: action.shell,
+                        reused_from,
+                    },
+                    action.commands,
+                )
+            })
+            .collect();
+        let rerun = self.repository.create_rerun(pipeline_id, actions).await?;
+        let pipeline = self.find_by_id(rerun.id).await?;
+
+        self.dispatch(pipeline.id);
+        Ok(pipeline)
+    }
+
+    async fn cancel_action(
+        &self,
+        pipeline_id: i64,
+        action_id: i64,
+    ) -> Result<Action, PipelineError> {
+        let action = self
+            .action_service
+            .find_by_id(action_id)
+            .await
+            .map_err(|e| match e {
+                ActionError::DatabaseError(sqlx::Error::RowNotFound) => {
+                    PipelineError::ActionNotFound
+                }
+                e => PipelineError::CreateError(format!("Failed to find action: {}", e)),
+            })?;
+        if action.pipeline_id != pipeline_id {
+            return Err(PipelineError::ActionNotFound);
+        }
+
+        let cancelled = self
+            .scheduler_service
+            .cancel_action(action_id)
+            .await
+            .map_err(|e| PipelineError::CreateError(format!("Error cancelling action: {}", e)))?;
+        if !cancelled {
+            return Err(PipelineError::AlreadyFinished);
+        }
+
+        let mut action = self
+            .action_service
+            .find_by_id(action_id)
+            .await
+            .map_err(|e| PipelineError::CreateError(format!("Failed to find action: {}", e)))?;
+        action.logs = None;
+        Ok(action)
     }
 }
@@ -1,48 +1,91 @@
 use async_trait::async_trait;
 use std::sync::Arc;
-use tracing::info;
+use tracing::{error, info};
 
 use crate::{
-    application::ports::release_service::ReleaseService,
-    domain::releases::{
-        entities::{CreateReleaseRequest, Release, ReleaseError, ReleaseStatus},
-        ports::ReleaseRepository,
-        services::ReleaseAgentClient,
+    application::ports::{
+        release_service::ReleaseService, secret_service::SecretService,
+        webhook_service::WebhookService,
+    },
+    domain::{
+        releases::{
+            entities::{CreateReleaseRequest, Release, ReleaseError, ReleaseStatus},
+            ports::ReleaseRepository,
+            services::ReleaseAgentClient,
+        },
+        webhooks::entities::{release_payload, WebhookEvent},
+    },
+    infrastructure::{
+        grpc::grpc_release_agent_client::GrpcReleaseAgentClient,
+        repositories::release_repository::PostgresReleaseRepository,
     },
 };
 
-pub struct ReleaseServiceImpl<R, P>
+use super::{secret_service::DefaultSecretServiceImpl, webhook_service::DefaultWebhookServiceImpl};
+
+pub type DefaultReleaseServiceImpl = ReleaseServiceImpl<
+    GrpcReleaseAgentClient,
+    PostgresReleaseRepository,
+    DefaultSecretServiceImpl,
+    DefaultWebhookServiceImpl,
+>;
+
+pub struct ReleaseServiceImpl<R, P, K, W>
 where
     R: ReleaseAgentClient + Send + Sync,
     P: ReleaseRepository + Send + Sync,
+    K: SecretService + Send + Sync,
+    W: WebhookService + Send + Sync,
 {
     release_agent_client: Arc<R>,
     release_repository: Arc<P>,
+    secret_service: Arc<K>,
+    webhook_service: Arc<W>,
 }
 
-impl<R, P> ReleaseServiceImpl<R, P>
+impl<R, P, K, W> ReleaseServiceImpl<R, P, K, W>
 where
     R: ReleaseAgentClient + Send + Sync,
     P: ReleaseRepository + Send + Sync,
+    K: SecretService + Send + Sync,
+    W: WebhookService + Send + Sync,
 {
-    pub fn new(release_agent_client: Arc<R>, release_repository: Arc<P>) -> Self {
+    pub fn new(
+        release_agent_client: Arc<R>,
+        release_repository: Arc<P>,
+        secret_service: Arc<K>,
+        webhook_service: Arc<W>,
+    ) -> Self {
         Self {
             release_agent_client,
             release_repository,
+            secret_service,
+            webhook_service,
         }
     }
 }
 
 #[async_trait]
-impl<R, P> ReleaseService for ReleaseServiceImpl<R, P>
+impl<R, P, K, W> ReleaseService for ReleaseServiceImpl<R, P, K, W>
 where
     R: ReleaseAgentClient + Send + Sync,
     P: ReleaseRepository + Send + Sync,
+    K: SecretService + Send + Sync,
+    W: WebhookService + Send + Sync,
 {
     async fn create_release(&self, repo_url: &str, revision: &str) -> Result<(), ReleaseError> {
+        let credentials = self
+            .secret_service
+            .reveal_credentials(repo_url)
+            .await
+            .map_err(|e| {
+                error!("Failed to load the credentials of {}: {}", repo_url, e);
+                ReleaseError::InternalError
+            })?;
         let request = CreateReleaseRequest {
             repo_url: repo_url.to_string(),
             revision: revision.to_string(),
+            credentials,
         };
         let client = self.release_agent_client.clone();
         let release_answer = client
@@ -54,7 +97,7 @@ where
         }
         let public_key = release_answer.public_key.unwrap().clone();
         info!("pk : {}", public_key.key_data);
-        let _ = self
+        let release = self
             .release_repository
             .create_release(
                 repo_url.to_string(),
@@ -64,6 +107,13 @@ where
                 public_key.fingerprint,
             )
             .await?;
+        self.webhook_service
+            .notify(
+                repo_url,
+                WebhookEvent::ReleaseCreated,
+                release_payload(&release),
+            )
+            .await;
         Ok(())
     }
 
@@ -1,70 +1,684 @@
 use crate::domain::action::entities::action::{
-    ActionRequest as DomainActionRequest, ExecutionContext,
+    Action, ActionError, ActionRequest as DomainActionRequest, ActionStatus, AttemptOutcome,
+    Checkout, ExecutionContext, Service,
 };
+use crate::domain::command::entities::command::Step;
+use crate::domain::log::entities::log::{LineBuffer, NewLog};
 use crate::{
-    application::ports::{action_service::ActionService, scheduler_service::SchedulerService},
+    application::ports::{
+        action_service::ActionService, artifact_service::ArtifactService,
+        scheduler_service::SchedulerService, secret_service::SecretService,
+        webhook_service::WebhookService,
+    },
     domain::{
-        pipeline::ports::pipeline_repository::PipelineRepository,
+        pipeline::{
+            entities::{condition::Condition, pipeline::TriggerContext},
+            ports::pipeline_repository::PipelineRepository,
+        },
+        reporting::ports::StatusReporter,
         scheduler::{
             entities::scheduler::SchedulerError, services::scheduler_client::SchedulerClient,
         },
+        secrets::entities::{mask_secrets, resolve_secret_references},
+        webhooks::entities::{pipeline_payload, WebhookEvent},
     },
     infrastructure::{
         grpc::grpc_scheduler_client::GrpcSchedulerClient,
+        reporting::github_status_reporter::GithubStatusReporter,
         repositories::pipeline_repository::PostgresPipelineRepository,
     },
 };
 use async_trait::async_trait;
 use futures::lock::Mutex;
+use futures::stream::FuturesUnordered;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use time::OffsetDateTime;
 use tokio_stream::StreamExt;
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
-use super::action_service::DefaultActionServiceImpl;
+use super::{
+    action_service::DefaultActionServiceImpl, artifact_service::DefaultArtifactServiceImpl,
+    log_hub::LogHub, secret_service::DefaultSecretServiceImpl,
+    webhook_service::DefaultWebhookServiceImpl,
+};
 
-pub type DefaultSchedulerServiceImpl =
-    SchedulerServiceImpl<DefaultActionServiceImpl, GrpcSchedulerClient, PostgresPipelineRepository>;
+pub type DefaultSchedulerServiceImpl = SchedulerServiceImpl<
+    DefaultActionServiceImpl,
+    GrpcSchedulerClient,
+    PostgresPipelineRepository,
+    DefaultSecretServiceImpl,
+    DefaultArtifactServiceImpl,
+    DefaultWebhookServiceImpl,
+    GithubStatusReporter,
+>;
 
-pub struct SchedulerServiceImpl<A, S, R>
+pub struct SchedulerServiceImpl<A, S, R, K, F, W, P>
 where
     A: ActionService + Send + Sync,
     S: SchedulerClient + Send + Sync,
     R: PipelineRepository + Send + Sync,
+    K: SecretService + Send + Sync,
+    F: ArtifactService + Send + Sync,
+    W: WebhookService + Send + Sync,
+    P: StatusReporter + Send + Sync,
 {
     action_service: Arc<A>,
     scheduler_client: Arc<Mutex<S>>,
     pipeline_repository: Arc<R>,
+    secret_service: Arc<K>,
+    artifact_service: Arc<F>,
+    log_hub: Arc<LogHub>,
+    webhook_service: Arc<W>,
+    /// Reports the status of the pipelines to their forge, when configured
+    status_reporter: Option<Arc<P>>,
 }
 
-impl<A, S, R> SchedulerServiceImpl<A, S, R>
+impl<A, S, R, K, F, W, P> SchedulerServiceImpl<A, S, R, K, F, W, P>
 where
     A: ActionService + Send + Sync,
     S: SchedulerClient + Send + Sync,
     R: PipelineRepository + Send + Sync,
+    K: SecretService + Send + Sync,
+    F: ArtifactService + Send + Sync,
+    W: WebhookService + Send + Sync,
+    P: StatusReporter + Send + Sync,
 {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         action_service: Arc<A>,
         scheduler_client: Arc<Mutex<S>>,
         pipeline_repository: Arc<R>,
+        secret_service: Arc<K>,
+        artifact_service: Arc<F>,
+        log_hub: Arc<LogHub>,
+        webhook_service: Arc<W>,
+        status_reporter: Option<Arc<P>>,
     ) -> Self {
         Self {
             action_service,
             scheduler_client,
             pipeline_repository,
+            secret_service,
+            artifact_service,
+            log_hub,
+            webhook_service,
+            status_reporter,
+        }
+    }
+
+    /// Runs a single action, dispatching it again as long as its retry policy
+    /// matches the way the last attempt failed.
+    ///
+    /// Returns the final status of the action. Failures of the action itself
+    /// (scheduler unreachable, broken stream, non-zero exit code) are reported
+    /// as `ActionStatus::Error`; only failures to persist the outcome are errors.
+    ///
+    /// `${{ secrets.NAME }}` references of the action env are resolved against
+    /// `secrets`, and every secret value is masked from the logs it produces.
+    async fn run_action(
+        &self,
+        action: Action,
+        repo_url: String,
+        checkout: Checkout,
+        secrets: &HashMap<String, String>,
+    ) -> Result<ActionStatus, SchedulerError> {
+        info!("Scheduling action {} with ID {}", action.name, action.id);
+        // The key or token of the repository is masked as well, should a
+        // command print it
+        let secret_values: Vec<String> = secrets
+            .values()
+            .cloned()
+            .chain(
+                checkout
+                    .credentials
+                    .iter()
+                    .map(|credentials| credentials.secret().to_string()),
+            )
+            .collect();
+
+        let resolve_env = |env: &HashMap<String, String>| {
+            env.iter()
+                .map(|(key, value)| {
+                    resolve_secret_references(value, secrets).map(|value| (key.clone(), value))
+                })
+                .collect::<Result<HashMap<String, String>, _>>()
+        };
+        // Services and steps get secrets in their environment too, such as a
+        // database password
+        let resolved = resolve_env(&action.env).and_then(|env| {
+            let services = action
+                .services
+                .iter()
+                .map(|(name, service)| {
+                    resolve_env(&service.env).map(|env| {
+                        (
+                            name.clone(),
+                            Service {
+                                env,
+                                ..service.clone()
+                            },
+                        )
+                    })
+                })
+                .collect::<Result<HashMap<String, Service>, _>>()?;
+            let commands = action
+                .commands
+                .iter()
+                .map(|step| {
+                    resolve_env(&step.env).map(|env| Step {
+                        env,
+                        ..step.clone()
+                    })
+                })
+                .collect::<Result<Vec<Step>, _>>()?;
+            Ok((env, services, commands))
+        });
+        let (env, services, commands) = match resolved {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                error!("Failed to resolve secrets of action {}: {}", action.id, e);
+                self.append_log(
+                    action.id,
+                    action.attempt,
+                    format!("Failed to resolve secrets: {}", e),
+                    &secret_values,
+                )
+                .await?;
+                self.update_status(action.id, ActionStatus::Error).await?;
+                return Ok(ActionStatus::Error);
+            }
+        };
+
+        // Prepare the action request for the scheduler gRPC
+        let action_request = DomainActionRequest {
+            action_id: action.id as u32,
+            context: ExecutionContext {
+                r#type: action.r#type as i32,
+                container_image: Some(action.container_uri.clone()),
+            },
+            commands,
+            repo_url,
+            env,
+            timeout_seconds: action.timeout_seconds.map(|timeout| timeout as u64),
+            artifacts: action.artifacts.clone(),
+            cache: action.cache.clone(),
+            services,
+            shell: action.shell.clone(),
+            checkout,
+        };
+
+        let mut attempt = 1;
+        loop {
+            // An action cancelled before it was dispatched, or between two
+            // attempts, cannot be scheduled anymore
+            let scheduled = if attempt == 1 {
+                self.update_status(action.id, ActionStatus::Scheduled)
+                    .await?
+            } else {
+                self.retry(action.id, attempt).await?
+            };
+            if !scheduled {
+                info!("Action {} was cancelled, not dispatching it", action.name);
+                return Ok(ActionStatus::Cancelled);
+            }
+
+            if attempt == 1 {
+                self.action_service
+                    .start_attempt(action.id, attempt)
+                    .await
+                    .map_err(|e| {
+                        error!("Failed to start attempt of action {}: {:?}", action.id, e);
+                        SchedulerError::Error(format!("Failed to start attempt: {}", e))
+                    })?;
+            }
+
+            let outcome = self
+                .run_attempt(action.id, attempt, action_request.clone(), &secret_values)
+                .await;
+            // An archive the agent did not report complete, such as one cut
+            // short by a timeout or a cancellation, is never served
+            if let Err(e) = self
+                .artifact_service
+                .discard_archive(action.id, attempt)
+                .await
+            {
+                error!("Failed to discard artifacts of action {}: {}", action.id, e);
+            }
+            let outcome = outcome?;
+
+            self.action_service
+                .finish_attempt(
+                    action.id,
+                    attempt,
+                    outcome.status.as_proto_name(),
+                    outcome.exit_code,
+                )
+                .await
+                .map_err(|e| {
+                    error!("Failed to finish attempt of action {}: {:?}", action.id, e);
+                    SchedulerError::Error(format!("Failed to finish attempt: {}", e))
+                })?;
+
+            match &action.retry {
+                Some(retry) if retry.should_retry(attempt, &outcome) => {
+                    info!(
+                        "Retrying action {} (attempt {} of {})",
+                        action.name,
+                        attempt + 1,
+                        retry.max_attempts
+                    );
+                    self.append_log(
+                        action.id,
+                        attempt,
+                        format!(
+                            "Retrying action, attempt {} of {}",
+                            attempt + 1,
+                            retry.max_attempts
+                        ),
+                        &secret_values,
+                    )
+                    .await?;
+                    attempt += 1;
+                }
+                _ => return Ok(outcome.status),
+            }
+        }
+    }
+
+    /// Schedules one attempt of an action and consumes its response stream
+    /// until the scheduler closes it, persisting status updates and logs on the way.
+    async fn run_attempt(
+        &self,
+        action_id: i64,
+        attempt: i32,
+        action_request: DomainActionRequest,
+        secret_values: &[String],
+    ) -> Result<AttemptOutcome, SchedulerError> {
+        // Call the scheduler client to schedule the action and get a response stream
+        // A response stream is a stream of ActionResponse items
+        // The client lock is only held while sending the request so that
+        // independent actions can be dispatched concurrently
+        let scheduled = self
+            .scheduler_client
+            .lock()
+            .await
+            .schedule_action(action_request)
+            .await;
+        let mut response_stream = match scheduled {
+            Ok(stream) => stream,
+            Err(e) => {
+                error!("Failed to schedule action {}: {:?}", action_id, e);
+                self.append_log(
+                    action_id,
+                    attempt,
+                    format!("Failed to schedule action: {}", e),
+                    secret_values,
+                )
+                .await?;
+                self.update_status(action_id, ActionStatus::Error).await?;
+                return Ok(AttemptOutcome {
+                    status: ActionStatus::Error,
+                    exit_code: None,
+                    agent_lost: false,
+                });
+            }
+        };
+
+        // The action was scheduled before its attempt started
+        let mut status = ActionStatus::Scheduled;
+        let mut exit_code = None;
+        let mut artifacts_failed = false;
+        let mut lines = LineBuffer::new(secret_values.to_vec());
+        while let Some(item) = response_stream.next().await {
+            // Process each item in the response stream
+            match item {
+                Ok(action_response) => {
+                    // Chunks of the artifacts archive carry neither a log nor a result
+                    if let Some(chunk) = &action_response.artifact {
+                        if artifacts_failed {
+                            continue;
+                        }
+                        let stored = if action_response.artifact_complete {
+                            self.artifact_service
+                                .commit_archive(action_id, attempt)
+                                .await
+                        } else {
+                            self.artifact_service
+                                .store_chunk(action_id, attempt, chunk)
+                                .await
+                        };
+                        if let Err(e) = stored {
+                            // Losing the artifacts does not fail the action
+                            error!("Failed to store artifacts of action {}: {}", action_id, e);
+                            self.append_log(
+                                action_id,
+                                attempt,
+                                format!("Failed to store artifacts: {}", e),
+                                secret_values,
+                            )
+                            .await?;
+                            artifacts_failed = true;
+                        }
+                        continue;
+                    }
+
+                    // Step boundaries carry neither a log nor a result either
+                    if let Some(event) = &action_response.step {
+                        // Losing the timings of a step does not fail the action
+                        if let Err(e) = self
+                            .action_service
+                            .record_step(action_id, attempt, event)
+                            .await
+                        {
+                            error!("Failed to record step of action {}: {}", action_id, e);
+                        }
+                        continue;
+                    }
+
+                    // The output is only traced once joined into lines, so
+                    // that the secrets split over chunks are masked too
+                    info!(
+                        "[SCHEDULER] RESPONSE = ActionResponse {{ action_id: {}, result: {:?} }}",
+                        action_response.action_id, action_response.result,
+                    );
+
+                    // Append log data to the action, keeping the metadata
+                    // the agent attached to the line
+                    let chunk = NewLog {
+                        attempt,
+                        data: action_response.log,
+                        ts: action_response
+                            .timestamp
+                            .unwrap_or_else(OffsetDateTime::now_utc),
+                        stream: action_response.stream,
+                        step_index: action_response.step_index,
+                    };
+                    for line in lines.push(chunk) {
+                        self.store_log(action_id, line, secret_values).await?;
+                    }
+
+                    // Update action status in the database
+                    if let Some(result) = &action_response.result {
+                        let previous = status;
+                        status = result.completion;
+                        if status.is_terminal() {
+                            // The output ends with the line reporting the result
+                            if let Some(line) = lines.flush() {
+                                self.store_log(action_id, line, secret_values).await?;
+                            }
+                            exit_code = result.exit_code;
+                            // A cancelled action keeps its status whatever
+                            // the agent reports while stopping it
+                            if self.is_cancelled(action_id).await? {
+                                status = ActionStatus::Cancelled;
+                            }
+                        }
+                        // Most chunks repeat the status of the action, which
+                        // is only stored, along with its pipeline, when it changes
+                        if status != previous {
+                            self.update_status(action_response.action_id as i64, status)
+                                .await?;
+                        }
+                    }
+                }
+
+                Err(e) => {
+                    if let Some(line) = lines.flush() {
+                        self.store_log(action_id, line, secret_values).await?;
+                    }
+                    error!(
+                        "Error from scheduler stream for action {}: {:?}",
+                        action_id, e
+                    );
+                    self.append_log(
+                        action_id,
+                        attempt,
+                        format!("Error from scheduler: {}", e),
+                        secret_values,
+                    )
+                    .await?;
+                    self.update_status(action_id, ActionStatus::Error).await?;
+                    return Ok(AttemptOutcome {
+                        status: ActionStatus::Error,
+                        exit_code: None,
+                        agent_lost: true,
+                    });
+                }
+            }
+        }
+
+        if let Some(line) = lines.flush() {
+            self.store_log(action_id, line, secret_values).await?;
+        }
+
+        // A stream closed before the action reported a result means the run was lost
+        if !status.is_terminal() {
+            self.append_log(
+                action_id,
+                attempt,
+                "Action ended without a result".to_string(),
+                secret_values,
+            )
+            .await?;
+            self.update_status(action_id, ActionStatus::Error).await?;
+            return Ok(AttemptOutcome {
+                status: ActionStatus::Error,
+                exit_code: None,
+                agent_lost: true,
+            });
         }
+
+        Ok(AttemptOutcome {
+            status,
+            exit_code,
+            agent_lost: false,
+        })
+    }
+
+    /// Evaluates the `if` condition of an action against the trigger context
+    /// of its pipeline, marking the action as skipped when it does not hold.
+    async fn should_run(
+        &self,
+        action: &Action,
+        trigger: &TriggerContext,
+    ) -> Result<bool, SchedulerError> {
+        let Some(condition) = &action.condition else {
+            return Ok(true);
+        };
+        match Condition::parse(condition) {
+            Ok(condition) if condition.evaluate(trigger) => Ok(true),
+            Ok(_) => {
+                info!(
+                    "Skipping action {} because its condition is false",
+                    action.name
+                );
+                self.update_status(action.id, ActionStatus::Skipped).await?;
+                Ok(false)
+            }
+            Err(e) => {
+                error!("Invalid condition of action {}: {}", action.id, e);
+                self.append_log(action.id, action.attempt, e.to_string(), &[])
+                    .await?;
+                self.update_status(action.id, ActionStatus::Error).await?;
+                Ok(false)
+            }
+        }
+    }
+
+    /// Whether the action was cancelled since the pipeline started.
+    async fn is_cancelled(&self, action_id: i64) -> Result<bool, SchedulerError> {
+        let action = self
+            .action_service
+            .find_by_id(action_id)
+            .await
+            .map_err(|e| {
+                error!("Failed to find action {}: {:?}", action_id, e);
+                SchedulerError::Error(format!("Failed to find action: {}", e))
+            })?;
+        Ok(action.status == ActionStatus::Cancelled)
+    }
+
+    /// Moves an action to a new status. Returns `false` when the action
+    /// cannot go there from its current status, such as a late report of an
+    /// action that was cancelled meanwhile: the update is then dropped.
+    async fn update_status(
+        &self,
+        action_id: i64,
+        status: ActionStatus,
+    ) -> Result<bool, SchedulerError> {
+        match self
+            .action_service
+            .update_status(action_id, &status.as_proto_name().to_string())
+            .await
+        {
+            Ok(action) => {
+                self.report_status(action.pipeline_id).await;
+                Ok(true)
+            }
+            Err(ActionError::InvalidTransition { from, to }) => {
+                warn!(
+                    "Ignoring transition of action {} from {} to {}",
+                    action_id, from, to
+                );
+                Ok(false)
+            }
+            Err(e) => {
+                error!("Failed to update action {} status: {:?}", action_id, e);
+                Err(SchedulerError::Error(format!(
+                    "Failed to update action: {}",
+                    e
+                )))
+            }
+        }
+    }
+
+    /// Schedules a failed action again for a new attempt. Returns `false`
+    /// when the action cannot be retried anymore, such as one cancelled
+    /// after its last attempt failed.
+    async fn retry(&self, action_id: i64, attempt: i32) -> Result<bool, SchedulerError> {
+        match self.action_service.retry(action_id, attempt).await {
+            Ok(action) => {
+                self.report_status(action.pipeline_id).await;
+                Ok(true)
+            }
+            Err(ActionError::InvalidTransition { from, .. }) => {
+                warn!("Not retrying action {} from {}", action_id, from);
+                Ok(false)
+            }
+            Err(e) => {
+                error!("Failed to retry action {}: {:?}", action_id, e);
+                Err(SchedulerError::Error(format!(
+                    "Failed to retry action: {}",
+                    e
+                )))
+            }
+        }
+    }
+
+    /// Reports the status of a pipeline to the forge of its repository, once
+    /// per change of status. A failed report is logged, and neither fails
+    /// the pipeline nor is sent again.
+    async fn report_status(&self, pipeline_id: i64) {
+        let Some(reporter) = &self.status_reporter else {
+            return;
+        };
+        match self
+            .pipeline_repository
+            .claim_status_report(pipeline_id)
+            .await
+        {
+            Ok(Some(pipeline)) => {
+                if let Err(e) = reporter.report(&pipeline).await {
+                    error!("Failed to report status of pipeline {}: {}", pipeline_id, e);
+                }
+            }
+            Ok(None) => {}
+            Err(e) => error!("Failed to find status of pipeline {}: {:?}", pipeline_id, e),
+        }
+    }
+
+    /// Cancels the actions of a run that stops early: those that were not
+    /// dispatched yet, and those still running on an agent.
+    async fn cancel_remaining(&self, pending: Vec<Action>, running: Vec<i64>) {
+        for action in pending {
+            if let Err(e) = self.update_status(action.id, ActionStatus::Cancelled).await {
+                error!("Failed to cancel action {}: {:?}", action.id, e);
+            }
+        }
+        for action_id in running {
+            if let Err(e) = SchedulerService::cancel_action(self, action_id).await {
+                error!("Failed to cancel action {}: {:?}", action_id, e);
+            }
+        }
+    }
+
+    /// Notifies the webhooks of the repository that a pipeline has finished,
+    /// unless it was cancelled or some of its actions could not run.
+    async fn notify_finished(&self, pipeline_id: i64) {
+        match self.pipeline_repository.find_by_id(pipeline_id).await {
+            Ok(pipeline) => {
+                if let Some(event) = WebhookEvent::finished(pipeline.status) {
+                    self.webhook_service
+                        .notify(
+                            &pipeline.repository_url,
+                            event,
+                            pipeline_payload(event, &pipeline),
+                        )
+                        .await;
+                }
+            }
+            Err(e) => error!("Failed to find status of pipeline {}: {:?}", pipeline_id, e),
+        }
+    }
+
+    /// Stores a line written by the controller itself in the logs of an
+    /// action attempt.
+    async fn append_log(
+        &self,
+        action_id: i64,
+        attempt: i32,
+        log: String,
+        secrets: &[String],
+    ) -> Result<(), SchedulerError> {
+        self.store_log(action_id, NewLog::system(attempt, log), secrets)
+            .await
+    }
+
+    /// Stores a log line of an action attempt once every secret value has been
+    /// masked out of it, and sends it to the clients following the action.
+    async fn store_log(
+        &self,
+        action_id: i64,
+        mut log: NewLog,
+        secrets: &[String],
+    ) -> Result<(), SchedulerError> {
+        log.data = mask_secrets(&log.data, secrets);
+        self.action_service
+            .append_log(action_id, log)
+            .await
+            .map(|log| self.log_hub.publish(&log))
+            .map_err(|e| {
+                error!("Failed to store log for action {}: {:?}", action_id, e);
+                SchedulerError::Error(format!("Failed to store log: {}", e))
+            })
     }
 }
 
 #[async_trait]
-impl<A, S, R> SchedulerService for SchedulerServiceImpl<A, S, R>
+impl<A, S, R, K, F, W, P> SchedulerService for SchedulerServiceImpl<A, S, R, K, F, W, P>
 where
     A: ActionService + Send + Sync,
     S: SchedulerClient + Send + Sync,
     R: PipelineRepository + Send + Sync,
+    K: SecretService + Send + Sync,
+    F: ArtifactService + Send + Sync,
+    W: WebhookService + Send + Sync,
+    P: StatusReporter + Send + Sync,
 {
     async fn execute_pipeline(&self, pipeline_id: i64) -> Result<(), SchedulerError> {
         // Find all actions associated with the pipeline
-        let mut actions = self
+        let mut pending = self
             .action_service
             .find_by_pipeline_id(pipeline_id)
             .await
@@ -82,93 +696,194 @@ where
             "Scheduling actions for pipeline {} with id {} with repository URL: {}",
             pipeline.name, pipeline_id, repo_url
         );
+        // The commit shows the pipeline as pending before any action starts
+        self.report_status(pipeline_id).await;
+        self.webhook_service
+            .notify(
+                &repo_url,
+                WebhookEvent::PipelineStarted,
+                pipeline_payload(WebhookEvent::PipelineStarted, &pipeline),
+            )
+            .await;
 
-        // Sort actions by their IDs to ensure they are processed in the correct order
-        actions.sort_by_key(|action| action.id);
-
-        // Acquire the scheduler client lock to ensure thread safety
-        let client = self.scheduler_client.lock().await;
+        let secrets = self
+            .secret_service
+            .reveal_secrets(&repo_url)
+            .await
+            .map_err(|e| SchedulerError::Error(format!("Failed to load secrets: {}", e)))?;
 
-        for action in actions {
-            info!("Scheduling action {} with ID {}", action.name, action.id);
+        let credentials = self
+            .secret_service
+            .reveal_credentials(&repo_url)
+            .await
+            .map_err(|e| SchedulerError::Error(format!("Failed to load credentials: {}", e)))?;
 
-            // Prepare the action request for the scheduler gRPC
-            let action_request = DomainActionRequest {
-                action_id: action.id as u32,
-                context: ExecutionContext {
-                    r#type: action.r#type as i32,
-                    container_image: Some(action.container_uri.clone()),
-                },
-                commands: action.commands.clone(),
-                repo_url: repo_url.clone(),
-            };
+        // Every action runs on the commit that triggered the pipeline
+        let checkout = Checkout {
+            revision: pipeline.trigger.commit_sha.clone(),
+            git_ref: pipeline.trigger.git_ref.clone(),
+            depth: pipeline.checkout.depth.max(0) as u32,
+            submodules: pipeline.checkout.submodules,
+            credentials,
+        };
 
-            // Call the scheduler client to schedule the action and get a response stream
-            // A response stream is a stream of ActionResponse items
-            let mut response_stream =
-                client.schedule_action(action_request).await.map_err(|e| {
-                    error!("Failed to schedule action {}: {:?}", action.id, e);
-                    SchedulerError::Error("SchedulerError: ".into())
-                })?;
+        // Sort actions by their IDs so that ready actions are dispatched in a stable
+        // order. IDs follow the order of the actions map, not the manifest
+        pending.sort_by_key(|action| action.id);
 
-            while let Some(item) = response_stream.next().await {
-                // Process each item in the response stream
-                match item {
-                    Ok(action_response) => {
+        // Walk the dependency graph: every action whose needs are all finished is
+        // dispatched right away, and the graph is re-evaluated each time one finishes.
+        // Actions needing a failed (or skipped) action are skipped instead.
+        // The actions of a re-run that reuse an earlier result are done already
+        let (reused, rest): (Vec<Action>, Vec<Action>) = pending
+            .into_iter()
+            .partition(|action| action.status == ActionStatus::Completed);
+        pending = rest;
+        let mut finished: HashSet<String> = reused.into_iter().map(|action| action.name).collect();
+        let mut failed: HashSet<String> = HashSet::new();
+        let mut running = FuturesUnordered::new();
+        // Actions dispatched and not finished yet, by name
+        let mut in_flight: HashMap<String, i64> = HashMap::new();
+        loop {
+            // Skipping is transitive, so repeat until no more action gets skipped
+            loop {
+                let (skipped, rest): (Vec<Action>, Vec<Action>) = pending
+                    .into_iter()
+                    .partition(|action| action.needs.iter().any(|need| failed.contains(need)));
+                pending = rest;
+                if skipped.is_empty() {
+                    break;
+                }
+                for action in skipped {
+                    if !self.is_cancelled(action.id).await? {
                         info!(
-                            "[SCHEDULER] RESPONSE = ActionResponse {{ action_id: {}, log: {:?}, result: {:?} }}",
-                            action_response.action_id,
-                            action_response.log,
-                            action_response.result,
+                            "Skipping action {} because one of its needs failed",
+                            action.name
                         );
+                        self.update_status(action.id, ActionStatus::Skipped).await?;
+                    }
+                    failed.insert(action.name);
+                }
+            }
 
-                        // Update action status in the database
-                        if let Some(result) = &action_response.result {
-                            let status_str = result.completion.as_proto_name();
-                            self.action_service
-                                .update_status(
-                                    action_response.action_id as i64,
-                                    &status_str.to_string(),
-                                )
-                                .await
-                                .map_err(|e| {
-                                    error!(
-                                        "Failed to update action {} status: {:?}",
-                                        action_response.action_id, e
-                                    );
-                                    SchedulerError::Error(format!("Failed to update action: {}", e))
-                                })?;
-                        }
+            let (ready, blocked): (Vec<Action>, Vec<Action>) = pending
+                .into_iter()
+                .partition(|action| action.needs.iter().all(|need| finished.contains(need)));
+            pending = blocked;
 
-                        // Append log data to the action
-                        // This assumes that the action_response.log is a String
-                        let log_data = action_response.log.clone();
-                        self.action_service
-                            .append_log(action_response.action_id as i64, log_data)
-                            .await
-                            .map_err(|e| {
-                                error!(
-                                    "Failed to store log for action {}: {:?}",
-                                    action_response.action_id, e
-                                );
-                                SchedulerError::Error(format!("Failed to store log: {}", e))
-                            })?;
-                    }
+            // Actions whose condition does not hold are skipped with their dependents
+            let mut skipped_by_condition = false;
+            for action in ready {
+                if !self.should_run(&action, &pipeline.trigger).await? {
+                    failed.insert(action.name);
+                    skipped_by_condition = true;
+                    continue;
+                }
+                let id = action.id;
+                let name = action.name.clone();
+                let allow_failure = action.allow_failure;
+                let log_hub = self.log_hub.clone();
+                log_hub.open(id);
+                in_flight.insert(name.clone(), id);
+                let run = self.run_action(action, repo_url.clone(), checkout.clone(), &secrets);
+                running.push(async move {
+                    let result = run.await;
+                    // Every line of the action is stored by now
+                    log_hub.close(id);
+                    (name, allow_failure, result)
+                });
+            }
 
-                    Err(e) => {
-                        error!(
-                            "Error from scheduler stream for action {}: {:?}",
-                            action.id, e
-                        );
-                        return Err(SchedulerError::Error(format!(
-                            "Error from scheduler: {}",
-                            e
-                        )));
-                    }
+            if skipped_by_condition {
+                continue;
+            }
+
+            let (name, allow_failure, result) = match running.next().await {
+                Some(outcome) => outcome,
+                None => break,
+            };
+            in_flight.remove(&name);
+            match result {
+                Ok(ActionStatus::Completed) => {
+                    finished.insert(name);
+                }
+                Ok(_) if allow_failure => {
+                    info!("Action {} failed but is allowed to fail", name);
+                    finished.insert(name);
+                }
+                Ok(_) => {
+                    failed.insert(name);
+                }
+                Err(e) => {
+                    // The outcome of an action could not be recorded: stop the
+                    // run, cancel whatever was not dispatched yet or is still
+                    // running, and wait for the running actions to stop
+                    self.cancel_remaining(pending, in_flight.into_values().collect())
+                        .await;
+                    while running.next().await.is_some() {}
+                    self.notify_finished(pipeline_id).await;
+                    return Err(e);
                 }
             }
         }
 
+        // Nothing is running anymore: the actions left wait on each other
+        if !pending.is_empty() {
+            let names: Vec<String> = pending.iter().map(|action| action.name.clone()).collect();
+            self.cancel_remaining(pending, Vec::new()).await;
+            self.notify_finished(pipeline_id).await;
+            return Err(SchedulerError::Error(format!(
+                "Unresolvable dependencies for actions: {}",
+                names.join(", ")
+            )));
+        }
+
+        self.notify_finished(pipeline_id).await;
         Ok(())
     }
+
+    async fn cancel_action(&self, action_id: i64) -> Result<bool, SchedulerError> {
+        let action = self
+            .action_service
+            .find_by_id(action_id)
+            .await
+            .map_err(|e| SchedulerError::Error(format!("Failed to find action: {}", e)))?;
+        if action.status.is_terminal() {
+            return Ok(false);
+        }
+
+        // Pending actions are not on an agent yet: marking them is enough for
+        // the pipeline run not to dispatch them
+        if action.status != ActionStatus::Pending {
+            if let Err(e) = self
+                .scheduler_client
+                .lock()
+                .await
+                .cancel_action(action_id as u32)
+                .await
+            {
+                error!(
+                    "Failed to cancel action {} on its agent: {:?}",
+                    action_id, e
+                );
+            }
+        }
+
+        info!("Cancelling action {} ({})", action.name, action_id);
+        // The action may have finished in the meantime
+        if !self
+            .update_status(action_id, ActionStatus::Cancelled)
+            .await?
+        {
+            return Ok(false);
+        }
+        self.append_log(
+            action_id,
+            action.attempt,
+            "Action cancelled".to_string(),
+            &[],
+        )
+        .await?;
+        Ok(true)
+    }
 }
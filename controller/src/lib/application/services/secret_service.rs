@@ -0,0 +1,213 @@
+use async_trait::async_trait;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::{
+    application::ports::secret_service::SecretService,
+    domain::secrets::{
+        entities::{
+            is_valid_secret_name, Credentials, CredentialsKind, RepositoryCredentials, Secret,
+            SecretError, DEFAULT_TOKEN_USERNAME,
+        },
+        ports::SecretRepository,
+        services::SecretCipher,
+    },
+    infrastructure::{
+        crypto::aes_gcm_cipher::AesGcmSecretCipher,
+        repositories::secret_repository::PostgresSecretRepository,
+    },
+};
+
+pub type DefaultSecretServiceImpl = SecretServiceImpl<PostgresSecretRepository, AesGcmSecretCipher>;
+
+pub struct SecretServiceImpl<R, C>
+where
+    R: SecretRepository + Send + Sync,
+    C: SecretCipher + Send + Sync,
+{
+    secret_repository: Arc<R>,
+    cipher: Arc<C>,
+}
+
+impl<R, C> SecretServiceImpl<R, C>
+where
+    R: SecretRepository + Send + Sync,
+    C: SecretCipher + Send + Sync,
+{
+    pub fn new(secret_repository: Arc<R>, cipher: Arc<C>) -> Self {
+        Self {
+            secret_repository,
+            cipher,
+        }
+    }
+}
+
+/// Ties a ciphertext to the secret it was created for.
+fn associated_data(repository_url: &str, name: &str) -> Vec<u8> {
+    format!("{}\n{}", repository_url, name).into_bytes()
+}
+
+/// Ties a ciphertext to the credentials it was created for, apart from the
+/// secrets of the repository.
+fn credentials_associated_data(repository_url: &str, kind: CredentialsKind) -> Vec<u8> {
+    format!("credentials\n{}\n{}", repository_url, kind.as_str()).into_bytes()
+}
+
+#[async_trait]
+impl<R, C> SecretService for SecretServiceImpl<R, C>
+where
+    R: SecretRepository + Send + Sync,
+    C: SecretCipher + Send + Sync,
+{
+    async fn create_secret(
+        &self,
+        repository_url: &str,
+        name: &str,
+        value: &str,
+    ) -> Result<Secret, SecretError> {
+        if !is_valid_secret_name(name) {
+            return Err(SecretError::InvalidName(name.to_string()));
+        }
+        let (nonce, ciphertext) = self
+            .cipher
+            .encrypt(value.as_bytes(), &associated_data(repository_url, name))?;
+        self.secret_repository
+            .upsert(
+                repository_url.to_string(),
+                name.to_string(),
+                nonce,
+                ciphertext,
+            )
+            .await
+    }
+
+    async fn list_secrets(&self, repository_url: &str) -> Result<Vec<Secret>, SecretError> {
+        self.secret_repository
+            .list(repository_url.to_string())
+            .await
+    }
+
+    async fn delete_secret(&self, repository_url: &str, name: &str) -> Result<(), SecretError> {
+        self.secret_repository
+            .delete(repository_url.to_string(), name.to_string())
+            .await
+    }
+
+    async fn reveal_secrets(
+        &self,
+        repository_url: &str,
+    ) -> Result<HashMap<String, String>, SecretError> {
+        let encrypted = self
+            .secret_repository
+            .find_encrypted(repository_url.to_string())
+            .await?;
+        encrypted
+            .into_iter()
+            .map(|secret| {
+                let plaintext = self.cipher.decrypt(
+                    &secret.nonce,
+                    &secret.ciphertext,
+                    &associated_data(repository_url, &secret.name),
+                )?;
+                let value =
+                    String::from_utf8(plaintext).map_err(|_| SecretError::DecryptionError)?;
+                Ok((secret.name, value))
+            })
+            .collect()
+    }
+
+    async fn set_credentials(
+        &self,
+        repository_url: &str,
+        kind: CredentialsKind,
+        username: Option<&str>,
+        value: &str,
+    ) -> Result<RepositoryCredentials, SecretError> {
+        let username = match kind {
+            CredentialsKind::SshKey => {
+                if username.is_some() {
+                    return Err(SecretError::InvalidCredentials(
+                        "a deploy key has no username".to_string(),
+                    ));
+                }
+                if !value.contains("PRIVATE KEY") {
+                    return Err(SecretError::InvalidCredentials(
+                        "the value is not a private key".to_string(),
+                    ));
+                }
+                None
+            }
+            CredentialsKind::HttpsToken => {
+                if value.trim().is_empty() {
+                    return Err(SecretError::InvalidCredentials(
+                        "the token is empty".to_string(),
+                    ));
+                }
+                Some(
+                    username
+                        .filter(|username| !username.trim().is_empty())
+                        .unwrap_or(DEFAULT_TOKEN_USERNAME)
+                        .to_string(),
+                )
+            }
+        };
+        let (nonce, ciphertext) = self.cipher.encrypt(
+            value.as_bytes(),
+            &credentials_associated_data(repository_url, kind),
+        )?;
+        self.secret_repository
+            .upsert_credentials(
+                repository_url.to_string(),
+                kind,
+                username,
+                nonce,
+                ciphertext,
+            )
+            .await
+    }
+
+    async fn find_credentials(
+        &self,
+        repository_url: &str,
+    ) -> Result<RepositoryCredentials, SecretError> {
+        self.secret_repository
+            .find_credentials(repository_url.to_string())
+            .await
+    }
+
+    async fn delete_credentials(&self, repository_url: &str) -> Result<(), SecretError> {
+        self.secret_repository
+            .delete_credentials(repository_url.to_string())
+            .await
+    }
+
+    async fn reveal_credentials(
+        &self,
+        repository_url: &str,
+    ) -> Result<Option<Credentials>, SecretError> {
+        let encrypted = match self
+            .secret_repository
+            .find_encrypted_credentials(repository_url.to_string())
+            .await?
+        {
+            Some(encrypted) => encrypted,
+            None => return Ok(None),
+        };
+        let plaintext = self.cipher.decrypt(
+            &encrypted.nonce,
+            &encrypted.ciphertext,
+            &credentials_associated_data(repository_url, encrypted.kind),
+        )?;
+        let value = String::from_utf8(plaintext).map_err(|_| SecretError::DecryptionError)?;
+        let credentials = match encrypted.kind {
+            CredentialsKind::SshKey => Credentials::SshKey { private_key: value },
+            CredentialsKind::HttpsToken => Credentials::HttpsToken {
+                username: encrypted
+                    .username
+                    .unwrap_or_else(|| DEFAULT_TOKEN_USERNAME.to_string()),
+                token: value,
+            },
+        };
+        Ok(Some(credentials))
+    }
+}
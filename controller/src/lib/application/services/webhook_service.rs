@@ -0,0 +1,288 @@
+use async_trait::async_trait;
+use reqwest::Url;
+use serde_json::Value;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+
+use crate::{
+    application::ports::webhook_service::WebhookService,
+    domain::{
+        secrets::{entities::SecretError, services::SecretCipher},
+        webhooks::{
+            entities::{
+                retry_delay, DeliveryStatus, EncryptedWebhook, Webhook, WebhookDelivery,
+                WebhookError, WebhookEvent, MAX_DELIVERY_ATTEMPTS,
+            },
+            ports::{WebhookRepository, WebhookSender},
+        },
+    },
+    infrastructure::{
+        crypto::aes_gcm_cipher::AesGcmSecretCipher,
+        repositories::webhook_repository::PostgresWebhookRepository,
+        webhooks::http_webhook_sender::HttpWebhookSender,
+    },
+};
+
+pub type DefaultWebhookServiceImpl =
+    WebhookServiceImpl<PostgresWebhookRepository, AesGcmSecretCipher, HttpWebhookSender>;
+
+/// Deliveries listed for a webhook.
+const DELIVERY_LOG_SIZE: i64 = 100;
+
+pub struct WebhookServiceImpl<R, C, S>
+where
+    R: WebhookRepository + Send + Sync + 'static,
+    C: SecretCipher + Send + Sync,
+    S: WebhookSender + Send + Sync + 'static,
+{
+    webhook_repository: Arc<R>,
+    cipher: Arc<C>,
+    sender: Arc<S>,
+}
+
+impl<R, C, S> WebhookServiceImpl<R, C, S>
+where
+    R: WebhookRepository + Send + Sync + 'static,
+    C: SecretCipher + Send + Sync,
+    S: WebhookSender + Send + Sync + 'static,
+{
+    pub fn new(webhook_repository: Arc<R>, cipher: Arc<C>, sender: Arc<S>) -> Self {
+        Self {
+            webhook_repository,
+            cipher,
+            sender,
+        }
+    }
+
+    fn reveal_secret(&self, encrypted: &EncryptedWebhook) -> Result<String, WebhookError> {
+        let plaintext = self.cipher.decrypt(
+            &encrypted.nonce,
+            &encrypted.ciphertext,
+            &associated_data(&encrypted.webhook.repository_url, &encrypted.webhook.url),
+        )?;
+        String::from_utf8(plaintext).map_err(|_| SecretError::DecryptionError.into())
+    }
+
+    /// Sends a delivery in the background, so that neither the pipeline nor
+    /// the request it comes from waits for the webhook.
+    fn spawn_delivery(&self, url: String, secret: String, delivery: WebhookDelivery) {
+        let webhook_repository = self.webhook_repository.clone();
+        let sender = self.sender.clone();
+        tokio::spawn(deliver(webhook_repository, sender, url, secret, delivery));
+    }
+}
+
+/// Ties a ciphertext to the webhook it was created for.
+fn associated_data(repository_url: &str, url: &str) -> Vec<u8> {
+    format!("webhook\n{}\n{}", repository_url, url).into_bytes()
+}
+
+/// Sends a delivery until the webhook accepts it with a 2xx response, waiting
+/// longer after each failed attempt. Every attempt is recorded, a delivery
+/// already attempted goes on from its next attempt.
+async fn deliver<R, S>(
+    webhook_repository: Arc<R>,
+    sender: Arc<S>,
+    url: String,
+    secret: String,
+    delivery: WebhookDelivery,
+) where
+    R: WebhookRepository + Send + Sync,
+    S: WebhookSender + Send + Sync,
+{
+    if delivery.attempts > 0 {
+        tokio::time::sleep(retry_delay(delivery.attempts)).await;
+    }
+    for attempt in delivery.attempts + 1..=MAX_DELIVERY_ATTEMPTS {
+        let (response_status, error) = match sender.send(&url, &delivery, &secret).await {
+            Ok(status) if (200..300).contains(&status) => (Some(status as i32), None),
+            Ok(status) => (
+                Some(status as i32),
+                Some(format!("The webhook answered with status {}", status)),
+            ),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        let status = match &error {
+            None => DeliveryStatus::Succeeded,
+            Some(_) if attempt == MAX_DELIVERY_ATTEMPTS => DeliveryStatus::Failed,
+            Some(_) => DeliveryStatus::Pending,
+        };
+        if let Some(error) = &error {
+            warn!(
+                "Attempt {} of delivery {} to {} failed: {}",
+                attempt, delivery.id, url, error
+            );
+        }
+        if let Err(e) = webhook_repository
+            .record_attempt(delivery.id, status, response_status, error)
+            .await
+        {
+            error!(
+                "Failed to record attempt of delivery {}: {}",
+                delivery.id, e
+            );
+        }
+        if status != DeliveryStatus::Pending {
+            return;
+        }
+        tokio::time::sleep(retry_delay(attempt)).await;
+    }
+}
+
+#[async_trait]
+impl<R, C, S> WebhookService for WebhookServiceImpl<R, C, S>
+where
+    R: WebhookRepository + Send + Sync + 'static,
+    C: SecretCipher + Send + Sync,
+    S: WebhookSender + Send + Sync + 'static,
+{
+    async fn create_webhook(
+        &self,
+        repository_url: &str,
+        url: &str,
+        mut events: Vec<WebhookEvent>,
+        secret: &str,
+    ) -> Result<Webhook, WebhookError> {
+        match Url::parse(url) {
+            Ok(parsed) if matches!(parsed.scheme(), "http" | "https") => {}
+            _ => {
+                return Err(WebhookError::InvalidWebhook(format!(
+                    "{} is not an HTTP URL",
+                    url
+                )))
+            }
+        }
+        events.sort_by_key(|event| event.as_str());
+        events.dedup();
+        if events.is_empty() {
+            return Err(WebhookError::InvalidWebhook(
+                "no event is subscribed to".to_string(),
+            ));
+        }
+        if secret.is_empty() {
+            return Err(WebhookError::InvalidWebhook(
+                "the secret is empty".to_string(),
+            ));
+        }
+        let (nonce, ciphertext) = self
+            .cipher
+            .encrypt(secret.as_bytes(), &associated_data(repository_url, url))?;
+        self.webhook_repository
+            .create(
+                repository_url.to_string(),
+                url.to_string(),
+                events,
+                nonce,
+                ciphertext,
+            )
+            .await
+    }
+
+    async fn list_webhooks(&self, repository_url: &str) -> Result<Vec<Webhook>, WebhookError> {
+        self.webhook_repository
+            .list(repository_url.to_string())
+            .await
+    }
+
+    async fn delete_webhook(&self, id: i64) -> Result<(), WebhookError> {
+        self.webhook_repository.delete(id).await
+    }
+
+    async fn list_deliveries(&self, webhook_id: i64) -> Result<Vec<WebhookDelivery>, WebhookError> {
+        // A webhook without deliveries is told apart from a missing one
+        self.webhook_repository.find_encrypted(webhook_id).await?;
+        self.webhook_repository
+            .list_deliveries(webhook_id, DELIVERY_LOG_SIZE)
+            .await
+    }
+
+    async fn redeliver(
+        &self,
+        webhook_id: i64,
+        delivery_id: i64,
+    ) -> Result<WebhookDelivery, WebhookError> {
+        let encrypted = self.webhook_repository.find_encrypted(webhook_id).await?;
+        let original = self
+            .webhook_repository
+            .find_delivery(webhook_id, delivery_id)
+            .await?;
+        let secret = self.reveal_secret(&encrypted)?;
+        let delivery = self
+            .webhook_repository
+            .create_delivery(webhook_id, original.event, original.payload)
+            .await?;
+        info!(
+            "Redelivering delivery {} of webhook {} as {}",
+            delivery_id, webhook_id, delivery.id
+        );
+        self.spawn_delivery(encrypted.webhook.url, secret, delivery.clone());
+        Ok(delivery)
+    }
+
+    async fn notify(&self, repository_url: &str, event: WebhookEvent, payload: Value) {
+        let webhooks = match self
+            .webhook_repository
+            .find_subscribed(repository_url.to_string(), event)
+            .await
+        {
+            Ok(webhooks) => webhooks,
+            Err(e) => {
+                error!("Failed to find the webhooks of {}: {}", repository_url, e);
+                return;
+            }
+        };
+        for encrypted in webhooks {
+            let webhook_id = encrypted.webhook.id;
+            let secret = match self.reveal_secret(&encrypted) {
+                Ok(secret) => secret,
+                Err(e) => {
+                    error!(
+                        "Failed to decrypt the secret of webhook {}: {}",
+                        webhook_id, e
+                    );
+                    continue;
+                }
+            };
+            match self
+                .webhook_repository
+                .create_delivery(webhook_id, event, payload.clone())
+                .await
+            {
+                Ok(delivery) => self.spawn_delivery(encrypted.webhook.url, secret, delivery),
+                Err(e) => error!("Failed to create delivery of webhook {}: {}", webhook_id, e),
+            }
+        }
+    }
+
+    async fn resume_deliveries(&self) {
+        let deliveries = match self.webhook_repository.list_pending().await {
+            Ok(deliveries) => deliveries,
+            Err(e) => {
+                error!("Failed to find the pending deliveries: {}", e);
+                return;
+            }
+        };
+        for delivery in deliveries {
+            let encrypted = match self
+                .webhook_repository
+                .find_encrypted(delivery.webhook_id)
+                .await
+                .and_then(|encrypted| {
+                    let secret = self.reveal_secret(&encrypted)?;
+                    Ok((encrypted.webhook.url, secret))
+                }) {
+                Ok(encrypted) => encrypted,
+                Err(e) => {
+                    error!("Failed to resume delivery {}: {}", delivery.id, e);
+                    continue;
+                }
+            };
+            info!(
+                "Resuming delivery {} after {} attempts",
+                delivery.id, delivery.attempts
+            );
+            let (url, secret) = encrypted;
+            self.spawn_delivery(url, secret, delivery);
+        }
+    }
+}
@@ -1,5 +1,5 @@
-use std::fmt::Display;
 use clap::Parser;
+use std::fmt::Display;
 
 #[derive(Parser, Debug, Clone)]
 pub struct Config {
@@ -33,9 +33,43 @@ pub struct Config {
     ///   --grpc http://127.0.0.1:50051
     #[clap(env, long)]
     pub grpc: String,
-    
+
     #[clap(env, long)]
     pub release_agent: String,
+
+    /// Master key used to encrypt repository secrets at rest.
+    /// Base64 encoded 256-bit key, e.g. generated with `openssl rand -base64 32`.
+    /// Read from the --secrets-key CLI flag, then the SECRETS_KEY env var.
+    /// If unset, creating or resolving a secret fails.
+    #[clap(env, long)]
+    pub secrets_key: Option<String>,
+
+    /// Directory where the artifacts archives of the actions are stored.
+    /// Read from the --artifacts-dir CLI flag, then the ARTIFACTS_DIR env var.
+    #[clap(env, long, default_value = "artifacts")]
+    pub artifacts_dir: String,
+
+    /// Token posting the status of the pipelines on their commits on GitHub.
+    /// It needs the commit statuses write permission on the repositories.
+    /// Read from the --github-token CLI flag, then the GITHUB_TOKEN env var.
+    /// If unset, no status is reported.
+    #[clap(env, long)]
+    pub github_token: Option<String>,
+
+    /// GitHub REST API endpoint, to change for GitHub Enterprise.
+    #[clap(env, long, default_value = "https://api.github.com")]
+    pub github_api_url: String,
+
+    /// Host of the repositories served by the GitHub API. The statuses of the
+    /// repositories hosted elsewhere are not reported.
+    #[clap(env, long, default_value = "github.com")]
+    pub github_host: String,
+
+    /// Base URL of the web client, linked from the commit statuses.
+    /// Example:
+    ///   --web-url https://ci.example.com
+    #[clap(env, long)]
+    pub web_url: Option<String>,
 }
 
 impl Display for Config {
@@ -46,4 +80,4 @@ impl Display for Config {
             self.http, self.database_url, self.grpc
         )
     }
-}
\ No newline at end of file
+}
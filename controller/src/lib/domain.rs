@@ -1,6 +1,10 @@
 pub mod action;
-pub mod releases;
+pub mod artifacts;
 pub mod command;
-pub mod pipeline;
 pub mod log;
+pub mod pipeline;
+pub mod releases;
+pub mod reporting;
 pub mod scheduler;
+pub mod secrets;
+pub mod webhooks;
@@ -1,9 +1,16 @@
 use core::fmt;
+use std::collections::HashMap;
 use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 use sqlx::prelude::FromRow;
+use sqlx::types::Json;
 use thiserror::Error;
+use time::OffsetDateTime;
+
+use crate::domain::command::entities::command::Step;
+use crate::domain::log::entities::log::LogStream;
+use crate::domain::secrets::entities::Credentials;
 
 #[derive(Debug, Clone, PartialEq, Deserialize)]
 pub enum ActionType {
@@ -53,6 +60,12 @@ pub enum ActionStatus {
     Running,
     Completed,
     Error,
+    /// The action was never dispatched because one of its needs failed.
+    Skipped,
+    /// The action was never dispatched because the pipeline run was aborted.
+    Cancelled,
+    /// The action ran past its timeout and was stopped by the agent.
+    TimedOut,
 }
 
 impl ActionStatus {
@@ -63,8 +76,47 @@ impl ActionStatus {
             ActionStatus::Running => "ACTION_STATUS_RUNNING",
             ActionStatus::Completed => "ACTION_STATUS_COMPLETED",
             ActionStatus::Error => "ACTION_STATUS_ERROR",
+            ActionStatus::Skipped => "ACTION_STATUS_SKIPPED",
+            ActionStatus::Cancelled => "ACTION_STATUS_CANCELLED",
+            ActionStatus::TimedOut => "ACTION_STATUS_TIMED_OUT",
         }
     }
+
+    /// Whether the action has reached a state it will not leave anymore.
+    pub fn is_terminal(&self) -> bool {
+        matches!(
+            self,
+            ActionStatus::Completed
+                | ActionStatus::Error
+                | ActionStatus::Skipped
+                | ActionStatus::Cancelled
+                | ActionStatus::TimedOut
+        )
+    }
+
+    /// Whether an action may go from this status to `next`. An action is
+    /// scheduled, runs, then ends. Reporting the current status again is not
+    /// a change. Retrying a failed attempt is not a transition either, see
+    /// `can_retry`.
+    pub fn can_transition_to(&self, next: ActionStatus) -> bool {
+        use ActionStatus::*;
+        *self == next
+            || matches!(
+                (self, next),
+                (Pending, Scheduled | Error | Skipped | Cancelled)
+                    | (
+                        Scheduled,
+                        Running | Completed | Error | Cancelled | TimedOut
+                    )
+                    | (Running, Completed | Error | Cancelled | TimedOut)
+            )
+    }
+
+    /// Whether an action that ended with this status may be scheduled again
+    /// for a new attempt.
+    pub fn can_retry(&self) -> bool {
+        matches!(self, ActionStatus::Error | ActionStatus::TimedOut)
+    }
 }
 
 impl Serialize for ActionStatus {
@@ -91,6 +143,9 @@ impl FromStr for ActionStatus {
             "Running" | "ACTION_STATUS_RUNNING" => Ok(ActionStatus::Running),
             "Completed" | "ACTION_STATUS_COMPLETED" => Ok(ActionStatus::Completed),
             "Error" | "ACTION_STATUS_ERROR" => Ok(ActionStatus::Error),
+            "Skipped" | "ACTION_STATUS_SKIPPED" => Ok(ActionStatus::Skipped),
+            "Cancelled" | "ACTION_STATUS_CANCELLED" => Ok(ActionStatus::Cancelled),
+            "TimedOut" | "ACTION_STATUS_TIMED_OUT" => Ok(ActionStatus::TimedOut),
             _ => Err(()),
         }
     }
@@ -105,9 +160,30 @@ impl From<String> for ActionStatus {
 #[derive(Debug, Clone)]
 pub struct ActionRequest {
     pub action_id: u32,
-    pub commands: Vec<String>,
+    pub commands: Vec<Step>,
     pub context: ExecutionContext,
     pub repo_url: String,
+    pub env: HashMap<String, String>,
+    pub timeout_seconds: Option<u64>,
+    pub artifacts: Vec<String>,
+    pub cache: Option<Cache>,
+    pub services: HashMap<String, Service>,
+    pub shell: Vec<String>,
+    pub checkout: Checkout,
+}
+
+/// Revision of the repository the agent checks out before running an action.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Checkout {
+    /// Commit that triggered the pipeline
+    pub revision: Option<String>,
+    /// Reference the commit is fetched from, e.g. `refs/pull/12/head`
+    pub git_ref: Option<String>,
+    /// Number of commits fetched, the whole history when 0
+    pub depth: u32,
+    pub submodules: bool,
+    /// Set for a private repository
+    pub credentials: Option<Credentials>,
 }
 
 #[derive(Debug, Clone)]
@@ -121,6 +197,32 @@ pub struct ActionResponse {
     pub action_id: u32,
     pub log: String,
     pub result: Option<ActionResult>,
+    /// Chunk of the tar archive of the artifacts of the action
+    pub artifact: Option<Vec<u8>>,
+    /// Set on the last chunk of the archive, once the agent wrote it completely
+    pub artifact_complete: bool,
+    pub step: Option<StepEvent>,
+    /// When the agent produced the line, if it said so
+    pub timestamp: Option<OffsetDateTime>,
+    pub stream: LogStream,
+    pub step_index: Option<i32>,
+}
+
+/// Boundary of a step of an action, as reported by the agent.
+#[derive(Debug, Clone, PartialEq)]
+pub enum StepEvent {
+    Started {
+        index: i32,
+        name: String,
+        started_at: OffsetDateTime,
+    },
+    Finished {
+        index: i32,
+        name: String,
+        exit_code: i32,
+        started_at: OffsetDateTime,
+        finished_at: OffsetDateTime,
+    },
 }
 
 #[derive(Debug, Clone)]
@@ -129,6 +231,100 @@ pub struct ActionResult {
     pub exit_code: Option<i32>,
 }
 
+/// Failure of an attempt that makes the action eligible for another one.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RetryCondition {
+    /// The agent running the action went away before reporting a result.
+    AgentLost,
+    /// The action failed with one of these exit codes.
+    ExitCode(Vec<i32>),
+}
+
+/// When and how many times a failed action is dispatched again.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RetryPolicy {
+    /// Total number of attempts, the first one included
+    pub max_attempts: i32,
+    pub on: Vec<RetryCondition>,
+}
+
+impl RetryPolicy {
+    /// Whether the attempt numbered `attempt`, which ended with `outcome`,
+    /// should be followed by another one.
+    pub fn should_retry(&self, attempt: i32, outcome: &AttemptOutcome) -> bool {
+        attempt < self.max_attempts
+            && outcome.status != ActionStatus::Completed
+            && self.on.iter().any(|condition| match condition {
+                RetryCondition::AgentLost => outcome.agent_lost,
+                RetryCondition::ExitCode(codes) => outcome
+                    .exit_code
+                    .is_some_and(|exit_code| codes.contains(&exit_code)),
+            })
+    }
+}
+
+/// Files an agent keeps between the runs of the actions sharing the same key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Cache {
+    /// May contain `${{ hashFiles('<pattern>', ...) }}` expressions, resolved
+    /// by the agent once the repository is cloned
+    pub key: String,
+    pub paths: Vec<String>,
+}
+
+/// Container started next to an action, on a network they share, and reached
+/// by the action under the name of the service.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Service {
+    pub image: String,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Shell command run in the service until it succeeds, the action starts
+    /// once it does
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub healthcheck: Option<String>,
+}
+
+/// How an attempt of an action ended.
+#[derive(Debug, Clone)]
+pub struct AttemptOutcome {
+    pub status: ActionStatus,
+    pub exit_code: Option<i32>,
+    /// The response stream broke or closed before the action reported a result
+    pub agent_lost: bool,
+}
+
+/// One dispatch of an action to the scheduler, with its own logs and exit code.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ActionAttempt {
+    pub attempt: i32,
+    pub status: ActionStatus,
+    pub exit_code: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub logs: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub steps: Option<Vec<ActionStep>>,
+}
+
+/// A step run during an attempt of an action, with its timings.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct ActionStep {
+    pub attempt: i32,
+    /// Position of the step in the action, starting at 0
+    pub index: i32,
+    /// Name of the step, or its command when it has none
+    pub name: String,
+    /// Not set while the step runs
+    pub exit_code: Option<i32>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub started_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub finished_at: Option<OffsetDateTime>,
+    /// Run time of the step in milliseconds, once it finished
+    pub duration_ms: Option<i64>,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, FromRow)]
 pub struct Action {
     pub id: i64,
@@ -137,10 +333,58 @@ pub struct Action {
     pub r#type: ActionType,
     pub container_uri: String,
     #[sqlx(default)]
-    pub commands: Vec<String>,
+    pub commands: Vec<Step>,
+    #[sqlx(default)]
+    pub needs: Vec<String>,
+    #[sqlx(default)]
+    pub allow_failure: bool,
+    #[sqlx(default, json)]
+    pub env: HashMap<String, String>,
+    #[sqlx(default)]
+    pub timeout_seconds: Option<i64>,
+    #[sqlx(default, json)]
+    pub retry: Option<RetryPolicy>,
+    /// `if` condition on the trigger context of the pipeline
+    #[sqlx(default)]
+    pub condition: Option<String>,
+    /// Paths archived out of the container once the commands have run
+    #[sqlx(default)]
+    pub artifacts: Vec<String>,
+    #[sqlx(default, json)]
+    pub cache: Option<Cache>,
+    /// Containers started next to the action, by name
+    #[sqlx(default, json)]
+    pub services: HashMap<String, Service>,
+    /// Program and arguments the commands are passed to, the agent default
+    /// (`/bin/sh -e -c`) when empty
+    #[sqlx(default)]
+    pub shell: Vec<String>,
+    /// Number of the current (or last) attempt, starting at 1
+    #[sqlx(default)]
+    pub attempt: i32,
+    /// Action of an earlier run whose successful result this action reuses,
+    /// along with its logs and artifacts
+    #[sqlx(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reused_from: Option<i64>,
     pub status: ActionStatus,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    /// When the action first started running
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub started_at: Option<OffsetDateTime>,
+    /// When the action reached its final status
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub finished_at: Option<OffsetDateTime>,
     #[serde(skip_serializing_if = "Option::is_none")]
     pub logs: Option<Vec<String>>,
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub attempts: Option<Vec<ActionAttempt>>,
+    /// Steps of the current attempt, only filled with the logs
+    #[sqlx(skip)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub steps: Option<Vec<ActionStep>>,
 }
 
 impl Action {
@@ -149,7 +393,7 @@ impl Action {
         pipeline_id: i64,
         name: String,
         container_uri: String,
-        commands: Vec<String>,
+        commands: Vec<Step>,
         r#type: ActionType,
         status: String,
     ) -> Result<Self, ActionError> {
@@ -162,11 +406,59 @@ impl Action {
             name,
             container_uri,
             commands,
+            needs: Vec::new(),
+            allow_failure: false,
+            env: HashMap::new(),
+            timeout_seconds: None,
+            retry: None,
+            condition: None,
+            artifacts: Vec::new(),
+            cache: None,
+            services: HashMap::new(),
+            shell: Vec::new(),
+            attempt: 1,
+            reused_from: None,
             r#type,
             status: ActionStatus::from(normalized.clone()),
+            created_at: OffsetDateTime::now_utc(),
+            started_at: None,
+            finished_at: None,
             logs: None,
+            attempts: None,
+            steps: None,
         })
     }
+
+    /// The action whose result a re-run of this action reuses instead of
+    /// running it again, if any. Only completed actions are reused, and only
+    /// when re-running the failed actions alone.
+    pub fn rerun_source(&self, only_failed: bool) -> Option<i64> {
+        (only_failed && self.status == ActionStatus::Completed)
+            .then(|| self.reused_from.unwrap_or(self.id))
+    }
+}
+
+/// An action of a pipeline that has not been persisted yet.
+#[derive(Debug, Clone)]
+pub struct NewAction {
+    pub pipeline_id: i64,
+    pub name: String,
+    pub container_uri: String,
+    pub r#type: ActionType,
+    pub status: String,
+    pub needs: Vec<String>,
+    pub allow_failure: bool,
+    pub env: HashMap<String, String>,
+    pub timeout_seconds: Option<i64>,
+    pub retry: Option<RetryPolicy>,
+    pub condition: Option<String>,
+    pub artifacts: Vec<String>,
+    pub cache: Option<Cache>,
+    pub services: HashMap<String, Service>,
+    pub shell: Vec<String>,
+    /// Set when the action is not run again but reuses the result of an
+    /// action of an earlier run
+    pub reused_from: Option<i64>,
 }
 
 #[derive(Debug, Error)]
@@ -181,6 +473,13 @@ pub enum ActionError {
     InvalidStatus(String),
     #[error("Invalid input: {0}")]
     InvalidType(String),
+    #[error("Invalid transition from {from} to {to}")]
+    InvalidTransition {
+        from: ActionStatus,
+        to: ActionStatus,
+    },
+    #[error("Attempt {0} does not follow the current attempt of the action")]
+    InvalidAttempt(i32),
 }
 
 #[derive(Debug, Clone, sqlx::FromRow)]
@@ -191,6 +490,43 @@ pub struct ActionDTO {
     pub action_type: String,
     pub container_uri: String,
     pub status: String,
+    pub needs: Vec<String>,
+    pub allow_failure: bool,
+    pub env: Json<HashMap<String, String>>,
+    pub timeout_seconds: Option<i64>,
+    pub retry: Option<Json<RetryPolicy>>,
+    pub condition: Option<String>,
+    pub artifacts: Vec<String>,
+    pub cache: Option<Json<Cache>>,
+    pub services: Json<HashMap<String, Service>>,
+    pub shell: Vec<String>,
+    pub attempt: i32,
+    pub reused_from: Option<i64>,
+    pub created_at: OffsetDateTime,
+    pub started_at: Option<OffsetDateTime>,
+    pub finished_at: Option<OffsetDateTime>,
     pub command: Option<String>,
     pub command_id: Option<i64>,
+    pub command_name: Option<String>,
+    pub command_working_directory: Option<String>,
+    pub command_env: Option<Json<HashMap<String, String>>>,
+    pub command_continue_on_error: Option<bool>,
+}
+
+impl ActionDTO {
+    /// The step of the row, joined from the commands of the action, if any.
+    pub fn step(&self) -> Option<Step> {
+        let run = self.command.clone()?;
+        Some(Step {
+            name: self.command_name.clone(),
+            run,
+            working_directory: self.command_working_directory.clone(),
+            env: self
+                .command_env
+                .as_ref()
+                .map(|env| env.0.clone())
+                .unwrap_or_default(),
+            continue_on_error: self.command_continue_on_error.unwrap_or_default(),
+        })
+    }
 }
@@ -1,23 +1,40 @@
+use crate::domain::action::entities::action::{
+    Action, ActionAttempt, ActionError, ActionStep, NewAction, StepEvent,
+};
+use crate::domain::log::entities::log::{Log, NewLog};
 use async_trait::async_trait;
-use crate::domain::action::entities::action::{Action, ActionError, ActionType};
-
 
 #[async_trait]
 pub trait ActionRepository: Send + Sync {
     async fn find_by_pipeline_id(&self, pipeline_id: i64) -> Result<Vec<Action>, ActionError>;
+    /// Returns the actions of all the pipelines at once, ordered by id.
+    async fn find_by_pipeline_ids(&self, pipeline_ids: &[i64]) -> Result<Vec<Action>, ActionError>;
     async fn find_by_id(&self, action_id: i64) -> Result<Action, ActionError>;
-    async fn create(
+    async fn create(&self, action: NewAction) -> Result<Action, ActionError>;
+    async fn update_status(&self, action_id: i64, status: &String) -> Result<Action, ActionError>;
+    async fn append_log(&self, action_id: i64, log: NewLog) -> Result<Log, ActionError>;
+    /// Records a new attempt of the action and makes it the current one.
+    async fn start_attempt(&self, action_id: i64, attempt: i32) -> Result<(), ActionError>;
+    /// Schedules a failed action again for its next attempt, recording that
+    /// attempt. Fails with `InvalidTransition` when the action did not fail,
+    /// such as one cancelled meanwhile, and with `InvalidAttempt` when
+    /// `attempt` does not follow its current attempt.
+    async fn retry(&self, action_id: i64, attempt: i32) -> Result<Action, ActionError>;
+    async fn finish_attempt(
         &self,
-        pipeline_id: i64,
-        name: String,
-        container_uri: String,
-        r#type: ActionType,
-        status: String,
-    ) -> Result<Action, ActionError>;
-    async fn update_status(
+        action_id: i64,
+        attempt: i32,
+        status: &str,
+        exit_code: Option<i32>,
+    ) -> Result<(), ActionError>;
+    async fn find_attempts(&self, action_id: i64) -> Result<Vec<ActionAttempt>, ActionError>;
+    /// Records the start or the end of a step of an attempt of the action.
+    async fn record_step(
         &self,
         action_id: i64,
-        status: &String,
-    ) -> Result<Action, ActionError>;
-    async fn append_log(&self, action_id: i64, log: String) -> Result<(), ActionError>;
+        attempt: i32,
+        event: &StepEvent,
+    ) -> Result<(), ActionError>;
+    /// Returns the steps of every attempt of the action, in order.
+    async fn find_steps(&self, action_id: i64) -> Result<Vec<ActionStep>, ActionError>;
 }
@@ -0,0 +1,15 @@
+use std::pin::Pin;
+
+use futures::Stream;
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ArtifactError {
+    #[error("Storage error: {0}")]
+    StorageError(#[from] std::io::Error),
+    #[error("Artifacts not found")]
+    NotFound,
+}
+
+/// Content of an artifacts archive, chunk by chunk.
+pub type ArtifactStream = Pin<Box<dyn Stream<Item = Result<Vec<u8>, ArtifactError>> + Send>>;
@@ -0,0 +1,18 @@
+use async_trait::async_trait;
+
+use crate::domain::artifacts::entities::{ArtifactError, ArtifactStream};
+
+/// Where the artifacts archives of the actions are kept, one tar archive per
+/// attempt of an action.
+#[async_trait]
+pub trait ArtifactStorage: Send + Sync {
+    /// Appends a chunk to the archive of an action attempt, creating it if
+    /// needed. The archive is not served before it is committed.
+    async fn append(&self, action_id: i64, attempt: i32, chunk: &[u8])
+        -> Result<(), ArtifactError>;
+    /// Keeps the archive of an action attempt once it was received completely.
+    async fn commit(&self, action_id: i64, attempt: i32) -> Result<(), ArtifactError>;
+    /// Deletes the archive of an action attempt that was not committed, if any.
+    async fn discard(&self, action_id: i64, attempt: i32) -> Result<(), ArtifactError>;
+    async fn open(&self, action_id: i64, attempt: i32) -> Result<ArtifactStream, ArtifactError>;
+}
@@ -1,10 +1,17 @@
+use std::collections::HashMap;
+
 use serde::{Deserialize, Serialize};
+use sqlx::types::Json;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Command {
     pub id: i64,
     pub action_id: i64,
     pub command: String,
+    pub name: Option<String>,
+    pub working_directory: Option<String>,
+    pub env: Json<HashMap<String, String>>,
+    pub continue_on_error: bool,
 }
 
 impl Command {
@@ -13,6 +20,106 @@ impl Command {
             id,
             action_id,
             command,
+            name: None,
+            working_directory: None,
+            env: Json(HashMap::new()),
+            continue_on_error: false,
+        }
+    }
+}
+
+/// A command of an action along with the options it runs with.
+///
+/// As in the manifest, a step without options is written as its bare command
+/// and one with options as an object.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(from = "StepRepr", into = "StepRepr")]
+pub struct Step {
+    /// Shown in the logs in place of the command
+    pub name: Option<String>,
+    pub run: String,
+    /// Directory the command runs in, relative to the repository
+    pub working_directory: Option<String>,
+    /// Variables added to the environment of the action for this step only
+    pub env: HashMap<String, String>,
+    /// A failure of the step is reported without failing the action
+    pub continue_on_error: bool,
+}
+
+impl Step {
+    pub fn new(run: String) -> Self {
+        Step {
+            run,
+            ..Default::default()
+        }
+    }
+}
+
+impl From<Command> for Step {
+    fn from(command: Command) -> Self {
+        Step {
+            name: command.name,
+            run: command.command,
+            working_directory: command.working_directory,
+            env: command.env.0,
+            continue_on_error: command.continue_on_error,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(untagged)]
+enum StepRepr {
+    Command(String),
+    Step {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        name: Option<String>,
+        run: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        working_directory: Option<String>,
+        #[serde(default, skip_serializing_if = "HashMap::is_empty")]
+        env: HashMap<String, String>,
+        #[serde(default)]
+        continue_on_error: bool,
+    },
+}
+
+impl From<StepRepr> for Step {
+    fn from(repr: StepRepr) -> Self {
+        match repr {
+            StepRepr::Command(run) => Step::new(run),
+            StepRepr::Step {
+                name,
+                run,
+                working_directory,
+                env,
+                continue_on_error,
+            } => Step {
+                name,
+                run,
+                working_directory,
+                env,
+                continue_on_error,
+            },
+        }
+    }
+}
+
+impl From<Step> for StepRepr {
+    fn from(step: Step) -> Self {
+        if step.name.is_none()
+            && step.working_directory.is_none()
+            && step.env.is_empty()
+            && !step.continue_on_error
+        {
+            return StepRepr::Command(step.run);
+        }
+        StepRepr::Step {
+            name: step.name,
+            run: step.run,
+            working_directory: step.working_directory,
+            env: step.env,
+            continue_on_error: step.continue_on_error,
         }
     }
 }
@@ -1,9 +1,9 @@
 use async_trait::async_trait;
 
-use crate::domain::command::entities::command::{Command, CommandError};
+use crate::domain::command::entities::command::{Command, CommandError, Step};
 
 #[async_trait]
 pub trait CommandRepository: Send + Sync {
     async fn find_by_action_id(&self, action_id: i64) -> Result<Vec<Command>, CommandError>;
-    async fn create(&self, action_id: i64, command: String) -> Result<Command, CommandError>;
+    async fn create(&self, action_id: i64, step: Step) -> Result<Command, CommandError>;
 }
@@ -1,22 +1,245 @@
+use std::{fmt, str::FromStr};
+
 use serde::{Deserialize, Serialize};
+use time::OffsetDateTime;
+
+/// Output a log line was written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogStream {
+    /// Written by the agent or the controller, not by a command
+    #[default]
+    System,
+    Stdout,
+    Stderr,
+}
+
+impl LogStream {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LogStream::System => "system",
+            LogStream::Stdout => "stdout",
+            LogStream::Stderr => "stderr",
+        }
+    }
+}
+
+impl fmt::Display for LogStream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for LogStream {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "system" => Ok(LogStream::System),
+            "stdout" => Ok(LogStream::Stdout),
+            "stderr" => Ok(LogStream::Stderr),
+            _ => Err(()),
+        }
+    }
+}
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Log {
     pub id: i64,
     pub action_id: i64,
+    /// Position of the line in the logs of the action, starting at 1
+    pub seq: i64,
+    /// Attempt of the action the line was produced by
+    pub attempt: i32,
+    /// Time the line was written at, by the agent for the output of the commands
+    #[serde(with = "time::serde::rfc3339")]
+    pub ts: OffsetDateTime,
+    pub stream: LogStream,
+    /// Index of the step the line was written by, if any
+    pub step_index: Option<i32>,
     pub data: String,
 }
 
 impl Log {
-    pub fn new(id: i64, action_id: i64, data: String) -> Log {
+    pub fn new(id: i64, action_id: i64, seq: i64, data: String, attempt: i32) -> Log {
         Log {
             id,
             action_id,
+            seq,
+            attempt,
+            ts: OffsetDateTime::now_utc(),
+            stream: LogStream::System,
+            step_index: None,
             data,
         }
     }
 }
 
+/// A log line of an action that has not been stored yet.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NewLog {
+    pub attempt: i32,
+    pub data: String,
+    pub ts: OffsetDateTime,
+    pub stream: LogStream,
+    pub step_index: Option<i32>,
+}
+
+impl NewLog {
+    /// A line written by the controller itself, now.
+    pub fn system(attempt: i32, data: String) -> Self {
+        Self {
+            attempt,
+            data,
+            ts: OffsetDateTime::now_utc(),
+            stream: LogStream::System,
+            step_index: None,
+        }
+    }
+}
+
+/// Length above which a line that is still not complete is released anyway,
+/// so that an output without newlines is not held back forever.
+pub const MAX_PENDING_LINE: usize = 64 * 1024;
+
+/// Joins the chunks of output sent by an agent into complete lines, so that
+/// a secret split over two chunks is masked all the same. A line is only
+/// released once its newline is received, or when the chunks following it
+/// come from another stream or step. The messages of the agent itself come
+/// without a newline and are each a line of their own.
+#[derive(Debug, Default)]
+pub struct LineBuffer {
+    pending: Option<NewLog>,
+    /// Values masked once the lines are released
+    secrets: Vec<String>,
+}
+
+impl LineBuffer {
+    /// A buffer that never releases a part of the given secrets on its own,
+    /// even when a line too long is split.
+    pub fn new(secrets: Vec<String>) -> Self {
+        Self {
+            pending: None,
+            secrets: secrets.into_iter().filter(|s| !s.is_empty()).collect(),
+        }
+    }
+
+    /// Adds a chunk of output, returning the lines it completes. The lines
+    /// keep the metadata of the chunk they started in.
+    pub fn push(&mut self, chunk: NewLog) -> Vec<NewLog> {
+        let mut lines = Vec::new();
+        if self.pending.as_ref().is_some_and(|pending| {
+            (pending.attempt, pending.stream, pending.step_index)
+                != (chunk.attempt, chunk.stream, chunk.step_index)
+        }) {
+            lines.extend(self.flush());
+        }
+        if chunk.data.is_empty() {
+            return lines;
+        }
+        if chunk.stream == LogStream::System {
+            lines.push(chunk);
+            return lines;
+        }
+
+        let mut pending = match self.pending.take() {
+            Some(mut pending) => {
+                pending.data.push_str(&chunk.data);
+                pending
+            }
+            None => chunk.clone(),
+        };
+        let end = match pending.data.rfind('\n') {
+            Some(end) => Some(end + 1),
+            None if pending.data.len() > MAX_PENDING_LINE => Some(self.split_point(&pending.data)),
+            None => None,
+        };
+        match end {
+            Some(end) if end > 0 => {
+                let rest = pending.data.split_off(end);
+                lines.push(pending);
+                if !rest.is_empty() {
+                    self.pending = Some(NewLog {
+                        data: rest,
+                        ..chunk
+                    });
+                }
+            }
+            _ => self.pending = Some(pending),
+        }
+        lines
+    }
+
+    /// Releases the line that is not complete yet, if any.
+    pub fn flush(&mut self) -> Option<NewLog> {
+        self.pending.take()
+    }
+
+    /// Where to split a line too long, keeping back what may be the start of
+    /// a secret whose end is not received yet, and never cutting through a
+    /// secret received whole.
+    fn split_point(&self, data: &str) -> usize {
+        let longest = self.secrets.iter().map(String::len).max().unwrap_or(0);
+        let mut split = data.len().saturating_sub(longest.saturating_sub(1));
+        while !data.is_char_boundary(split) {
+            split -= 1;
+        }
+        // Moving the split before a secret may make it cut through another one
+        while let Some(start) = self
+            .secrets
+            .iter()
+            .flat_map(|secret| {
+                data.match_indices(secret.as_str())
+                    .map(|(start, secret)| (start, start + secret.len()))
+            })
+            .filter(|(start, end)| *start < split && *end > split)
+            .map(|(start, _)| start)
+            .min()
+        {
+            split = start;
+        }
+        split
+    }
+}
+
+/// Selects the log lines of an action, in order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LogFilter {
+    /// Only the lines after this sequence number
+    pub after_seq: i64,
+    /// Maximum number of lines
+    pub limit: i64,
+    /// Only the lines containing this text
+    pub grep: Option<String>,
+}
+
+/// Row of the `logs` table.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct LogDTO {
+    pub id: i64,
+    pub action_id: i64,
+    pub seq: i64,
+    pub attempt: i32,
+    pub ts: OffsetDateTime,
+    pub stream: String,
+    pub step_index: Option<i32>,
+    pub data: String,
+}
+
+impl From<LogDTO> for Log {
+    fn from(row: LogDTO) -> Self {
+        Log {
+            id: row.id,
+            action_id: row.action_id,
+            seq: row.seq,
+            attempt: row.attempt,
+            ts: row.ts,
+            stream: row.stream.parse().unwrap_or_default(),
+            step_index: row.step_index,
+            data: row.data,
+        }
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum LogError {
     #[error("Database error: {0}")]
@@ -4,6 +4,9 @@ use crate::domain::log::entities::log::*;
 
 #[async_trait]
 pub trait LogRepository {
-    async fn create(&self, action_id: i64, data: String) -> Result<Log, LogError>;
-    async fn find_by_action_id(&self, action_id: i64) -> Result<Vec<Log>, LogError>;
+    async fn create(&self, action_id: i64, log: NewLog) -> Result<Log, LogError>;
+    /// Lines of an action selected by `filter`, in order.
+    async fn find_page(&self, action_id: i64, filter: &LogFilter) -> Result<Vec<Log>, LogError>;
+    /// The last `limit` lines of an action, in order.
+    async fn find_tail(&self, action_id: i64, limit: i64) -> Result<Vec<Log>, LogError>;
 }
@@ -1 +1,3 @@
-pub mod pipeline;
\ No newline at end of file
+pub mod condition;
+pub mod expression;
+pub mod pipeline;
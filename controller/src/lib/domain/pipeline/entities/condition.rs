@@ -0,0 +1,281 @@
+//! `if` conditions of actions, evaluated against the trigger context of the
+//! pipeline, as in `branch == "main" && event == "push"` or `tag =~ "^v"`.
+//!
+//! A condition is made of the variables `branch`, `event`, `tag` and
+//! `message`, double-quoted strings, the comparisons `==`, `!=` and `=~`
+//! (regular expression match), the operators `!`, `&&` and `||`, and
+//! parentheses. A variable or a string on its own is true when not empty.
+//! Variables missing from the trigger context are empty strings. A condition
+//! may nest operators and parentheses at most 64 levels deep.
+
+use regex::Regex;
+use thiserror::Error;
+
+use super::pipeline::TriggerContext;
+
+#[derive(Debug, Error, PartialEq)]
+pub enum ConditionError {
+    #[error("Unexpected end of condition")]
+    UnexpectedEnd,
+    #[error("Unexpected token in condition: {0}")]
+    UnexpectedToken(String),
+    #[error("Unknown variable in condition: {0}")]
+    UnknownVariable(String),
+    #[error("Unterminated string in condition")]
+    UnterminatedString,
+    #[error("Invalid regular expression in condition: {0}")]
+    InvalidRegex(String),
+    #[error("Condition is nested more than {0} levels deep")]
+    TooDeep(usize),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Variable {
+    Branch,
+    Event,
+    Tag,
+    Message,
+}
+
+impl Variable {
+    fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "branch" => Some(Variable::Branch),
+            "event" => Some(Variable::Event),
+            "tag" => Some(Variable::Tag),
+            "message" => Some(Variable::Message),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Operand {
+    Variable(Variable),
+    Literal(String),
+}
+
+impl Operand {
+    fn value<'a>(&'a self, context: &'a TriggerContext) -> &'a str {
+        let value = match self {
+            Operand::Literal(literal) => return literal,
+            Operand::Variable(Variable::Branch) => &context.branch,
+            Operand::Variable(Variable::Event) => &context.event,
+            Operand::Variable(Variable::Tag) => &context.tag,
+            Operand::Variable(Variable::Message) => &context.message,
+        };
+        value.as_deref().unwrap_or_default()
+    }
+}
+
+#[derive(Debug, Clone)]
+enum Expression {
+    Or(Box<Expression>, Box<Expression>),
+    And(Box<Expression>, Box<Expression>),
+    Not(Box<Expression>),
+    Equals(Operand, Operand),
+    NotEquals(Operand, Operand),
+    Matches(Operand, Regex),
+    Truthy(Operand),
+}
+
+impl Expression {
+    fn evaluate(&self, context: &TriggerContext) -> bool {
+        match self {
+            Expression::Or(left, right) => left.evaluate(context) || right.evaluate(context),
+            Expression::And(left, right) => left.evaluate(context) && right.evaluate(context),
+            Expression::Not(expression) => !expression.evaluate(context),
+            Expression::Equals(left, right) => left.value(context) == right.value(context),
+            Expression::NotEquals(left, right) => left.value(context) != right.value(context),
+            Expression::Matches(operand, regex) => regex.is_match(operand.value(context)),
+            Expression::Truthy(operand) => !operand.value(context).is_empty(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Identifier(String),
+    String(String),
+    Equals,
+    NotEquals,
+    Matches,
+    And,
+    Or,
+    Not,
+    OpenParenthesis,
+    CloseParenthesis,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, ConditionError> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    while let Some(c) = chars.next() {
+        let token = match c {
+            c if c.is_whitespace() => continue,
+            '(' => Token::OpenParenthesis,
+            ')' => Token::CloseParenthesis,
+            '"' => {
+                let mut value = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(escaped) => value.push(escaped),
+                            None => return Err(ConditionError::UnterminatedString),
+                        },
+                        Some(c) => value.push(c),
+                        None => return Err(ConditionError::UnterminatedString),
+                    }
+                }
+                Token::String(value)
+            }
+            '=' if chars.next_if_eq(&'=').is_some() => Token::Equals,
+            '=' if chars.next_if_eq(&'~').is_some() => Token::Matches,
+            '!' if chars.next_if_eq(&'=').is_some() => Token::NotEquals,
+            '!' => Token::Not,
+            '&' if chars.next_if_eq(&'&').is_some() => Token::And,
+            '|' if chars.next_if_eq(&'|').is_some() => Token::Or,
+            c if c.is_ascii_alphabetic() || c == '_' => {
+                let mut name = c.to_string();
+                while let Some(c) = chars.next_if(|c| c.is_ascii_alphanumeric() || *c == '_') {
+                    name.push(c);
+                }
+                Token::Identifier(name)
+            }
+            c => return Err(ConditionError::UnexpectedToken(c.to_string())),
+        };
+        tokens.push(token);
+    }
+    Ok(tokens)
+}
+
+/// Deepest expression a condition may make, so that neither parsing nor
+/// evaluating it can overflow the stack.
+const MAX_DEPTH: usize = 64;
+
+/// Recursive descent parser, from the loosest operator to the tightest one.
+struct Parser {
+    tokens: std::iter::Peekable<std::vec::IntoIter<Token>>,
+    /// Depth of the expression being parsed
+    depth: usize,
+}
+
+impl Parser {
+    /// Goes one level deeper in the expression, failing beyond `MAX_DEPTH`.
+    fn descend(&mut self) -> Result<(), ConditionError> {
+        if self.depth == MAX_DEPTH {
+            return Err(ConditionError::TooDeep(MAX_DEPTH));
+        }
+        self.depth += 1;
+        Ok(())
+    }
+
+    fn or(&mut self) -> Result<Expression, ConditionError> {
+        // Each operator of a chain nests the expression one level deeper
+        let depth = self.depth;
+        let mut expression = self.and()?;
+        while self.tokens.next_if_eq(&Token::Or).is_some() {
+            self.descend()?;
+            expression = Expression::Or(Box::new(expression), Box::new(self.and()?));
+        }
+        self.depth = depth;
+        Ok(expression)
+    }
+
+    fn and(&mut self) -> Result<Expression, ConditionError> {
+        let depth = self.depth;
+        let mut expression = self.unary()?;
+        while self.tokens.next_if_eq(&Token::And).is_some() {
+            self.descend()?;
+            expression = Expression::And(Box::new(expression), Box::new(self.unary()?));
+        }
+        self.depth = depth;
+        Ok(expression)
+    }
+
+    fn unary(&mut self) -> Result<Expression, ConditionError> {
+        if self.tokens.next_if_eq(&Token::Not).is_some() {
+            self.descend()?;
+            let expression = Expression::Not(Box::new(self.unary()?));
+            self.depth -= 1;
+            return Ok(expression);
+        }
+        if self.tokens.next_if_eq(&Token::OpenParenthesis).is_some() {
+            self.descend()?;
+            let expression = self.or()?;
+            self.depth -= 1;
+            return match self.tokens.next() {
+                Some(Token::CloseParenthesis) => Ok(expression),
+                Some(token) => Err(ConditionError::UnexpectedToken(format!("{:?}", token))),
+                None => Err(ConditionError::UnexpectedEnd),
+            };
+        }
+        self.comparison()
+    }
+
+    fn comparison(&mut self) -> Result<Expression, ConditionError> {
+        let left = self.operand()?;
+        let expression = match self.tokens.peek() {
+            Some(Token::Equals) => {
+                self.tokens.next();
+                Expression::Equals(left, self.operand()?)
+            }
+            Some(Token::NotEquals) => {
+                self.tokens.next();
+                Expression::NotEquals(left, self.operand()?)
+            }
+            Some(Token::Matches) => {
+                self.tokens.next();
+                // The pattern is compiled once, so it must be a literal
+                let pattern = match self.tokens.next() {
+                    Some(Token::String(pattern)) => pattern,
+                    Some(token) => {
+                        return Err(ConditionError::UnexpectedToken(format!("{:?}", token)))
+                    }
+                    None => return Err(ConditionError::UnexpectedEnd),
+                };
+                let regex = Regex::new(&pattern)
+                    .map_err(|_| ConditionError::InvalidRegex(pattern.clone()))?;
+                Expression::Matches(left, regex)
+            }
+            _ => Expression::Truthy(left),
+        };
+        Ok(expression)
+    }
+
+    fn operand(&mut self) -> Result<Operand, ConditionError> {
+        match self.tokens.next() {
+            Some(Token::Identifier(name)) => Variable::from_name(&name)
+                .map(Operand::Variable)
+                .ok_or(ConditionError::UnknownVariable(name)),
+            Some(Token::String(value)) => Ok(Operand::Literal(value)),
+            Some(token) => Err(ConditionError::UnexpectedToken(format!("{:?}", token))),
+            None => Err(ConditionError::UnexpectedEnd),
+        }
+    }
+}
+
+/// A parsed `if` condition.
+#[derive(Debug, Clone)]
+pub struct Condition {
+    expression: Expression,
+}
+
+impl Condition {
+    pub fn parse(source: &str) -> Result<Self, ConditionError> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?.into_iter().peekable(),
+            depth: 0,
+        };
+        let expression = parser.or()?;
+        match parser.tokens.next() {
+            Some(token) => Err(ConditionError::UnexpectedToken(format!("{:?}", token))),
+            None => Ok(Condition { expression }),
+        }
+    }
+
+    pub fn evaluate(&self, context: &TriggerContext) -> bool {
+        self.expression.evaluate(context)
+    }
+}
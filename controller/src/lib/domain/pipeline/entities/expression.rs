@@ -0,0 +1,44 @@
+//! `${{ <context>.<name> }}` expressions embedded in manifest values.
+
+const EXPRESSION_START: &str = "${{";
+const EXPRESSION_END: &str = "}}";
+
+/// Returns the trimmed content of every expression of `value`, or `None` when
+/// an expression is not closed.
+pub fn expressions(value: &str) -> Option<Vec<&str>> {
+    let mut found = Vec::new();
+    let mut rest = value;
+    while let Some(start) = rest.find(EXPRESSION_START) {
+        let after = &rest[start + EXPRESSION_START.len()..];
+        let end = after.find(EXPRESSION_END)?;
+        found.push(after[..end].trim());
+        rest = &after[end + EXPRESSION_END.len()..];
+    }
+    Some(found)
+}
+
+/// Replaces the expressions of `value` with what `resolve` returns for their
+/// content. Expressions `resolve` returns `None` for, as well as unclosed ones,
+/// are kept as-is.
+pub fn replace_expressions<E>(
+    value: &str,
+    mut resolve: impl FnMut(&str) -> Result<Option<String>, E>,
+) -> Result<String, E> {
+    let mut replaced = String::with_capacity(value.len());
+    let mut rest = value;
+    while let Some(start) = rest.find(EXPRESSION_START) {
+        let after = &rest[start + EXPRESSION_START.len()..];
+        let Some(end) = after.find(EXPRESSION_END) else {
+            break;
+        };
+        let expression = &rest[start..start + EXPRESSION_START.len() + end + EXPRESSION_END.len()];
+        replaced.push_str(&rest[..start]);
+        match resolve(after[..end].trim())? {
+            Some(resolved) => replaced.push_str(&resolved),
+            None => replaced.push_str(expression),
+        }
+        rest = &after[end + EXPRESSION_END.len()..];
+    }
+    replaced.push_str(rest);
+    Ok(replaced)
+}
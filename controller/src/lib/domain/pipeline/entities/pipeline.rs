@@ -1,13 +1,33 @@
+use std::{collections::HashMap, convert::Infallible, fmt, str::FromStr};
+
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
+use time::OffsetDateTime;
+
+use crate::domain::action::entities::action::{Action, Cache, RetryPolicy, Service};
+use crate::domain::command::entities::command::Step;
 
-use crate::domain::action::entities::action::Action;
+use super::expression::replace_expressions;
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Pipeline {
     pub id: i64,
     pub name: String,
     pub repository_url: String,
+    pub trigger: TriggerContext,
+    pub checkout: CheckoutOptions,
+    pub status: PipelineStatus,
+    /// Pipeline this one is a re-run of
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rerun_of: Option<i64>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    /// When the first of its actions started running
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub started_at: Option<OffsetDateTime>,
+    /// When the last of its actions finished
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub finished_at: Option<OffsetDateTime>,
     pub actions: Vec<Action>,
 }
 
@@ -17,6 +37,13 @@ impl Pipeline {
             id,
             repository_url,
             name,
+            trigger: TriggerContext::default(),
+            checkout: CheckoutOptions::default(),
+            status: PipelineStatus::Queued,
+            rerun_of: None,
+            created_at: OffsetDateTime::now_utc(),
+            started_at: None,
+            finished_at: None,
             actions,
         }
     }
@@ -26,22 +53,358 @@ impl Pipeline {
     }
 }
 
+/// Overall state of a pipeline run, derived from the status of its actions.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PipelineStatus {
+    /// None of its actions has been dispatched yet
+    Queued,
+    Running,
+    /// Every action finished, those that failed were allowed to
+    Success,
+    Failed,
+    Cancelled,
+}
+
+impl PipelineStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PipelineStatus::Queued => "queued",
+            PipelineStatus::Running => "running",
+            PipelineStatus::Success => "success",
+            PipelineStatus::Failed => "failed",
+            PipelineStatus::Cancelled => "cancelled",
+        }
+    }
+}
+
+impl fmt::Display for PipelineStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for PipelineStatus {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "queued" => Ok(PipelineStatus::Queued),
+            "running" => Ok(PipelineStatus::Running),
+            "success" => Ok(PipelineStatus::Success),
+            "failed" => Ok(PipelineStatus::Failed),
+            "cancelled" => Ok(PipelineStatus::Cancelled),
+            _ => Err(()),
+        }
+    }
+}
+
+/// What triggered a pipeline run, as reported by the monitor. The `if`
+/// conditions of the actions are evaluated against it.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct TriggerContext {
+    /// `push`, `pull_request` or `tag`
+    pub event: Option<String>,
+    pub branch: Option<String>,
+    pub tag: Option<String>,
+    /// Message of the commit the pipeline runs on
+    pub message: Option<String>,
+    /// Commit the pipeline runs on
+    pub commit_sha: Option<String>,
+    /// Full name of the git reference, e.g. `refs/heads/main` or `refs/pull/12/head`
+    #[serde(rename = "ref")]
+    pub git_ref: Option<String>,
+    pub pr_number: Option<i32>,
+    /// Author of the commit
+    pub author: Option<String>,
+}
+
+/// Whether `sha` is the full SHA-1 or SHA-256 hash of a commit, the only form
+/// of commit the agents check out and the forges are told about.
+pub fn is_commit_hash(sha: &str) -> bool {
+    matches!(sha.len(), 40 | 64) && sha.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// How the agents check the repository out, set by the `checkout` section of
+/// the manifest.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq)]
+pub struct CheckoutOptions {
+    /// Number of commits fetched, the whole history when 0
+    pub depth: i32,
+    pub submodules: bool,
+}
+
+impl Default for CheckoutOptions {
+    /// A shallow clone without submodules.
+    fn default() -> Self {
+        Self {
+            depth: 1,
+            submodules: false,
+        }
+    }
+}
+
+/// Row of the `pipelines` table.
+#[derive(Debug, Clone, sqlx::FromRow)]
+pub struct PipelineDTO {
+    pub id: i64,
+    pub repository_url: String,
+    pub name: String,
+    pub event: Option<String>,
+    pub branch: Option<String>,
+    pub tag: Option<String>,
+    pub message: Option<String>,
+    pub commit_sha: Option<String>,
+    pub git_ref: Option<String>,
+    pub pr_number: Option<i32>,
+    pub author: Option<String>,
+    pub checkout_depth: i32,
+    pub checkout_submodules: bool,
+    /// Updated with the status of its actions
+    pub status: String,
+    pub rerun_of: Option<i64>,
+    pub created_at: OffsetDateTime,
+    pub started_at: Option<OffsetDateTime>,
+    pub finished_at: Option<OffsetDateTime>,
+}
+
+impl From<PipelineDTO> for Pipeline {
+    fn from(row: PipelineDTO) -> Self {
+        Pipeline {
+            id: row.id,
+            name: row.name,
+            repository_url: row.repository_url,
+            trigger: TriggerContext {
+                event: row.event,
+                branch: row.branch,
+                tag: row.tag,
+                message: row.message,
+                commit_sha: row.commit_sha,
+                git_ref: row.git_ref,
+                pr_number: row.pr_number,
+                author: row.author,
+            },
+            checkout: CheckoutOptions {
+                depth: row.checkout_depth,
+                submodules: row.checkout_submodules,
+            },
+            status: row.status.parse().unwrap_or(PipelineStatus::Queued),
+            rerun_of: row.rerun_of,
+            created_at: row.created_at,
+            started_at: row.started_at,
+            finished_at: row.finished_at,
+            actions: vec![],
+        }
+    }
+}
+
+/// Selects pipelines by their repository, trigger, status and creation time.
+/// Unset fields match every pipeline.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PipelineFilter {
+    pub repository_url: Option<String>,
+    pub commit_sha: Option<String>,
+    pub git_ref: Option<String>,
+    pub branch: Option<String>,
+    pub tag: Option<String>,
+    pub event: Option<String>,
+    pub author: Option<String>,
+    pub pr_number: Option<i32>,
+    pub status: Option<PipelineStatus>,
+    /// Only the pipelines created at or after this time
+    pub since: Option<OffsetDateTime>,
+    /// Only the pipelines created before this time
+    pub until: Option<OffsetDateTime>,
+}
+
+/// A page of pipelines, newest first, without the logs of their actions.
+#[derive(Debug, Serialize)]
+pub struct PipelinePage {
+    pub pipelines: Vec<Pipeline>,
+    /// Cursor of the next page, `None` on the last one
+    pub next_cursor: Option<i64>,
+}
+
+impl PipelinePage {
+    /// Builds a page of at most `limit` pipelines out of up to `limit + 1`
+    /// of them: an extra one means that there is a next page, starting
+    /// after the last pipeline of this one.
+    pub fn new(mut pipelines: Vec<Pipeline>, limit: usize) -> Self {
+        let next_cursor = if pipelines.len() > limit {
+            pipelines.truncate(limit);
+            pipelines.last().map(|pipeline| pipeline.id)
+        } else {
+            None
+        };
+        Self {
+            pipelines,
+            next_cursor,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ManifestPipeline {
     pub name: String,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    /// Default timeout of the actions, in seconds
+    #[serde(default)]
+    pub timeout: Option<u64>,
+    /// Default shell of the actions
+    #[serde(default)]
+    pub shell: Option<Vec<String>>,
+    #[serde(default)]
+    pub checkout: CheckoutOptions,
     pub actions: ActionsMap,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ActionsMap {
     #[serde(flatten)]
-    pub actions: std::collections::HashMap<String, ActionManifest>,
+    pub actions: HashMap<String, ActionManifest>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct ActionManifest {
     pub configuration: Configuration,
-    pub commands: Vec<String>,
+    pub commands: Vec<Step>,
+    #[serde(default)]
+    pub needs: Vec<String>,
+    #[serde(default)]
+    pub allow_failure: bool,
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+    #[serde(default)]
+    pub matrix: Option<Matrix>,
+    /// Timeout in seconds
+    #[serde(default)]
+    pub timeout: Option<u64>,
+    #[serde(default)]
+    pub retry: Option<RetryPolicy>,
+    /// Condition on the trigger context for the action to run
+    #[serde(default, rename = "if")]
+    pub condition: Option<String>,
+    #[serde(default)]
+    pub artifacts: Option<Artifacts>,
+    #[serde(default)]
+    pub cache: Option<Cache>,
+    #[serde(default)]
+    pub services: HashMap<String, Service>,
+    #[serde(default)]
+    pub shell: Option<Vec<String>>,
+}
+
+/// Values of a matrix combination, by key, in declaration order.
+pub type MatrixCombination = Vec<(String, String)>;
+
+/// Expression context giving access to the matrix values, as in `${{ matrix.image }}`.
+pub const MATRIX_CONTEXT: &str = "matrix.";
+
+/// A matrix action is run once per combination of the values of its axes.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq)]
+pub struct Matrix {
+    pub axes: Vec<(String, Vec<String>)>,
+    /// Entries extending the combinations they are compatible with, or added
+    /// as new combinations when there is none.
+    #[serde(default)]
+    pub include: Vec<MatrixCombination>,
+    /// Partial combinations removed from the cross product of the axes.
+    #[serde(default)]
+    pub exclude: Vec<MatrixCombination>,
+}
+
+impl Matrix {
+    /// Keys that can be referenced from the action, i.e. the axes and the
+    /// keys brought by `include`.
+    pub fn keys(&self) -> Vec<&str> {
+        let mut keys: Vec<&str> = self.axes.iter().map(|(axis, _)| axis.as_str()).collect();
+        for (key, _) in self.include.iter().flatten() {
+            if !keys.contains(&key.as_str()) {
+                keys.push(key);
+            }
+        }
+        keys
+    }
+
+    /// Expands the matrix: the cross product of the axes, without the
+    /// excluded combinations, then the included entries applied in order.
+    pub fn combinations(&self) -> Vec<MatrixCombination> {
+        let mut combinations: Vec<MatrixCombination> = if self.axes.is_empty() {
+            Vec::new()
+        } else {
+            vec![Vec::new()]
+        };
+        for (axis, values) in &self.axes {
+            combinations = combinations
+                .into_iter()
+                .flat_map(|combination| {
+                    values.iter().map(move |value| {
+                        let mut combination = combination.clone();
+                        combination.push((axis.clone(), value.clone()));
+                        combination
+                    })
+                })
+                .collect();
+        }
+
+        combinations.retain(|combination| {
+            !self
+                .exclude
+                .iter()
+                .any(|excluded| excluded.iter().all(|entry| combination.contains(entry)))
+        });
+
+        // An include entry can only extend combinations whose axis values it
+        // does not overwrite; the keys it adds may overwrite previous includes
+        let crossed = combinations.len();
+        for included in &self.include {
+            let mut extended = false;
+            for combination in combinations.iter_mut().take(crossed) {
+                let compatible = included.iter().all(|(key, value)| {
+                    !self.axes.iter().any(|(axis, _)| axis == key)
+                        || combination.iter().any(|(k, v)| k == key && v == value)
+                });
+                if !compatible {
+                    continue;
+                }
+                for (key, value) in included {
+                    match combination.iter_mut().find(|(k, _)| k == key) {
+                        Some(entry) => entry.1 = value.clone(),
+                        None => combination.push((key.clone(), value.clone())),
+                    }
+                }
+                extended = true;
+            }
+            if !extended {
+                combinations.push(included.clone());
+            }
+        }
+
+        combinations
+    }
+}
+
+/// Name of the action run for a matrix combination, e.g. `test (rust:1.80, a)`.
+pub fn matrix_action_name(name: &str, combination: &MatrixCombination) -> String {
+    let values: Vec<&str> = combination
+        .iter()
+        .map(|(_, value)| value.as_str())
+        .collect();
+    format!("{} ({})", name, values.join(", "))
+}
+
+/// Replaces the `${{ matrix.<key> }}` expressions of `value` with the values
+/// of the combination. Other expressions are kept as-is.
+pub fn substitute_matrix(value: &str, combination: &MatrixCombination) -> String {
+    replace_expressions(value, |expression| {
+        Ok::<_, Infallible>(expression.strip_prefix(MATRIX_CONTEXT).and_then(|key| {
+            combination
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, value)| value.clone())
+        }))
+    })
+    .unwrap_or_else(|never| match never {})
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -49,6 +412,13 @@ pub struct Configuration {
     pub container: String,
 }
 
+/// Files kept once the commands of an action have run.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct Artifacts {
+    /// Paths relative to the repository, shell patterns are expanded
+    pub paths: Vec<String>,
+}
+
 #[derive(Debug, Error)]
 pub enum CreatePipelineError {
     #[error("Error while creating pipeline: {0}")]
@@ -67,4 +437,16 @@ pub enum PipelineError {
 
     #[error("Invalid input: {0}")]
     InvalidInput(String),
+
+    #[error("Action not found")]
+    ActionNotFound,
+
+    #[error("Nothing left to cancel")]
+    AlreadyFinished,
+
+    #[error("Pipeline is still running")]
+    StillRunning,
+
+    #[error("No failed action to re-run")]
+    NothingToRerun,
 }
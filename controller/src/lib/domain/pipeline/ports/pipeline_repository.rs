@@ -1,11 +1,45 @@
 use async_trait::async_trait;
 
-use crate::domain::pipeline::entities::pipeline::{Pipeline, PipelineError};
+use crate::domain::{
+    action::entities::action::NewAction,
+    command::entities::command::Step,
+    pipeline::entities::pipeline::{
+        CheckoutOptions, Pipeline, PipelineError, PipelineFilter, TriggerContext,
+    },
+};
 
 #[async_trait]
 pub trait PipelineRepository: Send + Sync {
-    async fn create(&self, repository_url: String, name: String)
-        -> Result<Pipeline, PipelineError>;
-    async fn find_all(&self) -> Result<Vec<Pipeline>, PipelineError>;
+    async fn create(
+        &self,
+        repository_url: String,
+        name: String,
+        trigger: TriggerContext,
+        checkout: CheckoutOptions,
+    ) -> Result<Pipeline, PipelineError>;
+    /// Creates a new run of a pipeline, with the same repository and trigger,
+    /// along with its actions and their commands. Either all of them are
+    /// created or none is. The actions are moved to the new run whatever
+    /// their `pipeline_id`, and are not filled in the returned pipeline.
+    async fn create_rerun(
+        &self,
+        pipeline_id: i64,
+        actions: Vec<(NewAction, Vec<Step>)>,
+    ) -> Result<Pipeline, PipelineError>;
+    /// Returns up to `limit` pipelines matching the filter, newest first,
+    /// starting after the pipeline `cursor` when set.
+    async fn find_page(
+        &self,
+        filter: &PipelineFilter,
+        cursor: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<Pipeline>, PipelineError>;
     async fn find_by_id(&self, pipeline_id: i64) -> Result<Pipeline, PipelineError>;
+    /// Marks the current status of the pipeline as reported. Returns the
+    /// pipeline if its status changed since the last report, `None` if it
+    /// was reported already.
+    async fn claim_status_report(
+        &self,
+        pipeline_id: i64,
+    ) -> Result<Option<Pipeline>, PipelineError>;
 }
@@ -1,6 +1,8 @@
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
+use crate::domain::secrets::entities::Credentials;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Release {
     pub id: i64,
@@ -47,6 +49,8 @@ pub enum ReleaseError {
 pub struct CreateReleaseRequest {
     pub repo_url: String,
     pub revision: String,
+    /// Set for a private repository
+    pub credentials: Option<Credentials>,
 }
 
 #[derive(Debug, Clone)]
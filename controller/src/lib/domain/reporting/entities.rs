@@ -0,0 +1,13 @@
+use thiserror::Error;
+
+#[derive(Debug, Error)]
+pub enum ReportError {
+    #[error("Cannot find the repository of {0}")]
+    InvalidRepository(String),
+    #[error("{0} is not a commit hash")]
+    InvalidCommit(String),
+    #[error("Request error: {0}")]
+    RequestError(String),
+    #[error("Status rejected with HTTP {status}: {body}")]
+    Rejected { status: u16, body: String },
+}
@@ -0,0 +1,12 @@
+use async_trait::async_trait;
+
+use crate::domain::pipeline::entities::pipeline::Pipeline;
+use crate::domain::reporting::entities::ReportError;
+
+/// Publishes the status of the pipelines on the forge hosting their
+/// repository, so that it shows next to the commit they run on.
+#[async_trait]
+pub trait StatusReporter: Send + Sync {
+    /// Reports the current status of a pipeline on its triggering commit.
+    async fn report(&self, pipeline: &Pipeline) -> Result<(), ReportError>;
+}
@@ -15,4 +15,7 @@ pub trait SchedulerClient: Send + Sync {
         Pin<Box<dyn Stream<Item = Result<ActionResponse, Box<dyn Error + Send + Sync>>> + Send>>,
         Box<dyn Error + Send + Sync>,
     >;
+
+    /// Asks the scheduler to stop an action running on one of its agents.
+    async fn cancel_action(&self, action_id: u32) -> Result<(), Box<dyn Error + Send + Sync>>;
 }
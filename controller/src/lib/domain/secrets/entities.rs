@@ -0,0 +1,181 @@
+use std::{collections::HashMap, fmt, str::FromStr};
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::domain::pipeline::entities::expression::replace_expressions;
+use crate::parser::pipe_parser::is_valid_env_name;
+
+/// A secret as exposed outside of the controller: its value never leaves the store.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Secret {
+    pub id: i64,
+    pub repository_url: String,
+    pub name: String,
+}
+
+/// A secret as it is persisted, encrypted with the controller master key.
+#[derive(Debug, Clone)]
+pub struct EncryptedSecret {
+    pub name: String,
+    pub nonce: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+}
+
+/// How a private repository is authenticated against when it is fetched.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CredentialsKind {
+    /// Private key of a deploy key, the repository is fetched over SSH
+    SshKey,
+    /// Token sent with a username over HTTPS
+    HttpsToken,
+}
+
+impl CredentialsKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CredentialsKind::SshKey => "ssh_key",
+            CredentialsKind::HttpsToken => "https_token",
+        }
+    }
+}
+
+impl FromStr for CredentialsKind {
+    type Err = SecretError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ssh_key" => Ok(CredentialsKind::SshKey),
+            "https_token" => Ok(CredentialsKind::HttpsToken),
+            _ => Err(SecretError::InvalidCredentials(format!(
+                "unknown kind {}",
+                s
+            ))),
+        }
+    }
+}
+
+/// Username of the HTTPS tokens stored without one, accepted along any token
+/// by GitHub.
+pub const DEFAULT_TOKEN_USERNAME: &str = "x-access-token";
+
+/// The credentials of a repository as exposed outside of the controller: the
+/// key or token never leaves the store.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RepositoryCredentials {
+    pub id: i64,
+    pub repository_url: String,
+    pub kind: CredentialsKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub username: Option<String>,
+}
+
+/// The credentials of a repository as they are persisted, the key or token
+/// encrypted with the controller master key.
+#[derive(Debug, Clone)]
+pub struct EncryptedCredentials {
+    pub kind: CredentialsKind,
+    pub username: Option<String>,
+    pub nonce: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+}
+
+/// Decrypted credentials, sent along the requests fetching the repository.
+/// Their debug output leaves the key and token out.
+#[derive(Clone, PartialEq)]
+pub enum Credentials {
+    SshKey { private_key: String },
+    HttpsToken { username: String, token: String },
+}
+
+impl Credentials {
+    /// The value to keep out of logs.
+    pub fn secret(&self) -> &str {
+        match self {
+            Credentials::SshKey { private_key } => private_key,
+            Credentials::HttpsToken { token, .. } => token,
+        }
+    }
+}
+
+impl fmt::Debug for Credentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Credentials::SshKey { .. } => f
+                .debug_struct("SshKey")
+                .field("private_key", &SECRET_MASK)
+                .finish(),
+            Credentials::HttpsToken { username, .. } => f
+                .debug_struct("HttpsToken")
+                .field("username", username)
+                .field("token", &SECRET_MASK)
+                .finish(),
+        }
+    }
+}
+
+#[derive(Debug, Error)]
+pub enum SecretError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+    #[error("Secret not found")]
+    NotFound,
+    #[error("Invalid secret name: {0}")]
+    InvalidName(String),
+    #[error("Invalid secret reference: {0}")]
+    InvalidReference(String),
+    #[error("Secret {0} is not defined for this repository")]
+    Undefined(String),
+    #[error("Invalid credentials: {0}")]
+    InvalidCredentials(String),
+    #[error("Invalid master key")]
+    InvalidKey,
+    #[error("No master key is configured, set SECRETS_KEY to store secrets")]
+    MissingKey,
+    #[error("Failed to encrypt secret")]
+    EncryptionError,
+    #[error("Failed to decrypt secret")]
+    DecryptionError,
+}
+
+/// Replacement written in place of a secret value in logs.
+pub const SECRET_MASK: &str = "***";
+
+/// Expression context giving access to the secrets, as in `${{ secrets.NAME }}`.
+pub const SECRETS_CONTEXT: &str = "secrets.";
+
+/// Secret names follow the rules of environment variable names.
+pub fn is_valid_secret_name(name: &str) -> bool {
+    is_valid_env_name(name)
+}
+
+/// Replaces every `${{ secrets.NAME }}` expression of `value` with the
+/// matching secret value.
+pub fn resolve_secret_references(
+    value: &str,
+    secrets: &HashMap<String, String>,
+) -> Result<String, SecretError> {
+    replace_expressions(value, |expression| {
+        let name = expression
+            .strip_prefix(SECRETS_CONTEXT)
+            .ok_or_else(|| SecretError::InvalidReference(expression.to_string()))?;
+        secrets
+            .get(name)
+            .cloned()
+            .map(Some)
+            .ok_or_else(|| SecretError::Undefined(name.to_string()))
+    })
+}
+
+/// Scrubs every secret value out of a log line.
+///
+/// Longer values are masked first so that a secret containing another one
+/// is not partially revealed.
+pub fn mask_secrets(line: &str, secrets: &[String]) -> String {
+    let mut values: Vec<&String> = secrets.iter().filter(|s| !s.is_empty()).collect();
+    values.sort_by_key(|s| std::cmp::Reverse(s.len()));
+    values.into_iter().fold(line.to_string(), |line, secret| {
+        line.replace(secret.as_str(), SECRET_MASK)
+    })
+}
@@ -0,0 +1,43 @@
+use async_trait::async_trait;
+
+use super::entities::{
+    CredentialsKind, EncryptedCredentials, EncryptedSecret, RepositoryCredentials, Secret,
+    SecretError,
+};
+
+#[async_trait]
+pub trait SecretRepository: Send + Sync {
+    /// Creates the secret, or replaces its value when it already exists.
+    async fn upsert(
+        &self,
+        repository_url: String,
+        name: String,
+        nonce: Vec<u8>,
+        ciphertext: Vec<u8>,
+    ) -> Result<Secret, SecretError>;
+    async fn list(&self, repository_url: String) -> Result<Vec<Secret>, SecretError>;
+    async fn find_encrypted(
+        &self,
+        repository_url: String,
+    ) -> Result<Vec<EncryptedSecret>, SecretError>;
+    async fn delete(&self, repository_url: String, name: String) -> Result<(), SecretError>;
+    /// Sets the credentials of a repository, replacing the previous ones.
+    async fn upsert_credentials(
+        &self,
+        repository_url: String,
+        kind: CredentialsKind,
+        username: Option<String>,
+        nonce: Vec<u8>,
+        ciphertext: Vec<u8>,
+    ) -> Result<RepositoryCredentials, SecretError>;
+    async fn find_credentials(
+        &self,
+        repository_url: String,
+    ) -> Result<RepositoryCredentials, SecretError>;
+    /// Returns `None` when the repository has no credentials.
+    async fn find_encrypted_credentials(
+        &self,
+        repository_url: String,
+    ) -> Result<Option<EncryptedCredentials>, SecretError>;
+    async fn delete_credentials(&self, repository_url: String) -> Result<(), SecretError>;
+}
@@ -0,0 +1,20 @@
+use crate::domain::secrets::entities::SecretError;
+
+/// Encrypts secret values at rest.
+///
+/// `associated_data` is authenticated but not encrypted: binding it to the
+/// secret identity prevents a ciphertext from being moved to another secret.
+pub trait SecretCipher: Send + Sync {
+    /// Returns the nonce and the ciphertext.
+    fn encrypt(
+        &self,
+        plaintext: &[u8],
+        associated_data: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>), SecretError>;
+    fn decrypt(
+        &self,
+        nonce: &[u8],
+        ciphertext: &[u8],
+        associated_data: &[u8],
+    ) -> Result<Vec<u8>, SecretError>;
+}
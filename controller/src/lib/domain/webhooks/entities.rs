@@ -0,0 +1,208 @@
+use std::{fmt, str::FromStr, time::Duration};
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use sha2::Sha256;
+use thiserror::Error;
+use time::OffsetDateTime;
+
+use crate::domain::{
+    pipeline::entities::pipeline::{Pipeline, PipelineStatus},
+    releases::entities::Release,
+    secrets::entities::SecretError,
+};
+
+/// What a webhook can be notified of.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookEvent {
+    PipelineStarted,
+    PipelineSucceeded,
+    PipelineFailed,
+    ReleaseCreated,
+}
+
+impl WebhookEvent {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            WebhookEvent::PipelineStarted => "pipeline_started",
+            WebhookEvent::PipelineSucceeded => "pipeline_succeeded",
+            WebhookEvent::PipelineFailed => "pipeline_failed",
+            WebhookEvent::ReleaseCreated => "release_created",
+        }
+    }
+
+    /// The event of a pipeline that finished with `status`. A cancelled or
+    /// unfinished pipeline has none.
+    pub fn finished(status: PipelineStatus) -> Option<Self> {
+        match status {
+            PipelineStatus::Success => Some(WebhookEvent::PipelineSucceeded),
+            PipelineStatus::Failed => Some(WebhookEvent::PipelineFailed),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for WebhookEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for WebhookEvent {
+    type Err = WebhookError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pipeline_started" => Ok(WebhookEvent::PipelineStarted),
+            "pipeline_succeeded" => Ok(WebhookEvent::PipelineSucceeded),
+            "pipeline_failed" => Ok(WebhookEvent::PipelineFailed),
+            "release_created" => Ok(WebhookEvent::ReleaseCreated),
+            _ => Err(WebhookError::InvalidEvent(s.to_string())),
+        }
+    }
+}
+
+/// A webhook as exposed outside of the controller: its secret never leaves
+/// the store.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Webhook {
+    pub id: i64,
+    pub repository_url: String,
+    /// Where the events are posted
+    pub url: String,
+    pub events: Vec<WebhookEvent>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+}
+
+impl Webhook {
+    pub fn subscribes_to(&self, event: WebhookEvent) -> bool {
+        self.events.contains(&event)
+    }
+}
+
+/// A webhook as it is persisted, its secret encrypted with the controller
+/// master key.
+#[derive(Debug, Clone)]
+pub struct EncryptedWebhook {
+    pub webhook: Webhook,
+    pub nonce: Vec<u8>,
+    pub ciphertext: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DeliveryStatus {
+    /// Not sent yet, or waiting to be sent again
+    Pending,
+    Succeeded,
+    /// Every attempt failed
+    Failed,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Pending => "pending",
+            DeliveryStatus::Succeeded => "succeeded",
+            DeliveryStatus::Failed => "failed",
+        }
+    }
+}
+
+impl FromStr for DeliveryStatus {
+    type Err = ();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pending" => Ok(DeliveryStatus::Pending),
+            "succeeded" => Ok(DeliveryStatus::Succeeded),
+            "failed" => Ok(DeliveryStatus::Failed),
+            _ => Err(()),
+        }
+    }
+}
+
+/// An event sent to a webhook, along with the outcome of its last attempt.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub id: i64,
+    pub webhook_id: i64,
+    pub event: WebhookEvent,
+    pub payload: Value,
+    pub status: DeliveryStatus,
+    pub attempts: i32,
+    /// Status code the webhook answered the last attempt with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub response_status: Option<i32>,
+    /// Why the last attempt failed
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(with = "time::serde::rfc3339")]
+    pub created_at: OffsetDateTime,
+    #[serde(with = "time::serde::rfc3339::option")]
+    pub delivered_at: Option<OffsetDateTime>,
+}
+
+#[derive(Debug, Error)]
+pub enum WebhookError {
+    #[error("Database error: {0}")]
+    DatabaseError(#[from] sqlx::Error),
+    #[error("Webhook not found")]
+    NotFound,
+    #[error("Invalid webhook: {0}")]
+    InvalidWebhook(String),
+    #[error("Unknown event {0}")]
+    InvalidEvent(String),
+    #[error("Secret store error: {0}")]
+    SecretError(#[from] SecretError),
+    #[error("Request error: {0}")]
+    RequestError(String),
+}
+
+/// Header carrying the signature of the payload.
+pub const SIGNATURE_HEADER: &str = "X-Sealci-Signature-256";
+/// Header carrying the event of the payload.
+pub const EVENT_HEADER: &str = "X-Sealci-Event";
+/// Header carrying the ID of the delivery, the same for all its attempts.
+pub const DELIVERY_HEADER: &str = "X-Sealci-Delivery";
+
+/// Attempts of a delivery before it is given up.
+pub const MAX_DELIVERY_ATTEMPTS: i32 = 5;
+
+const FIRST_RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// Delay before the attempt following `attempt`, doubled after each failure.
+pub fn retry_delay(attempt: i32) -> Duration {
+    FIRST_RETRY_DELAY * 2u32.pow(attempt.clamp(1, MAX_DELIVERY_ATTEMPTS) as u32 - 1)
+}
+
+/// HMAC-SHA256 of the payload keyed with the secret of the webhook, as in
+/// `sha256=<hex digest>`.
+pub fn sign(secret: &str, payload: &[u8]) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(payload);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Payload of the events of a pipeline, without its actions.
+pub fn pipeline_payload(event: WebhookEvent, pipeline: &Pipeline) -> Value {
+    json!({
+        "event": event,
+        "repository_url": pipeline.repository_url,
+        "pipeline": Pipeline {
+            actions: vec![],
+            ..pipeline.clone()
+        },
+    })
+}
+
+pub fn release_payload(release: &Release) -> Value {
+    json!({
+        "event": WebhookEvent::ReleaseCreated,
+        "repository_url": release.repo_url,
+        "release": release,
+    })
+}
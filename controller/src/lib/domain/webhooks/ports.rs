@@ -0,0 +1,67 @@
+use async_trait::async_trait;
+use serde_json::Value;
+
+use super::entities::{
+    DeliveryStatus, EncryptedWebhook, Webhook, WebhookDelivery, WebhookError, WebhookEvent,
+};
+
+#[async_trait]
+pub trait WebhookRepository: Send + Sync {
+    async fn create(
+        &self,
+        repository_url: String,
+        url: String,
+        events: Vec<WebhookEvent>,
+        nonce: Vec<u8>,
+        ciphertext: Vec<u8>,
+    ) -> Result<Webhook, WebhookError>;
+    async fn list(&self, repository_url: String) -> Result<Vec<Webhook>, WebhookError>;
+    async fn find_encrypted(&self, id: i64) -> Result<EncryptedWebhook, WebhookError>;
+    /// The webhooks of a repository notified of `event`.
+    async fn find_subscribed(
+        &self,
+        repository_url: String,
+        event: WebhookEvent,
+    ) -> Result<Vec<EncryptedWebhook>, WebhookError>;
+    /// Deletes the webhook along with its deliveries.
+    async fn delete(&self, id: i64) -> Result<(), WebhookError>;
+    async fn create_delivery(
+        &self,
+        webhook_id: i64,
+        event: WebhookEvent,
+        payload: Value,
+    ) -> Result<WebhookDelivery, WebhookError>;
+    /// Records the outcome of an attempt of a delivery.
+    async fn record_attempt(
+        &self,
+        delivery_id: i64,
+        status: DeliveryStatus,
+        response_status: Option<i32>,
+        error: Option<String>,
+    ) -> Result<(), WebhookError>;
+    /// The last `limit` deliveries of a webhook, the latest first.
+    async fn list_deliveries(
+        &self,
+        webhook_id: i64,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>, WebhookError>;
+    async fn find_delivery(
+        &self,
+        webhook_id: i64,
+        delivery_id: i64,
+    ) -> Result<WebhookDelivery, WebhookError>;
+    /// The deliveries that are still to be sent, the oldest first.
+    async fn list_pending(&self) -> Result<Vec<WebhookDelivery>, WebhookError>;
+}
+
+#[async_trait]
+pub trait WebhookSender: Send + Sync {
+    /// Posts the payload of a delivery to `url`, signed with `secret`.
+    /// Returns the status code of the response, whatever it is.
+    async fn send(
+        &self,
+        url: &str,
+        delivery: &WebhookDelivery,
+        secret: &str,
+    ) -> Result<u16, WebhookError>;
+}
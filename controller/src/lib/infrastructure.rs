@@ -1,3 +1,7 @@
+pub mod crypto;
 pub mod db;
 pub mod grpc;
-pub mod repositories;
\ No newline at end of file
+pub mod reporting;
+pub mod repositories;
+pub mod storage;
+pub mod webhooks;
@@ -0,0 +1 @@
+pub mod aes_gcm_cipher;
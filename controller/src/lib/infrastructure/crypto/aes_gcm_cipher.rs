@@ -0,0 +1,81 @@
+use aes_gcm::{
+    aead::{Aead, AeadCore, KeyInit, OsRng, Payload},
+    Aes256Gcm, Key, Nonce,
+};
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+use crate::domain::secrets::{entities::SecretError, services::SecretCipher};
+
+const KEY_LENGTH: usize = 32;
+const NONCE_LENGTH: usize = 12;
+
+/// AES-256-GCM cipher keyed with the controller master key.
+pub struct AesGcmSecretCipher {
+    /// `None` when no master key is configured, nothing can be stored then
+    cipher: Option<Aes256Gcm>,
+}
+
+impl AesGcmSecretCipher {
+    /// Builds the cipher from a base64 encoded 256-bit key.
+    pub fn new(master_key: &str) -> Result<Self, SecretError> {
+        let key = STANDARD
+            .decode(master_key.trim())
+            .map_err(|_| SecretError::InvalidKey)?;
+        if key.len() != KEY_LENGTH {
+            return Err(SecretError::InvalidKey);
+        }
+        Ok(Self {
+            cipher: Some(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key))),
+        })
+    }
+
+    /// Builds a cipher without key, failing every encryption and decryption.
+    pub fn without_key() -> Self {
+        Self { cipher: None }
+    }
+
+    fn cipher(&self) -> Result<&Aes256Gcm, SecretError> {
+        self.cipher.as_ref().ok_or(SecretError::MissingKey)
+    }
+}
+
+impl SecretCipher for AesGcmSecretCipher {
+    fn encrypt(
+        &self,
+        plaintext: &[u8],
+        associated_data: &[u8],
+    ) -> Result<(Vec<u8>, Vec<u8>), SecretError> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher()?
+            .encrypt(
+                &nonce,
+                Payload {
+                    msg: plaintext,
+                    aad: associated_data,
+                },
+            )
+            .map_err(|_| SecretError::EncryptionError)?;
+        Ok((nonce.to_vec(), ciphertext))
+    }
+
+    fn decrypt(
+        &self,
+        nonce: &[u8],
+        ciphertext: &[u8],
+        associated_data: &[u8],
+    ) -> Result<Vec<u8>, SecretError> {
+        if nonce.len() != NONCE_LENGTH {
+            return Err(SecretError::DecryptionError);
+        }
+        self.cipher()?
+            .decrypt(
+                Nonce::from_slice(nonce),
+                Payload {
+                    msg: ciphertext,
+                    aad: associated_data,
+                },
+            )
+            .map_err(|_| SecretError::DecryptionError)
+    }
+}
@@ -11,13 +11,15 @@ use crate::{
         releases::entities::{
             CreateReleaseRequest, CreateReleaseResponse, PublicKey, ReleaseStatus,
         },
+        secrets::entities::Credentials,
     },
 };
 
 use crate::infrastructure::grpc::proto_release_agent::{
     CreateReleaseRequest as ProtoCreateReleaseRequest,
     CreateReleaseResponse as ProtoCreateReleaseResponse,
-    CreateReleaseStatus as ProtoCreateReleaseStatus, PublicKey as ProtoPublicKey,
+    CreateReleaseStatus as ProtoCreateReleaseStatus, Credentials as ProtoCredentials,
+    PublicKey as ProtoPublicKey,
 };
 
 use super::proto_release_agent::release_agent_client::ReleaseAgentClient;
@@ -61,6 +63,23 @@ impl From<CreateReleaseRequest> for ProtoCreateReleaseRequest {
         ProtoCreateReleaseRequest {
             repo_url: grpc_request.repo_url,
             revision: grpc_request.revision,
+            credentials: grpc_request.credentials.map(ProtoCredentials::from),
+        }
+    }
+}
+
+impl From<Credentials> for ProtoCredentials {
+    fn from(credentials: Credentials) -> Self {
+        match credentials {
+            Credentials::SshKey { private_key } => ProtoCredentials {
+                ssh_private_key: private_key,
+                ..Default::default()
+            },
+            Credentials::HttpsToken { username, token } => ProtoCredentials {
+                username,
+                token,
+                ..Default::default()
+            },
         }
     }
 }
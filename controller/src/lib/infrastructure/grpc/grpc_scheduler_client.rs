@@ -2,13 +2,17 @@ use crate::application::AppError;
 use crate::domain::action::entities::action::{
     ActionRequest as DomainActionRequest, ActionResponse as DomainActionResponse,
     ActionResult as DomainActionResult, ActionStatus as DomainActionStatus,
+    StepEvent as DomainStepEvent,
 };
+use crate::domain::log::entities::log::LogStream as DomainLogStream;
 use crate::domain::scheduler::services::scheduler_client::SchedulerClient;
+use crate::domain::secrets::entities::Credentials as DomainCredentials;
 use futures::lock::Mutex;
 use futures::{Stream, StreamExt};
 use std::error::Error;
 use std::pin::Pin;
 use std::sync::Arc;
+use time::OffsetDateTime;
 use tonic::transport::Channel;
 use tonic::{async_trait, Streaming};
 use tracing::error;
@@ -16,7 +20,9 @@ use tracing::error;
 use crate::infrastructure::grpc::proto_scheduler::controller_client::ControllerClient;
 use crate::infrastructure::grpc::proto_scheduler::{
     ActionRequest as ProtoActionRequest, ActionResponse as ProtoActionResponse,
-    ActionResult as ProtoActionResult, ExecutionContext, RunnerType,
+    ActionResult as ProtoActionResult, Cache as ProtoCache, CancelActionRequest,
+    Checkout as ProtoCheckout, Credentials as ProtoCredentials, ExecutionContext,
+    LogStream as ProtoLogStream, RunnerType, Service as ProtoService, Step as ProtoStep,
 };
 
 impl From<ProtoActionResponse> for DomainActionResponse {
@@ -27,10 +33,46 @@ impl From<ProtoActionResponse> for DomainActionResponse {
             result: grpc_response
                 .result
                 .map(|res| DomainActionResult::from(res)),
+            artifact_complete: grpc_response
+                .artifact
+                .as_ref()
+                .is_some_and(|chunk| chunk.complete),
+            artifact: grpc_response.artifact.map(|chunk| chunk.data),
+            step: match (grpc_response.step_started, grpc_response.step_finished) {
+                (_, Some(step)) => Some(DomainStepEvent::Finished {
+                    index: step.index as i32,
+                    name: step.name,
+                    exit_code: step.exit_code,
+                    started_at: from_unix_millis(step.started_at),
+                    finished_at: from_unix_millis(step.finished_at),
+                }),
+                (Some(step), None) => Some(DomainStepEvent::Started {
+                    index: step.index as i32,
+                    name: step.name,
+                    started_at: from_unix_millis(step.started_at),
+                }),
+                (None, None) => None,
+            },
+            timestamp: match grpc_response.timestamp {
+                0 => None,
+                millis => Some(from_unix_millis(millis)),
+            },
+            stream: match ProtoLogStream::try_from(grpc_response.stream) {
+                Ok(ProtoLogStream::Stdout) => DomainLogStream::Stdout,
+                Ok(ProtoLogStream::Stderr) => DomainLogStream::Stderr,
+                _ => DomainLogStream::System,
+            },
+            step_index: grpc_response.step_index.map(|index| index as i32),
         }
     }
 }
 
+/// Step timestamps are sent as milliseconds since the Unix epoch.
+fn from_unix_millis(millis: i64) -> OffsetDateTime {
+    OffsetDateTime::from_unix_timestamp_nanos(millis as i128 * 1_000_000)
+        .unwrap_or(OffsetDateTime::UNIX_EPOCH)
+}
+
 impl From<ProtoActionResult> for DomainActionResult {
     fn from(grpc_result: ProtoActionResult) -> Self {
         DomainActionResult {
@@ -48,11 +90,30 @@ impl DomainActionStatus {
             2 => DomainActionStatus::Running,
             3 => DomainActionStatus::Completed,
             4 => DomainActionStatus::Error,
+            5 => DomainActionStatus::Skipped,
+            6 => DomainActionStatus::Cancelled,
+            7 => DomainActionStatus::TimedOut,
             _ => DomainActionStatus::Error, // Default case for unknown status
         }
     }
 }
 
+impl From<DomainCredentials> for ProtoCredentials {
+    fn from(credentials: DomainCredentials) -> Self {
+        match credentials {
+            DomainCredentials::SshKey { private_key } => ProtoCredentials {
+                ssh_private_key: private_key,
+                ..Default::default()
+            },
+            DomainCredentials::HttpsToken { username, token } => ProtoCredentials {
+                username,
+                token,
+                ..Default::default()
+            },
+        }
+    }
+}
+
 impl From<DomainActionRequest> for ProtoActionRequest {
     fn from(domain_request: DomainActionRequest) -> Self {
         ProtoActionRequest {
@@ -61,8 +122,51 @@ impl From<DomainActionRequest> for ProtoActionRequest {
                 r#type: RunnerType::Docker as i32,
                 container_image: domain_request.context.container_image.clone(),
             }),
-            commands: domain_request.commands.clone(),
+            commands: domain_request
+                .commands
+                .iter()
+                .map(|step| step.run.clone())
+                .collect(),
             repo_url: domain_request.repo_url.clone(),
+            env: domain_request.env,
+            timeout_seconds: domain_request.timeout_seconds,
+            artifacts: domain_request.artifacts,
+            cache: domain_request.cache.map(|cache| ProtoCache {
+                key: cache.key,
+                paths: cache.paths,
+            }),
+            services: domain_request
+                .services
+                .into_iter()
+                .map(|(name, service)| ProtoService {
+                    name,
+                    image: service.image,
+                    env: service.env,
+                    healthcheck: service.healthcheck,
+                })
+                .collect(),
+            shell: domain_request.shell,
+            steps: domain_request
+                .commands
+                .into_iter()
+                .map(|step| ProtoStep {
+                    run: step.run,
+                    name: step.name,
+                    working_directory: step.working_directory,
+                    env: step.env,
+                    continue_on_error: step.continue_on_error,
+                })
+                .collect(),
+            checkout: Some(ProtoCheckout {
+                revision: domain_request.checkout.revision.unwrap_or_default(),
+                r#ref: domain_request.checkout.git_ref.unwrap_or_default(),
+                depth: domain_request.checkout.depth,
+                submodules: domain_request.checkout.submodules,
+                credentials: domain_request
+                    .checkout
+                    .credentials
+                    .map(ProtoCredentials::from),
+            }),
         }
     }
 }
@@ -134,4 +238,16 @@ impl SchedulerClient for GrpcSchedulerClient {
 
         Ok(Box::pin(stream))
     }
+
+    async fn cancel_action(&self, action_id: u32) -> Result<(), Box<dyn Error + Send + Sync>> {
+        let mut client = self.client.lock().await;
+        client
+            .cancel_action(CancelActionRequest { action_id })
+            .await
+            .map_err(|e| {
+                error!("Error while cancelling action {}: {:?}", action_id, e);
+                Box::new(e) as Box<dyn Error + Send + Sync>
+            })?;
+        Ok(())
+    }
 }
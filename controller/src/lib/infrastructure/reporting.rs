@@ -0,0 +1 @@
+pub mod github_status_reporter;
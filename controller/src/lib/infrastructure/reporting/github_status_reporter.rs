@@ -0,0 +1,141 @@
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::Serialize;
+use std::time::Duration;
+
+use crate::domain::pipeline::entities::pipeline::{is_commit_hash, Pipeline, PipelineStatus};
+use crate::domain::reporting::{entities::ReportError, ports::StatusReporter};
+
+/// GitHub rejects the requests without a user agent.
+const USER_AGENT: &str = "sealci";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Reports the pipelines as commit statuses through the GitHub REST API. Each
+/// pipeline has its own status context, `sealci/<pipeline name>`, that branch
+/// protection rules can require.
+pub struct GithubStatusReporter {
+    client: Client,
+    api_url: String,
+    /// Host of the repositories the API serves, the others are not reported
+    host: String,
+    token: String,
+    /// Base URL of the web client, the statuses link to the pipeline there
+    web_url: Option<String>,
+}
+
+#[derive(Serialize)]
+struct CommitStatusRequest<'a> {
+    state: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    target_url: Option<String>,
+    description: &'a str,
+    context: String,
+}
+
+impl GithubStatusReporter {
+    pub fn new(api_url: String, host: String, token: String, web_url: Option<String>) -> Self {
+        Self {
+            client: Client::new(),
+            api_url: api_url.trim_end_matches('/').to_string(),
+            host,
+            token,
+            web_url: web_url.map(|url| url.trim_end_matches('/').to_string()),
+        }
+    }
+}
+
+/// Owner and name of a repository from its URL, over HTTPS or SSH, e.g.
+/// `https://github.com/owner/name` or `git@github.com:owner/name.git`.
+pub fn repository_slug(repository_url: &str) -> Option<(&str, &str)> {
+    let path = repository_url.trim_end_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    let mut segments = path.rsplit(['/', ':']);
+    let name = segments.next()?;
+    let owner = segments.next()?;
+    // Neither can be the host of the repository
+    if name.is_empty() || owner.is_empty() || owner.contains(['.', '@']) {
+        return None;
+    }
+    Some((owner, name))
+}
+
+/// Host of a repository from its URL, over HTTPS or SSH, without the user
+/// info nor the port.
+pub fn repository_host(repository_url: &str) -> Option<&str> {
+    let address = match repository_url.split_once("://") {
+        Some((_, address)) => address.split('/').next()?,
+        // SSH addresses such as `git@github.com:owner/name.git`
+        None => repository_url.split_once(':')?.0,
+    };
+    let host = address.rsplit('@').next()?;
+    let host = host.split(':').next()?;
+    (!host.is_empty()).then_some(host)
+}
+
+/// State and description of the commit status of a pipeline.
+pub fn commit_state(status: PipelineStatus) -> (&'static str, &'static str) {
+    match status {
+        PipelineStatus::Queued => ("pending", "Pipeline is queued"),
+        PipelineStatus::Running => ("pending", "Pipeline is running"),
+        PipelineStatus::Success => ("success", "Pipeline succeeded"),
+        PipelineStatus::Failed => ("failure", "Pipeline failed"),
+        PipelineStatus::Cancelled => ("error", "Pipeline was cancelled"),
+    }
+}
+
+#[async_trait]
+impl StatusReporter for GithubStatusReporter {
+    async fn report(&self, pipeline: &Pipeline) -> Result<(), ReportError> {
+        // A pipeline uploaded by hand has no commit to report on
+        let Some(commit_sha) = &pipeline.trigger.commit_sha else {
+            return Ok(());
+        };
+        // Nor has a repository hosted elsewhere, even if GitHub has one of
+        // the same name
+        if !repository_host(&pipeline.repository_url)
+            .is_some_and(|host| host.eq_ignore_ascii_case(&self.host))
+        {
+            return Ok(());
+        }
+        // The hash is part of the path the token is sent to
+        if !is_commit_hash(commit_sha) {
+            return Err(ReportError::InvalidCommit(commit_sha.clone()));
+        }
+        let (owner, name) = repository_slug(&pipeline.repository_url)
+            .ok_or_else(|| ReportError::InvalidRepository(pipeline.repository_url.clone()))?;
+        let (state, description) = commit_state(pipeline.status);
+
+        let response = self
+            .client
+            .post(format!(
+                "{}/repos/{}/{}/statuses/{}",
+                self.api_url, owner, name, commit_sha
+            ))
+            .bearer_auth(&self.token)
+            .header(reqwest::header::ACCEPT, "application/vnd.github+json")
+            .header(reqwest::header::USER_AGENT, USER_AGENT)
+            .timeout(REQUEST_TIMEOUT)
+            .json(&CommitStatusRequest {
+                state,
+                target_url: self
+                    .web_url
+                    .as_ref()
+                    .map(|url| format!("{}/pipelines/{}", url, pipeline.id)),
+                description,
+                context: format!("sealci/{}", pipeline.name),
+            })
+            .send()
+            .await
+            .map_err(|e| ReportError::RequestError(e.to_string()))?;
+
+        let status = response.status();
+        if !status.is_success() {
+            return Err(ReportError::Rejected {
+                status: status.as_u16(),
+                body: response.text().await.unwrap_or_default(),
+            });
+        }
+        Ok(())
+    }
+}
@@ -3,3 +3,5 @@ pub mod command_repository;
 pub mod log_repository;
 pub mod pipeline_repository;
 pub mod release_repository;
+pub mod secret_repository;
+pub mod webhook_repository;
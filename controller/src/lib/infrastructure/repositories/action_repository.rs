@@ -1,10 +1,13 @@
 use async_trait::async_trait;
+use sqlx::{types::Json, Transaction};
 use std::{collections::HashMap, sync::Arc};
 
 use crate::domain::action::entities::action::{
-    Action, ActionDTO, ActionError, ActionStatus, ActionType,
+    Action, ActionAttempt, ActionDTO, ActionError, ActionStatus, ActionStep, ActionType, Cache,
+    NewAction, RetryPolicy, Service, StepEvent,
 };
 use crate::domain::action::ports::action_repository::ActionRepository;
+use crate::domain::log::entities::log::{Log, LogDTO, NewLog};
 use crate::infrastructure::db::postgres::Postgres;
 
 pub struct PostgresActionRepository {
@@ -17,19 +20,38 @@ impl PostgresActionRepository {
     }
 }
 
+/// Updates a pipeline after the status of one of its actions changed: the
+/// pipeline starts with its first action and finishes with its last, and its
+/// status follows the status of its actions.
+async fn refresh_pipeline(
+    tx: &mut Transaction<'_, sqlx::Postgres>,
+    pipeline_id: i64,
+    running: bool,
+) -> Result<(), ActionError> {
+    sqlx::query!(
+        r#"UPDATE pipelines SET
+            started_at = CASE WHEN $2 THEN COALESCE(started_at, now()) ELSE started_at END,
+            finished_at = CASE
+                WHEN EXISTS (SELECT 1 FROM actions WHERE pipeline_id = $1 AND finished_at IS NULL) THEN NULL
+                ELSE COALESCE(finished_at, now())
+            END,
+            status = COALESCE((SELECT status FROM pipeline_statuses WHERE pipeline_id = $1), status)
+        WHERE id = $1"#,
+        pipeline_id,
+        running
+    )
+    .execute(&mut **tx)
+    .await
+    .map_err(ActionError::DatabaseError)?;
+    Ok(())
+}
+
 #[async_trait]
 impl ActionRepository for PostgresActionRepository {
-    async fn create(
-        &self,
-        pipeline_id: i64,
-        name: String,
-        container_uri: String,
-        r#type: ActionType,
-        status: String,
-    ) -> Result<Action, ActionError> {
+    async fn create(&self, action: NewAction) -> Result<Action, ActionError> {
         let result = sqlx::query!(
-      r#"INSERT INTO actions (pipeline_id, name, container_uri, type, status) VALUES ($1, $2, $3, $4, $5) RETURNING id, pipeline_id, name, container_uri, type, status"#,
-      pipeline_id, name, container_uri, &r#type.to_string(), status
+      r#"INSERT INTO actions (pipeline_id, name, container_uri, type, status, needs, allow_failure, env, timeout_seconds, retry, condition, artifacts, cache, services, shell, reused_from, attempt, started_at, finished_at) SELECT $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, COALESCE(source.attempt, 1), source.started_at, source.finished_at FROM (SELECT 1) AS one LEFT JOIN actions source ON source.id = $16 RETURNING id, pipeline_id, name, container_uri, type, status, needs, allow_failure, env AS "env: Json<HashMap<String, String>>", timeout_seconds, retry AS "retry: Json<RetryPolicy>", condition, artifacts, cache AS "cache: Json<Cache>", services AS "services: Json<HashMap<String, Service>>", shell, attempt, reused_from, created_at, started_at, finished_at"#,
+      action.pipeline_id, action.name, action.container_uri, &action.r#type.to_string(), action.status, &action.needs, action.allow_failure, Json(&action.env) as _, action.timeout_seconds, action.retry.as_ref().map(Json) as _, action.condition, &action.artifacts, action.cache.as_ref().map(Json) as _, Json(&action.services) as _, &action.shell, action.reused_from
     )
     .fetch_one(&self.postgres.get_pool())
     .await;
@@ -43,7 +65,24 @@ impl ActionRepository for PostgresActionRepository {
                 container_uri: row.container_uri,
                 status: row.status.into(),
                 commands: vec![],
+                needs: row.needs,
+                allow_failure: row.allow_failure,
+                env: row.env.0,
+                timeout_seconds: row.timeout_seconds,
+                retry: row.retry.map(|retry| retry.0),
+                condition: row.condition,
+                artifacts: row.artifacts,
+                cache: row.cache.map(|cache| cache.0),
+                services: row.services.0,
+                shell: row.shell,
+                attempt: row.attempt,
+                reused_from: row.reused_from,
+                created_at: row.created_at,
+                started_at: row.started_at,
+                finished_at: row.finished_at,
                 logs: None,
+                attempts: None,
+                steps: None,
             })
             .map_err(ActionError::DatabaseError)
     }
@@ -59,11 +98,31 @@ impl ActionRepository for PostgresActionRepository {
                 a.type          AS action_type,
                 a.container_uri,
                 a.status,
+                a.needs,
+                a.allow_failure,
+                a.env           AS "env: Json<HashMap<String, String>>",
+                a.timeout_seconds,
+                a.retry         AS "retry: Json<RetryPolicy>",
+                a.condition,
+                a.artifacts,
+                a.cache         AS "cache: Json<Cache>",
+                a.services      AS "services: Json<HashMap<String, Service>>",
+                a.shell,
+                a.attempt,
+                a.reused_from,
+                a.created_at,
+                a.started_at,
+                a.finished_at,
                 c.command       AS "command?",
-                c.id            AS "command_id?"
+                c.id            AS "command_id?",
+                c.name          AS "command_name?",
+                c.working_directory AS "command_working_directory?",
+                c.env           AS "command_env?: Json<HashMap<String, String>>",
+                c.continue_on_error AS "command_continue_on_error?"
             FROM actions a
             LEFT JOIN commands c ON a.id = c.action_id
             WHERE a.id = $1
+            ORDER BY c.id
             "#,
             action_id
         )
@@ -87,8 +146,8 @@ impl ActionRepository for PostgresActionRepository {
 
         let mut commands_vec = Vec::new();
         for r in &rows {
-            if let Some(cmd) = r.command.clone() {
-                commands_vec.push(cmd);
+            if let Some(step) = r.step() {
+                commands_vec.push(step);
             }
         }
 
@@ -100,11 +159,32 @@ impl ActionRepository for PostgresActionRepository {
             container_uri: first.container_uri.clone(),
             status,
             commands: commands_vec,
+            needs: first.needs.clone(),
+            allow_failure: first.allow_failure,
+            env: first.env.0.clone(),
+            timeout_seconds: first.timeout_seconds,
+            retry: first.retry.as_ref().map(|retry| retry.0.clone()),
+            condition: first.condition.clone(),
+            artifacts: first.artifacts.clone(),
+            cache: first.cache.as_ref().map(|cache| cache.0.clone()),
+            services: first.services.0.clone(),
+            shell: first.shell.clone(),
+            attempt: first.attempt,
+            reused_from: first.reused_from,
+            created_at: first.created_at,
+            started_at: first.started_at,
+            finished_at: first.finished_at,
             logs: None,
+            attempts: None,
+            steps: None,
         })
     }
 
     async fn find_by_pipeline_id(&self, pipeline_id: i64) -> Result<Vec<Action>, ActionError> {
+        self.find_by_pipeline_ids(&[pipeline_id]).await
+    }
+
+    async fn find_by_pipeline_ids(&self, pipeline_ids: &[i64]) -> Result<Vec<Action>, ActionError> {
         let rows = sqlx::query_as!(
             ActionDTO,
             r#"
@@ -115,13 +195,33 @@ impl ActionRepository for PostgresActionRepository {
                 a.type          AS action_type,
                 a.container_uri,
                 a.status,
+                a.needs,
+                a.allow_failure,
+                a.env           AS "env: Json<HashMap<String, String>>",
+                a.timeout_seconds,
+                a.retry         AS "retry: Json<RetryPolicy>",
+                a.condition,
+                a.artifacts,
+                a.cache         AS "cache: Json<Cache>",
+                a.services      AS "services: Json<HashMap<String, Service>>",
+                a.shell,
+                a.attempt,
+                a.reused_from,
+                a.created_at,
+                a.started_at,
+                a.finished_at,
                 c.command       AS "command?",
-                c.id            AS "command_id?"
+                c.id            AS "command_id?",
+                c.name          AS "command_name?",
+                c.working_directory AS "command_working_directory?",
+                c.env           AS "command_env?: Json<HashMap<String, String>>",
+                c.continue_on_error AS "command_continue_on_error?"
             FROM   actions  a
             LEFT   JOIN commands c ON a.id = c.action_id
-            WHERE  a.pipeline_id = $1
+            WHERE  a.pipeline_id = ANY($1)
+            ORDER  BY a.id, c.id
             "#,
-            pipeline_id
+            pipeline_ids
         )
         .fetch_all(&self.postgres.get_pool())
         .await
@@ -147,11 +247,28 @@ impl ActionRepository for PostgresActionRepository {
                 container_uri: row.container_uri.clone(),
                 status,
                 commands: Vec::new(),
+                needs: row.needs.clone(),
+                allow_failure: row.allow_failure,
+                env: row.env.0.clone(),
+                timeout_seconds: row.timeout_seconds,
+                retry: row.retry.as_ref().map(|retry| retry.0.clone()),
+                condition: row.condition.clone(),
+                artifacts: row.artifacts.clone(),
+                cache: row.cache.as_ref().map(|cache| cache.0.clone()),
+                services: row.services.0.clone(),
+                shell: row.shell.clone(),
+                attempt: row.attempt,
+                reused_from: row.reused_from,
+                created_at: row.created_at,
+                started_at: row.started_at,
+                finished_at: row.finished_at,
                 logs: None,
+                attempts: None,
+                steps: None,
             });
 
-            if let Some(cmd) = row.command {
-                entry.commands.push(cmd);
+            if let Some(step) = row.step() {
+                entry.commands.push(step);
             }
         }
 
@@ -161,37 +278,310 @@ impl ActionRepository for PostgresActionRepository {
     }
 
     async fn update_status(&self, action_id: i64, status: &String) -> Result<Action, ActionError> {
-        let result = sqlx::query!(
-            r#"UPDATE actions SET status = $1 WHERE id = $2 RETURNING id, pipeline_id, name, container_uri, type, status"#,
-            status,
+        let next = status
+            .parse::<ActionStatus>()
+            .map_err(|_| ActionError::InvalidStatus(status.clone()))?;
+        let running = next == ActionStatus::Running;
+        let terminal = next.is_terminal();
+
+        let mut tx = self
+            .postgres
+            .get_pool()
+            .begin()
+            .await
+            .map_err(ActionError::DatabaseError)?;
+
+        // The action and its pipeline stay locked until the end so that
+        // concurrent updates, such as a cancellation racing with a result,
+        // are checked in turn and each sees the status of the others
+        let current = sqlx::query_scalar!(
+            r#"SELECT a.status FROM actions a JOIN pipelines p ON p.id = a.pipeline_id WHERE a.id = $1 FOR UPDATE"#,
             action_id
         )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(ActionError::DatabaseError)?;
+        let current = current
+            .parse::<ActionStatus>()
+            .map_err(|_| ActionError::InvalidStatus(current.clone()))?;
+        if !current.can_transition_to(next) {
+            return Err(ActionError::InvalidTransition {
+                from: current,
+                to: next,
+            });
+        }
+
+        // An action starts once, and is finished again after each attempt
+        let row = sqlx::query!(
+            r#"UPDATE actions SET status = $1,
+                started_at = CASE WHEN $3 THEN COALESCE(started_at, now()) ELSE started_at END,
+                finished_at = CASE WHEN $4 THEN COALESCE(finished_at, now()) ELSE NULL END
+            WHERE id = $2 RETURNING id, pipeline_id, name, container_uri, type, status, needs, allow_failure, env AS "env: Json<HashMap<String, String>>", timeout_seconds, retry AS "retry: Json<RetryPolicy>", condition, artifacts, cache AS "cache: Json<Cache>", services AS "services: Json<HashMap<String, Service>>", shell, attempt, reused_from, created_at, started_at, finished_at"#,
+            status,
+            action_id,
+            running,
+            terminal
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(ActionError::DatabaseError)?;
+
+        refresh_pipeline(&mut tx, row.pipeline_id, running).await?;
+
+        tx.commit().await.map_err(ActionError::DatabaseError)?;
+
+        Ok(Action {
+            id: row.id,
+            pipeline_id: row.pipeline_id,
+            name: row.name,
+            r#type: row.r#type.into(),
+            container_uri: row.container_uri,
+            status: row.status.into(),
+            commands: vec![],
+            needs: row.needs,
+            allow_failure: row.allow_failure,
+            env: row.env.0,
+            timeout_seconds: row.timeout_seconds,
+            retry: row.retry.map(|retry| retry.0),
+            condition: row.condition,
+            artifacts: row.artifacts,
+            cache: row.cache.map(|cache| cache.0),
+            services: row.services.0,
+            shell: row.shell,
+            attempt: row.attempt,
+            reused_from: row.reused_from,
+            created_at: row.created_at,
+            started_at: row.started_at,
+            finished_at: row.finished_at,
+            logs: None,
+            attempts: None,
+            steps: None,
+        })
+    }
+
+    async fn append_log(&self, action_id: i64, log: NewLog) -> Result<Log, ActionError> {
+        // The counter of the action gives the line the next sequence number
+        let row = sqlx::query_as!(
+            LogDTO,
+            r#"WITH next AS (UPDATE actions SET log_seq = log_seq + 1 WHERE id = $1 RETURNING log_seq)
+               INSERT INTO logs (action_id, seq, attempt, ts, stream, step_index, data)
+               SELECT $1, next.log_seq, $2, $3, $4, $5, $6 FROM next
+               RETURNING id, action_id, seq, attempt, ts, stream, step_index, data"#,
+            action_id,
+            log.attempt,
+            log.ts,
+            log.stream.as_str(),
+            log.step_index,
+            log.data
+        )
         .fetch_one(&self.postgres.get_pool())
-        .await;
+        .await
+        .map_err(ActionError::DatabaseError)?;
+        Ok(row.into())
+    }
 
-        result
-            .map(|row| Action {
-                id: row.id,
-                pipeline_id: row.pipeline_id,
-                name: row.name,
-                r#type: row.r#type.into(),
-                container_uri: row.container_uri,
-                status: row.status.into(),
-                commands: vec![],
-                logs: None,
-            })
-            .map_err(ActionError::DatabaseError)
+    async fn start_attempt(&self, action_id: i64, attempt: i32) -> Result<(), ActionError> {
+        let mut tx = self
+            .postgres
+            .get_pool()
+            .begin()
+            .await
+            .map_err(ActionError::DatabaseError)?;
+
+        sqlx::query!(
+            r#"INSERT INTO action_attempts (action_id, attempt, status) VALUES ($1, $2, $3)"#,
+            action_id,
+            attempt,
+            ActionStatus::Scheduled.as_proto_name()
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(ActionError::DatabaseError)?;
+
+        sqlx::query!(
+            r#"UPDATE actions SET attempt = $1 WHERE id = $2"#,
+            attempt,
+            action_id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(ActionError::DatabaseError)?;
+
+        tx.commit().await.map_err(ActionError::DatabaseError)
+    }
+
+    async fn retry(&self, action_id: i64, attempt: i32) -> Result<Action, ActionError> {
+        let mut tx = self
+            .postgres
+            .get_pool()
+            .begin()
+            .await
+            .map_err(ActionError::DatabaseError)?;
+
+        // Locked like in update_status, so that a retry and a cancellation
+        // racing with it are checked in turn
+        let current = sqlx::query!(
+            r#"SELECT a.status, a.attempt, a.pipeline_id FROM actions a JOIN pipelines p ON p.id = a.pipeline_id WHERE a.id = $1 FOR UPDATE"#,
+            action_id
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(ActionError::DatabaseError)?;
+        let status = current
+            .status
+            .parse::<ActionStatus>()
+            .map_err(|_| ActionError::InvalidStatus(current.status.clone()))?;
+        if !status.can_retry() {
+            return Err(ActionError::InvalidTransition {
+                from: status,
+                to: ActionStatus::Scheduled,
+            });
+        }
+        if attempt != current.attempt + 1 {
+            return Err(ActionError::InvalidAttempt(attempt));
+        }
+
+        sqlx::query!(
+            r#"INSERT INTO action_attempts (action_id, attempt, status) VALUES ($1, $2, $3)"#,
+            action_id,
+            attempt,
+            ActionStatus::Scheduled.as_proto_name()
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(ActionError::DatabaseError)?;
+
+        sqlx::query!(
+            r#"UPDATE actions SET status = $1, attempt = $2, finished_at = NULL WHERE id = $3"#,
+            ActionStatus::Scheduled.as_proto_name(),
+            attempt,
+            action_id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(ActionError::DatabaseError)?;
+
+        refresh_pipeline(&mut tx, current.pipeline_id, false).await?;
+
+        tx.commit().await.map_err(ActionError::DatabaseError)?;
+        self.find_by_id(action_id).await
     }
 
-    async fn append_log(&self, action_id: i64, log: String) -> Result<(), ActionError> {
+    async fn finish_attempt(
+        &self,
+        action_id: i64,
+        attempt: i32,
+        status: &str,
+        exit_code: Option<i32>,
+    ) -> Result<(), ActionError> {
         sqlx::query!(
-            r#"INSERT INTO logs (action_id, data) VALUES ($1, $2)"#,
+            r#"UPDATE action_attempts SET status = $1, exit_code = $2 WHERE action_id = $3 AND attempt = $4"#,
+            status,
+            exit_code,
             action_id,
-            log
+            attempt
         )
         .execute(&self.postgres.get_pool())
         .await
         .map_err(ActionError::DatabaseError)?;
         Ok(())
     }
+
+    async fn find_attempts(&self, action_id: i64) -> Result<Vec<ActionAttempt>, ActionError> {
+        let rows = sqlx::query!(
+            r#"SELECT attempt, status, exit_code FROM action_attempts WHERE action_id = $1 ORDER BY attempt"#,
+            action_id
+        )
+        .fetch_all(&self.postgres.get_pool())
+        .await
+        .map_err(ActionError::DatabaseError)?;
+
+        rows.into_iter()
+            .map(|row| {
+                let status = row
+                    .status
+                    .parse::<ActionStatus>()
+                    .map_err(|_| ActionError::InvalidStatus(row.status.clone()))?;
+                Ok(ActionAttempt {
+                    attempt: row.attempt,
+                    status,
+                    exit_code: row.exit_code,
+                    logs: None,
+                    steps: None,
+                })
+            })
+            .collect()
+    }
+
+    async fn record_step(
+        &self,
+        action_id: i64,
+        attempt: i32,
+        event: &StepEvent,
+    ) -> Result<(), ActionError> {
+        // A step that finished without a recorded start is still inserted
+        match event {
+            StepEvent::Started {
+                index,
+                name,
+                started_at,
+            } => sqlx::query!(
+                r#"INSERT INTO steps (action_id, attempt, index, name, started_at) VALUES ($1, $2, $3, $4, $5)
+                ON CONFLICT (action_id, attempt, index) DO UPDATE SET name = EXCLUDED.name, started_at = EXCLUDED.started_at, exit_code = NULL, finished_at = NULL"#,
+                action_id,
+                attempt,
+                index,
+                name,
+                started_at
+            )
+            .execute(&self.postgres.get_pool())
+            .await,
+            StepEvent::Finished {
+                index,
+                name,
+                exit_code,
+                started_at,
+                finished_at,
+            } => sqlx::query!(
+                r#"INSERT INTO steps (action_id, attempt, index, name, exit_code, started_at, finished_at) VALUES ($1, $2, $3, $4, $5, $6, $7)
+                ON CONFLICT (action_id, attempt, index) DO UPDATE SET exit_code = EXCLUDED.exit_code, finished_at = EXCLUDED.finished_at"#,
+                action_id,
+                attempt,
+                index,
+                name,
+                exit_code,
+                started_at,
+                finished_at
+            )
+            .execute(&self.postgres.get_pool())
+            .await,
+        }
+        .map_err(ActionError::DatabaseError)?;
+        Ok(())
+    }
+
+    async fn find_steps(&self, action_id: i64) -> Result<Vec<ActionStep>, ActionError> {
+        let rows = sqlx::query!(
+            r#"SELECT attempt, index, name, exit_code, started_at, finished_at FROM steps WHERE action_id = $1 ORDER BY attempt, index"#,
+            action_id
+        )
+        .fetch_all(&self.postgres.get_pool())
+        .await
+        .map_err(ActionError::DatabaseError)?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| ActionStep {
+                attempt: row.attempt,
+                index: row.index,
+                name: row.name,
+                exit_code: row.exit_code,
+                started_at: row.started_at,
+                finished_at: row.finished_at,
+                duration_ms: row
+                    .finished_at
+                    .map(|finished_at| (finished_at - row.started_at).whole_milliseconds() as i64),
+            })
+            .collect())
+    }
 }
@@ -1,12 +1,13 @@
 use crate::{
     domain::command::{
-        entities::command::{Command, CommandError},
+        entities::command::{Command, CommandError, Step},
         ports::command_repository::CommandRepository,
     },
     infrastructure::db::postgres::Postgres,
 };
 use async_trait::async_trait;
-use std::sync::Arc;
+use sqlx::types::Json;
+use std::{collections::HashMap, sync::Arc};
 
 pub struct PostgresCommandRepository {
     pub postgres: Arc<Postgres>,
@@ -23,7 +24,7 @@ impl CommandRepository for PostgresCommandRepository {
     async fn find_by_action_id(&self, action_id: i64) -> Result<Vec<Command>, CommandError> {
         let result = sqlx::query_as!(
             Command,
-            "SELECT id, action_id, command FROM commands WHERE action_id = $1",
+            r#"SELECT id, action_id, command, name, working_directory, env AS "env: Json<HashMap<String, String>>", continue_on_error FROM commands WHERE action_id = $1 ORDER BY id"#,
             action_id
         )
         .fetch_all(&self.postgres.get_pool())
@@ -32,8 +33,8 @@ impl CommandRepository for PostgresCommandRepository {
         result.map_err(CommandError::DatabaseError)
     }
 
-    async fn create(&self, action_id: i64, command: String) -> Result<Command, CommandError> {
-        let result = sqlx::query_as!(Command, "INSERT INTO commands (action_id, command) VALUES ($1, $2) RETURNING id, action_id, command", action_id, command)
+    async fn create(&self, action_id: i64, step: Step) -> Result<Command, CommandError> {
+        let result = sqlx::query_as!(Command, r#"INSERT INTO commands (action_id, command, name, working_directory, env, continue_on_error) VALUES ($1, $2, $3, $4, $5, $6) RETURNING id, action_id, command, name, working_directory, env AS "env: Json<HashMap<String, String>>", continue_on_error"#, action_id, step.run, step.name, step.working_directory, Json(&step.env) as _, step.continue_on_error)
           .fetch_one(&self.postgres.get_pool())
           .await;
 
@@ -3,7 +3,7 @@ use std::sync::Arc;
 
 use crate::{
     domain::log::{
-        entities::log::{Log, LogError},
+        entities::log::{Log, LogDTO, LogError, LogFilter, NewLog},
         ports::log_repository::LogRepository,
     },
     infrastructure::db::postgres::Postgres,
@@ -21,39 +21,68 @@ impl PostgresLogRepository {
 
 #[async_trait]
 impl LogRepository for PostgresLogRepository {
-    async fn create(&self, action_id: i64, data: String) -> Result<Log, LogError> {
+    async fn create(&self, action_id: i64, log: NewLog) -> Result<Log, LogError> {
+        // The counter of the action gives the line the next sequence number
         let row = sqlx::query_as!(
-            Log,
-            r#"INSERT INTO logs (action_id, data) VALUES ($1, $2) RETURNING *"#,
+            LogDTO,
+            r#"WITH next AS (UPDATE actions SET log_seq = log_seq + 1 WHERE id = $1 RETURNING log_seq)
+               INSERT INTO logs (action_id, seq, attempt, ts, stream, step_index, data)
+               SELECT $1, next.log_seq, $2, $3, $4, $5, $6 FROM next
+               RETURNING id, action_id, seq, attempt, ts, stream, step_index, data"#,
             action_id,
-            data,
+            log.attempt,
+            log.ts,
+            log.stream.as_str(),
+            log.step_index,
+            log.data,
         )
         .fetch_one(&self.postgres.get_pool())
         .await
         .map_err(LogError::DatabaseError)?;
 
-        Ok(Log {
-            id: row.id,
-            action_id: row.action_id,
-            data: row.data,
-        })
+        Ok(row.into())
     }
 
-    async fn find_by_action_id(&self, action_id: i64) -> Result<Vec<Log>, LogError> {
-        let rows = sqlx::query_as!(Log, r#"SELECT * FROM logs WHERE action_id = $1"#, action_id)
-            .fetch_all(&self.postgres.get_pool())
-            .await
-            .map_err(LogError::DatabaseError)?;
-
-        let logs = rows
-            .into_iter()
-            .map(|row| Log {
-                id: row.id,
-                action_id: row.action_id,
-                data: row.data,
-            })
-            .collect();
-
-        Ok(logs)
+    async fn find_page(&self, action_id: i64, filter: &LogFilter) -> Result<Vec<Log>, LogError> {
+        let rows = sqlx::query_as!(
+            LogDTO,
+            r#"SELECT id, action_id, seq, attempt, ts, stream, step_index, data
+               FROM logs
+               WHERE action_id = $1 AND seq > $2 AND ($3::TEXT IS NULL OR strpos(data, $3) > 0)
+               ORDER BY seq
+               LIMIT $4"#,
+            action_id,
+            filter.after_seq,
+            filter.grep,
+            filter.limit,
+        )
+        .fetch_all(&self.postgres.get_pool())
+        .await
+        .map_err(LogError::DatabaseError)?;
+
+        Ok(rows.into_iter().map(Log::from).collect())
+    }
+
+    async fn find_tail(&self, action_id: i64, limit: i64) -> Result<Vec<Log>, LogError> {
+        let rows = sqlx::query_as!(
+            LogDTO,
+            r#"SELECT id AS "id!", action_id AS "action_id!", seq AS "seq!", attempt AS "attempt!",
+                      ts AS "ts!", stream AS "stream!", step_index, data AS "data!"
+               FROM (
+                   SELECT id, action_id, seq, attempt, ts, stream, step_index, data
+                   FROM logs
+                   WHERE action_id = $1
+                   ORDER BY seq DESC
+                   LIMIT $2
+               ) AS tail
+               ORDER BY seq"#,
+            action_id,
+            limit,
+        )
+        .fetch_all(&self.postgres.get_pool())
+        .await
+        .map_err(LogError::DatabaseError)?;
+
+        Ok(rows.into_iter().map(Log::from).collect())
     }
 }
@@ -1,7 +1,12 @@
-use crate::domain::pipeline::entities::pipeline::{Pipeline, PipelineError};
+use crate::domain::action::entities::action::NewAction;
+use crate::domain::command::entities::command::Step;
+use crate::domain::pipeline::entities::pipeline::{
+    CheckoutOptions, Pipeline, PipelineDTO, PipelineError, PipelineFilter, TriggerContext,
+};
 use crate::domain::pipeline::ports::pipeline_repository::PipelineRepository;
 use crate::infrastructure::db::postgres::Postgres;
 use async_trait::async_trait;
+use sqlx::{types::Json, QueryBuilder};
 use std::sync::Arc;
 
 pub struct PostgresPipelineRepository {
@@ -20,61 +25,188 @@ impl PipelineRepository for PostgresPipelineRepository {
         &self,
         repository_url: String,
         name: String,
+        trigger: TriggerContext,
+        checkout: CheckoutOptions,
     ) -> Result<Pipeline, PipelineError> {
-        let row = sqlx::query!(
-            "INSERT INTO pipelines (repository_url, name) VALUES ($1, $2)
-             RETURNING id, repository_url, name",
+        let row = sqlx::query_as!(
+            PipelineDTO,
+            r#"INSERT INTO pipelines (repository_url, name, event, branch, tag, message, commit_sha, ref, pr_number, author,
+                                    checkout_depth, checkout_submodules)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+             RETURNING id, repository_url, name, event, branch, tag, message, commit_sha, ref AS git_ref, pr_number, author,
+                       checkout_depth, checkout_submodules, status, rerun_of, created_at, started_at, finished_at"#,
             repository_url,
-            name
+            name,
+            trigger.event,
+            trigger.branch,
+            trigger.tag,
+            trigger.message,
+            trigger.commit_sha,
+            trigger.git_ref,
+            trigger.pr_number,
+            trigger.author,
+            checkout.depth,
+            checkout.submodules
         )
         .fetch_one(&self.postgres.get_pool())
         .await
         .map_err(PipelineError::DatabaseError)?;
 
-        Ok(Pipeline {
-            id: row.id,
-            repository_url: row.repository_url,
-            name: row.name,
-            actions: vec![],
-        })
+        Ok(row.into())
     }
 
-    async fn find_all(&self) -> Result<Vec<Pipeline>, PipelineError> {
-        let rows = sqlx::query!("SELECT id, repository_url, name FROM pipelines")
+    async fn create_rerun(
+        &self,
+        pipeline_id: i64,
+        actions: Vec<(NewAction, Vec<Step>)>,
+    ) -> Result<Pipeline, PipelineError> {
+        // Dropping the transaction on an error rolls the whole run back, so
+        // that no run is left with only part of its actions
+        let mut tx = self
+            .postgres
+            .get_pool()
+            .begin()
+            .await
+            .map_err(PipelineError::DatabaseError)?;
+
+        let result = sqlx::query_as!(
+            PipelineDTO,
+            r#"INSERT INTO pipelines (repository_url, name, event, branch, tag, message, commit_sha, ref, pr_number, author,
+                                    checkout_depth, checkout_submodules, rerun_of)
+             SELECT repository_url, name, event, branch, tag, message, commit_sha, ref, pr_number, author,
+                    checkout_depth, checkout_submodules, id
+             FROM pipelines WHERE id = $1
+             RETURNING id, repository_url, name, event, branch, tag, message, commit_sha, ref AS git_ref, pr_number, author,
+                       checkout_depth, checkout_submodules, status, rerun_of, created_at, started_at, finished_at"#,
+            pipeline_id
+        )
+        .fetch_one(&mut *tx)
+        .await;
+        let pipeline: Pipeline = match result {
+            Ok(row) => row.into(),
+            Err(sqlx::Error::RowNotFound) => return Err(PipelineError::NotFound),
+            Err(err) => return Err(PipelineError::DatabaseError(err)),
+        };
+
+        for (action, steps) in actions {
+            let action_id = sqlx::query_scalar!(
+                r#"INSERT INTO actions (pipeline_id, name, container_uri, type, status, needs, allow_failure, env, timeout_seconds, retry, condition, artifacts, cache, services, shell, reused_from, attempt, started_at, finished_at) SELECT $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, COALESCE(source.attempt, 1), source.started_at, source.finished_at FROM (SELECT 1) AS one LEFT JOIN actions source ON source.id = $16 RETURNING id"#,
+                pipeline.id, action.name, action.container_uri, &action.r#type.to_string(), action.status, &action.needs, action.allow_failure, Json(&action.env) as _, action.timeout_seconds, action.retry.as_ref().map(Json) as _, action.condition, &action.artifacts, action.cache.as_ref().map(Json) as _, Json(&action.services) as _, &action.shell, action.reused_from
+            )
+            .fetch_one(&mut *tx)
+            .await
+            .map_err(PipelineError::DatabaseError)?;
+
+            for step in steps {
+                sqlx::query!(
+                    r#"INSERT INTO commands (action_id, command, name, working_directory, env, continue_on_error) VALUES ($1, $2, $3, $4, $5, $6)"#,
+                    action_id, step.run, step.name, step.working_directory, Json(&step.env) as _, step.continue_on_error
+                )
+                .execute(&mut *tx)
+                .await
+                .map_err(PipelineError::DatabaseError)?;
+            }
+        }
+
+        tx.commit().await.map_err(PipelineError::DatabaseError)?;
+        Ok(pipeline)
+    }
+
+    async fn find_page(
+        &self,
+        filter: &PipelineFilter,
+        cursor: Option<i64>,
+        limit: i64,
+    ) -> Result<Vec<Pipeline>, PipelineError> {
+        // Only the filters that are set end up in the query, so that the
+        // planner can pick the index of each filtered column
+        let mut query = QueryBuilder::<sqlx::Postgres>::new(
+            r#"SELECT p.id, p.repository_url, p.name, p.event, p.branch, p.tag, p.message, p.commit_sha, p.ref AS git_ref,
+                      p.pr_number, p.author, p.checkout_depth, p.checkout_submodules,
+                      p.status, p.rerun_of, p.created_at, p.started_at, p.finished_at
+             FROM pipelines p
+             WHERE TRUE"#,
+        );
+        let text_filters = [
+            ("p.repository_url", &filter.repository_url),
+            ("p.commit_sha", &filter.commit_sha),
+            ("p.ref", &filter.git_ref),
+            ("p.branch", &filter.branch),
+            ("p.tag", &filter.tag),
+            ("p.event", &filter.event),
+            ("p.author", &filter.author),
+        ];
+        for (column, value) in text_filters {
+            if let Some(value) = value {
+                query
+                    .push(format!(" AND {} = ", column))
+                    .push_bind(value);
+            }
+        }
+        if let Some(pr_number) = filter.pr_number {
+            query.push(" AND p.pr_number = ").push_bind(pr_number);
+        }
+        if let Some(since) = filter.since {
+            query.push(" AND p.created_at >= ").push_bind(since);
+        }
+        if let Some(until) = filter.until {
+            query.push(" AND p.created_at < ").push_bind(until);
+        }
+        if let Some(status) = filter.status {
+            query.push(" AND p.status = ").push_bind(status.as_str());
+        }
+        // Pages are keyed on the id, newest first, so that pipelines created
+        // while paginating do not shift the next pages
+        if let Some(cursor) = cursor {
+            query.push(" AND p.id < ").push_bind(cursor);
+        }
+        query.push(" ORDER BY p.id DESC LIMIT ").push_bind(limit);
+
+        let rows = query
+            .build_query_as::<PipelineDTO>()
             .fetch_all(&self.postgres.get_pool())
             .await
             .map_err(PipelineError::DatabaseError)?;
 
-        let pipelines = rows
-            .into_iter()
-            .map(|row| Pipeline {
-                id: row.id,
-                repository_url: row.repository_url,
-                name: row.name,
-                actions: vec![],
-            })
-            .collect();
-
-        Ok(pipelines)
+        Ok(rows.into_iter().map(Pipeline::from).collect())
     }
 
     async fn find_by_id(&self, pipeline_id: i64) -> Result<Pipeline, PipelineError> {
-        let result = sqlx::query!(
-            "SELECT id, repository_url, name FROM pipelines WHERE id = $1",
+        let result = sqlx::query_as!(
+            PipelineDTO,
+            r#"SELECT p.id, p.repository_url, p.name, p.event, p.branch, p.tag, p.message, p.commit_sha, p.ref AS git_ref,
+                      p.pr_number, p.author, p.checkout_depth, p.checkout_submodules,
+                      p.status, p.rerun_of, p.created_at, p.started_at, p.finished_at
+             FROM pipelines p
+             WHERE p.id = $1"#,
             pipeline_id
         )
         .fetch_one(&self.postgres.get_pool())
         .await;
 
         match result {
-            Ok(row) => Ok(Pipeline {
-                id: row.id,
-                repository_url: row.repository_url,
-                name: row.name,
-                actions: vec![],
-            }),
+            Ok(row) => Ok(row.into()),
             Err(sqlx::Error::RowNotFound) => Err(PipelineError::NotFound),
             Err(err) => Err(PipelineError::DatabaseError(err)),
         }
     }
+
+    async fn claim_status_report(
+        &self,
+        pipeline_id: i64,
+    ) -> Result<Option<Pipeline>, PipelineError> {
+        let row = sqlx::query_as!(
+            PipelineDTO,
+            r#"UPDATE pipelines SET reported_status = status
+             WHERE id = $1 AND reported_status IS DISTINCT FROM status
+             RETURNING id, repository_url, name, event, branch, tag, message, commit_sha, ref AS git_ref, pr_number, author,
+                       checkout_depth, checkout_submodules, status, rerun_of, created_at, started_at, finished_at"#,
+            pipeline_id
+        )
+        .fetch_optional(&self.postgres.get_pool())
+        .await
+        .map_err(PipelineError::DatabaseError)?;
+
+        Ok(row.map(Pipeline::from))
+    }
 }
@@ -0,0 +1,205 @@
+use async_trait::async_trait;
+use std::sync::Arc;
+
+use crate::{
+    domain::secrets::{
+        entities::{
+            CredentialsKind, EncryptedCredentials, EncryptedSecret, RepositoryCredentials, Secret,
+            SecretError,
+        },
+        ports::SecretRepository,
+    },
+    infrastructure::db::postgres::Postgres,
+};
+
+pub struct PostgresSecretRepository {
+    pub postgres: Arc<Postgres>,
+}
+
+impl PostgresSecretRepository {
+    pub fn new(postgres: Arc<Postgres>) -> Self {
+        Self { postgres }
+    }
+}
+
+#[async_trait]
+impl SecretRepository for PostgresSecretRepository {
+    async fn upsert(
+        &self,
+        repository_url: String,
+        name: String,
+        nonce: Vec<u8>,
+        ciphertext: Vec<u8>,
+    ) -> Result<Secret, SecretError> {
+        let row = sqlx::query!(
+            "INSERT INTO secrets (repository_url, name, nonce, ciphertext) VALUES ($1, $2, $3, $4)
+             ON CONFLICT (repository_url, name) DO UPDATE SET nonce = EXCLUDED.nonce, ciphertext = EXCLUDED.ciphertext
+             RETURNING id, repository_url, name",
+            repository_url,
+            name,
+            nonce,
+            ciphertext
+        )
+        .fetch_one(&self.postgres.get_pool())
+        .await
+        .map_err(SecretError::DatabaseError)?;
+
+        Ok(Secret {
+            id: row.id,
+            repository_url: row.repository_url,
+            name: row.name,
+        })
+    }
+
+    async fn list(&self, repository_url: String) -> Result<Vec<Secret>, SecretError> {
+        let rows = sqlx::query!(
+            "SELECT id, repository_url, name FROM secrets WHERE repository_url = $1 ORDER BY name",
+            repository_url
+        )
+        .fetch_all(&self.postgres.get_pool())
+        .await
+        .map_err(SecretError::DatabaseError)?;
+
+        let secrets = rows
+            .into_iter()
+            .map(|row| Secret {
+                id: row.id,
+                repository_url: row.repository_url,
+                name: row.name,
+            })
+            .collect();
+
+        Ok(secrets)
+    }
+
+    async fn find_encrypted(
+        &self,
+        repository_url: String,
+    ) -> Result<Vec<EncryptedSecret>, SecretError> {
+        let rows = sqlx::query!(
+            "SELECT name, nonce, ciphertext FROM secrets WHERE repository_url = $1",
+            repository_url
+        )
+        .fetch_all(&self.postgres.get_pool())
+        .await
+        .map_err(SecretError::DatabaseError)?;
+
+        let secrets = rows
+            .into_iter()
+            .map(|row| EncryptedSecret {
+                name: row.name,
+                nonce: row.nonce,
+                ciphertext: row.ciphertext,
+            })
+            .collect();
+
+        Ok(secrets)
+    }
+
+    async fn delete(&self, repository_url: String, name: String) -> Result<(), SecretError> {
+        let result = sqlx::query!(
+            "DELETE FROM secrets WHERE repository_url = $1 AND name = $2",
+            repository_url,
+            name
+        )
+        .execute(&self.postgres.get_pool())
+        .await
+        .map_err(SecretError::DatabaseError)?;
+
+        if result.rows_affected() == 0 {
+            return Err(SecretError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn upsert_credentials(
+        &self,
+        repository_url: String,
+        kind: CredentialsKind,
+        username: Option<String>,
+        nonce: Vec<u8>,
+        ciphertext: Vec<u8>,
+    ) -> Result<RepositoryCredentials, SecretError> {
+        let row = sqlx::query!(
+            "INSERT INTO repository_credentials (repository_url, kind, username, nonce, ciphertext)
+             VALUES ($1, $2, $3, $4, $5)
+             ON CONFLICT (repository_url) DO UPDATE SET kind = EXCLUDED.kind, username = EXCLUDED.username,
+                 nonce = EXCLUDED.nonce, ciphertext = EXCLUDED.ciphertext
+             RETURNING id, repository_url, username",
+            repository_url,
+            kind.as_str(),
+            username,
+            nonce,
+            ciphertext
+        )
+        .fetch_one(&self.postgres.get_pool())
+        .await
+        .map_err(SecretError::DatabaseError)?;
+
+        Ok(RepositoryCredentials {
+            id: row.id,
+            repository_url: row.repository_url,
+            kind,
+            username: row.username,
+        })
+    }
+
+    async fn find_credentials(
+        &self,
+        repository_url: String,
+    ) -> Result<RepositoryCredentials, SecretError> {
+        let row = sqlx::query!(
+            "SELECT id, repository_url, kind, username FROM repository_credentials WHERE repository_url = $1",
+            repository_url
+        )
+        .fetch_optional(&self.postgres.get_pool())
+        .await
+        .map_err(SecretError::DatabaseError)?
+        .ok_or(SecretError::NotFound)?;
+
+        Ok(RepositoryCredentials {
+            id: row.id,
+            repository_url: row.repository_url,
+            kind: row.kind.parse()?,
+            username: row.username,
+        })
+    }
+
+    async fn find_encrypted_credentials(
+        &self,
+        repository_url: String,
+    ) -> Result<Option<EncryptedCredentials>, SecretError> {
+        let row = sqlx::query!(
+            "SELECT kind, username, nonce, ciphertext FROM repository_credentials WHERE repository_url = $1",
+            repository_url
+        )
+        .fetch_optional(&self.postgres.get_pool())
+        .await
+        .map_err(SecretError::DatabaseError)?;
+
+        row.map(|row| {
+            Ok(EncryptedCredentials {
+                kind: row.kind.parse()?,
+                username: row.username,
+                nonce: row.nonce,
+                ciphertext: row.ciphertext,
+            })
+        })
+        .transpose()
+    }
+
+    async fn delete_credentials(&self, repository_url: String) -> Result<(), SecretError> {
+        let result = sqlx::query!(
+            "DELETE FROM repository_credentials WHERE repository_url = $1",
+            repository_url
+        )
+        .execute(&self.postgres.get_pool())
+        .await
+        .map_err(SecretError::DatabaseError)?;
+
+        if result.rows_affected() == 0 {
+            return Err(SecretError::NotFound);
+        }
+        Ok(())
+    }
+}
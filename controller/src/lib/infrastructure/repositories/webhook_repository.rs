@@ -0,0 +1,275 @@
+use async_trait::async_trait;
+use serde_json::Value;
+use std::sync::Arc;
+use time::OffsetDateTime;
+
+use crate::{
+    domain::webhooks::{
+        entities::{
+            DeliveryStatus, EncryptedWebhook, Webhook, WebhookDelivery, WebhookError, WebhookEvent,
+        },
+        ports::WebhookRepository,
+    },
+    infrastructure::db::postgres::Postgres,
+};
+
+pub struct PostgresWebhookRepository {
+    pub postgres: Arc<Postgres>,
+}
+
+impl PostgresWebhookRepository {
+    pub fn new(postgres: Arc<Postgres>) -> Self {
+        Self { postgres }
+    }
+}
+
+struct WebhookRow {
+    id: i64,
+    repository_url: String,
+    url: String,
+    events: Vec<String>,
+    nonce: Vec<u8>,
+    ciphertext: Vec<u8>,
+    created_at: OffsetDateTime,
+}
+
+impl WebhookRow {
+    fn into_encrypted(self) -> Result<EncryptedWebhook, WebhookError> {
+        let events = self
+            .events
+            .iter()
+            .map(|event| event.parse())
+            .collect::<Result<Vec<WebhookEvent>, _>>()?;
+        Ok(EncryptedWebhook {
+            webhook: Webhook {
+                id: self.id,
+                repository_url: self.repository_url,
+                url: self.url,
+                events,
+                created_at: self.created_at,
+            },
+            nonce: self.nonce,
+            ciphertext: self.ciphertext,
+        })
+    }
+}
+
+struct DeliveryRow {
+    id: i64,
+    webhook_id: i64,
+    event: String,
+    payload: Value,
+    status: String,
+    attempts: i32,
+    response_status: Option<i32>,
+    error: Option<String>,
+    created_at: OffsetDateTime,
+    delivered_at: Option<OffsetDateTime>,
+}
+
+impl TryFrom<DeliveryRow> for WebhookDelivery {
+    type Error = WebhookError;
+
+    fn try_from(row: DeliveryRow) -> Result<Self, Self::Error> {
+        Ok(WebhookDelivery {
+            id: row.id,
+            webhook_id: row.webhook_id,
+            event: row.event.parse()?,
+            payload: row.payload,
+            status: row.status.parse().unwrap_or(DeliveryStatus::Pending),
+            attempts: row.attempts,
+            response_status: row.response_status,
+            error: row.error,
+            created_at: row.created_at,
+            delivered_at: row.delivered_at,
+        })
+    }
+}
+
+#[async_trait]
+impl WebhookRepository for PostgresWebhookRepository {
+    async fn create(
+        &self,
+        repository_url: String,
+        url: String,
+        events: Vec<WebhookEvent>,
+        nonce: Vec<u8>,
+        ciphertext: Vec<u8>,
+    ) -> Result<Webhook, WebhookError> {
+        let events: Vec<String> = events.iter().map(|event| event.to_string()).collect();
+        let row = sqlx::query_as!(
+            WebhookRow,
+            "INSERT INTO webhooks (repository_url, url, events, nonce, ciphertext) VALUES ($1, $2, $3, $4, $5)
+             RETURNING id, repository_url, url, events, nonce, ciphertext, created_at",
+            repository_url,
+            url,
+            &events,
+            nonce,
+            ciphertext
+        )
+        .fetch_one(&self.postgres.get_pool())
+        .await
+        .map_err(WebhookError::DatabaseError)?;
+
+        Ok(row.into_encrypted()?.webhook)
+    }
+
+    async fn list(&self, repository_url: String) -> Result<Vec<Webhook>, WebhookError> {
+        let rows = sqlx::query_as!(
+            WebhookRow,
+            "SELECT id, repository_url, url, events, nonce, ciphertext, created_at
+             FROM webhooks WHERE repository_url = $1 ORDER BY id",
+            repository_url
+        )
+        .fetch_all(&self.postgres.get_pool())
+        .await
+        .map_err(WebhookError::DatabaseError)?;
+
+        rows.into_iter()
+            .map(|row| row.into_encrypted().map(|encrypted| encrypted.webhook))
+            .collect()
+    }
+
+    async fn find_encrypted(&self, id: i64) -> Result<EncryptedWebhook, WebhookError> {
+        let row = sqlx::query_as!(
+            WebhookRow,
+            "SELECT id, repository_url, url, events, nonce, ciphertext, created_at
+             FROM webhooks WHERE id = $1",
+            id
+        )
+        .fetch_optional(&self.postgres.get_pool())
+        .await
+        .map_err(WebhookError::DatabaseError)?
+        .ok_or(WebhookError::NotFound)?;
+
+        row.into_encrypted()
+    }
+
+    async fn find_subscribed(
+        &self,
+        repository_url: String,
+        event: WebhookEvent,
+    ) -> Result<Vec<EncryptedWebhook>, WebhookError> {
+        let rows = sqlx::query_as!(
+            WebhookRow,
+            "SELECT id, repository_url, url, events, nonce, ciphertext, created_at
+             FROM webhooks WHERE repository_url = $1 AND $2 = ANY(events) ORDER BY id",
+            repository_url,
+            event.as_str()
+        )
+        .fetch_all(&self.postgres.get_pool())
+        .await
+        .map_err(WebhookError::DatabaseError)?;
+
+        rows.into_iter().map(WebhookRow::into_encrypted).collect()
+    }
+
+    async fn delete(&self, id: i64) -> Result<(), WebhookError> {
+        let result = sqlx::query!("DELETE FROM webhooks WHERE id = $1", id)
+            .execute(&self.postgres.get_pool())
+            .await
+            .map_err(WebhookError::DatabaseError)?;
+
+        if result.rows_affected() == 0 {
+            return Err(WebhookError::NotFound);
+        }
+        Ok(())
+    }
+
+    async fn create_delivery(
+        &self,
+        webhook_id: i64,
+        event: WebhookEvent,
+        payload: Value,
+    ) -> Result<WebhookDelivery, WebhookError> {
+        let row = sqlx::query_as!(
+            DeliveryRow,
+            "INSERT INTO webhook_deliveries (webhook_id, event, payload) VALUES ($1, $2, $3)
+             RETURNING id, webhook_id, event, payload, status, attempts, response_status, error, created_at, delivered_at",
+            webhook_id,
+            event.as_str(),
+            payload
+        )
+        .fetch_one(&self.postgres.get_pool())
+        .await
+        .map_err(WebhookError::DatabaseError)?;
+
+        row.try_into()
+    }
+
+    async fn record_attempt(
+        &self,
+        delivery_id: i64,
+        status: DeliveryStatus,
+        response_status: Option<i32>,
+        error: Option<String>,
+    ) -> Result<(), WebhookError> {
+        sqlx::query!(
+            "UPDATE webhook_deliveries
+             SET status = $2, attempts = attempts + 1, response_status = $3, error = $4,
+                 delivered_at = CASE WHEN $2::VARCHAR = 'succeeded' THEN NOW() ELSE delivered_at END
+             WHERE id = $1",
+            delivery_id,
+            status.as_str(),
+            response_status,
+            error
+        )
+        .execute(&self.postgres.get_pool())
+        .await
+        .map_err(WebhookError::DatabaseError)?;
+
+        Ok(())
+    }
+
+    async fn list_deliveries(
+        &self,
+        webhook_id: i64,
+        limit: i64,
+    ) -> Result<Vec<WebhookDelivery>, WebhookError> {
+        let rows = sqlx::query_as!(
+            DeliveryRow,
+            "SELECT id, webhook_id, event, payload, status, attempts, response_status, error, created_at, delivered_at
+             FROM webhook_deliveries WHERE webhook_id = $1 ORDER BY id DESC LIMIT $2",
+            webhook_id,
+            limit
+        )
+        .fetch_all(&self.postgres.get_pool())
+        .await
+        .map_err(WebhookError::DatabaseError)?;
+
+        rows.into_iter().map(WebhookDelivery::try_from).collect()
+    }
+
+    async fn find_delivery(
+        &self,
+        webhook_id: i64,
+        delivery_id: i64,
+    ) -> Result<WebhookDelivery, WebhookError> {
+        let row = sqlx::query_as!(
+            DeliveryRow,
+            "SELECT id, webhook_id, event, payload, status, attempts, response_status, error, created_at, delivered_at
+             FROM webhook_deliveries WHERE webhook_id = $1 AND id = $2",
+            webhook_id,
+            delivery_id
+        )
+        .fetch_optional(&self.postgres.get_pool())
+        .await
+        .map_err(WebhookError::DatabaseError)?
+        .ok_or(WebhookError::NotFound)?;
+
+        row.try_into()
+    }
+
+    async fn list_pending(&self) -> Result<Vec<WebhookDelivery>, WebhookError> {
+        let rows = sqlx::query_as!(
+            DeliveryRow,
+            "SELECT id, webhook_id, event, payload, status, attempts, response_status, error, created_at, delivered_at
+             FROM webhook_deliveries WHERE status = 'pending' ORDER BY id"
+        )
+        .fetch_all(&self.postgres.get_pool())
+        .await
+        .map_err(WebhookError::DatabaseError)?;
+
+        rows.into_iter().map(WebhookDelivery::try_from).collect()
+    }
+}
@@ -0,0 +1,96 @@
+use async_trait::async_trait;
+use std::path::PathBuf;
+use tokio::{
+    fs::{self, OpenOptions},
+    io::{AsyncReadExt, AsyncWriteExt},
+};
+
+use crate::domain::artifacts::{
+    entities::{ArtifactError, ArtifactStream},
+    ports::ArtifactStorage,
+};
+
+const READ_CHUNK_SIZE: usize = 64 * 1024;
+
+/// Stores the archives on the local filesystem, as `<root>/<action_id>/<attempt>.tar`.
+/// An archive is written to `<attempt>.tar.part` while it is received, and
+/// renamed once committed.
+pub struct LocalArtifactStorage {
+    root: PathBuf,
+}
+
+impl LocalArtifactStorage {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    fn archive_path(&self, action_id: i64, attempt: i32) -> PathBuf {
+        self.root
+            .join(action_id.to_string())
+            .join(format!("{}.tar", attempt))
+    }
+
+    fn partial_path(&self, action_id: i64, attempt: i32) -> PathBuf {
+        self.root
+            .join(action_id.to_string())
+            .join(format!("{}.tar.part", attempt))
+    }
+}
+
+#[async_trait]
+impl ArtifactStorage for LocalArtifactStorage {
+    async fn append(
+        &self,
+        action_id: i64,
+        attempt: i32,
+        chunk: &[u8],
+    ) -> Result<(), ArtifactError> {
+        let path = self.partial_path(action_id, attempt);
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent).await?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)
+            .await?;
+        file.write_all(chunk).await?;
+        Ok(())
+    }
+
+    async fn commit(&self, action_id: i64, attempt: i32) -> Result<(), ArtifactError> {
+        fs::rename(
+            self.partial_path(action_id, attempt),
+            self.archive_path(action_id, attempt),
+        )
+        .await?;
+        Ok(())
+    }
+
+    async fn discard(&self, action_id: i64, attempt: i32) -> Result<(), ArtifactError> {
+        match fs::remove_file(self.partial_path(action_id, attempt)).await {
+            Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+            _ => Ok(()),
+        }
+    }
+
+    async fn open(&self, action_id: i64, attempt: i32) -> Result<ArtifactStream, ArtifactError> {
+        let mut file = match fs::File::open(self.archive_path(action_id, attempt)).await {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(ArtifactError::NotFound)
+            }
+            Err(e) => return Err(e.into()),
+        };
+        Ok(Box::pin(async_stream::try_stream! {
+            let mut buffer = vec![0; READ_CHUNK_SIZE];
+            loop {
+                let read = file.read(&mut buffer).await?;
+                if read == 0 {
+                    break;
+                }
+                yield buffer[..read].to_vec();
+            }
+        }))
+    }
+}
@@ -0,0 +1,68 @@
+use async_trait::async_trait;
+use reqwest::{redirect, Client};
+use std::time::Duration;
+
+use crate::domain::webhooks::{
+    entities::{
+        sign, WebhookDelivery, WebhookError, DELIVERY_HEADER, EVENT_HEADER, SIGNATURE_HEADER,
+    },
+    ports::WebhookSender,
+};
+
+const USER_AGENT: &str = "sealci";
+
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Posts the deliveries as JSON, signed in the `X-Sealci-Signature-256`
+/// header. Redirects are not followed, they would turn the signed POST into a
+/// GET to another URL.
+pub struct HttpWebhookSender {
+    client: Client,
+}
+
+impl HttpWebhookSender {
+    pub fn new() -> Self {
+        Self {
+            client: Client::builder()
+                .redirect(redirect::Policy::none())
+                .build()
+                .expect("Failed to build the webhook HTTP client"),
+        }
+    }
+}
+
+impl Default for HttpWebhookSender {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[async_trait]
+impl WebhookSender for HttpWebhookSender {
+    async fn send(
+        &self,
+        url: &str,
+        delivery: &WebhookDelivery,
+        secret: &str,
+    ) -> Result<u16, WebhookError> {
+        // The signature covers the exact bytes that are sent
+        let body = serde_json::to_vec(&delivery.payload)
+            .map_err(|e| WebhookError::RequestError(e.to_string()))?;
+
+        let response = self
+            .client
+            .post(url)
+            .header(reqwest::header::CONTENT_TYPE, "application/json")
+            .header(reqwest::header::USER_AGENT, USER_AGENT)
+            .header(EVENT_HEADER, delivery.event.as_str())
+            .header(DELIVERY_HEADER, delivery.id.to_string())
+            .header(SIGNATURE_HEADER, sign(secret, &body))
+            .timeout(REQUEST_TIMEOUT)
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| WebhookError::RequestError(e.to_string()))?;
+
+        Ok(response.status().as_u16())
+    }
+}
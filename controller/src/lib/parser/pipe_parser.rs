@@ -1,22 +1,57 @@
 use core::fmt;
+use std::collections::{HashMap, HashSet};
 use std::str::FromStr;
 
 use serde::{Deserialize, Serialize};
 use yaml_rust::yaml::Yaml;
 use yaml_rust::YamlLoader;
 
+use crate::domain::action::entities::action::{Cache, RetryCondition, RetryPolicy, Service};
+use crate::domain::command::entities::command::Step;
+use crate::domain::pipeline::entities::{
+    condition::Condition,
+    expression::expressions,
+    pipeline::{Artifacts, CheckoutOptions, Matrix, MatrixCombination, MATRIX_CONTEXT},
+};
+use crate::domain::secrets::entities::{is_valid_secret_name, SECRETS_CONTEXT};
+
+/// Upper bound on the number of actions a single matrix expands to.
+const MAX_MATRIX_COMBINATIONS: usize = 256;
+
+/// Upper bound on the cross product of the axes of a matrix, checked before
+/// it is expanded since `exclude` may only remove part of it.
+const MAX_MATRIX_CROSS_PRODUCT: usize = 4096;
+
+/// Upper bound on the number of attempts of a retried action.
+const MAX_RETRY_ATTEMPTS: i32 = 10;
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ManifestPipeline {
     pub name: String,
+    pub env: HashMap<String, String>,
+    pub timeout: Option<u64>,
+    pub shell: Option<Vec<String>>,
+    pub checkout: CheckoutOptions,
     pub actions: Vec<ManifestAction>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct ManifestAction {
     pub name: String,
-    pub commands: Vec<String>,
+    pub commands: Vec<Step>,
     pub configuration_type: Type,
     pub configuration_version: String,
+    pub needs: Vec<String>,
+    pub allow_failure: bool,
+    pub env: HashMap<String, String>,
+    pub matrix: Option<Matrix>,
+    pub timeout: Option<u64>,
+    pub retry: Option<RetryPolicy>,
+    pub condition: Option<String>,
+    pub artifacts: Option<Artifacts>,
+    pub cache: Option<Cache>,
+    pub services: HashMap<String, Service>,
+    pub shell: Option<Vec<String>>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
@@ -68,6 +103,23 @@ pub enum ParsingError {
     MissingConfiguration,
     MissingCommands,
     MissingStepName,
+    InvalidEnvironment,
+    InvalidSecretReference,
+    InvalidMatrix,
+    InvalidMatrixReference,
+    InvalidTimeout,
+    InvalidRetry,
+    InvalidCondition,
+    InvalidArtifacts,
+    InvalidCache,
+    InvalidServices,
+    InvalidShell,
+    InvalidStep,
+    InvalidNeeds,
+    InvalidAllowFailure,
+    InvalidCheckout,
+    UnknownDependency,
+    DependencyCycle,
 }
 
 #[derive(Clone)]
@@ -78,9 +130,25 @@ impl ManifestParser for PipeParser {
         check_command_indentation(&yaml)?;
         let doc = parse_yaml(&yaml)?;
         let name = parse_pipeline_name(&doc)?;
+        let env = parse_env(&doc)?;
+        check_env_expressions(&env, &[])?;
+        let timeout = parse_timeout(&doc)?;
+        let shell = parse_shell(&doc)?;
+        for value in shell.iter().flatten() {
+            check_matrix_expressions(value, &[])?;
+        }
+        let checkout = parse_checkout(&doc)?;
         let actions = parse_actions(&doc)?;
+        check_dependencies(&actions)?;
 
-        Ok(ManifestPipeline { name, actions })
+        Ok(ManifestPipeline {
+            name,
+            env,
+            timeout,
+            shell,
+            checkout,
+            actions,
+        })
     }
 }
 
@@ -110,12 +178,65 @@ fn parse_action(name: &Yaml, action: &Yaml) -> Result<ManifestAction, ParsingErr
     let name = parse_action_name(name)?;
     let configuration = parse_configuration(action)?;
     let commands = parse_commands(action)?;
+    let needs = parse_needs(action)?;
+    let allow_failure = parse_allow_failure(action)?;
+    let env = parse_env(action)?;
+    let matrix = parse_matrix(action)?;
+    let timeout = parse_timeout(action)?;
+    let retry = parse_retry(action)?;
+    let condition = parse_condition(action)?;
+    let artifacts = parse_artifacts(action)?;
+    let cache = parse_cache(action)?;
+    let services = parse_services(action)?;
+    let shell = parse_shell(action)?;
+
+    let matrix_keys = matrix.as_ref().map(Matrix::keys).unwrap_or_default();
+    check_env_expressions(&env, &matrix_keys)?;
+    for service in services.values() {
+        check_env_expressions(&service.env, &matrix_keys)?;
+    }
+    for step in &commands {
+        check_env_expressions(&step.env, &matrix_keys)?;
+    }
+    let step_values = commands.iter().flat_map(|step| {
+        std::iter::once(&step.run)
+            .chain(&step.name)
+            .chain(&step.working_directory)
+    });
+    let service_values = services
+        .values()
+        .flat_map(|service| std::iter::once(&service.image).chain(&service.healthcheck));
+    let artifact_paths = artifacts.iter().flat_map(|artifacts| &artifacts.paths);
+    let cache_paths = cache.iter().flat_map(|cache| &cache.paths);
+    for value in step_values
+        .chain(std::iter::once(&configuration))
+        .chain(artifact_paths)
+        .chain(cache_paths)
+        .chain(service_values)
+        .chain(shell.iter().flatten())
+    {
+        check_matrix_expressions(value, &matrix_keys)?;
+    }
+    if let Some(cache) = &cache {
+        check_cache_key_expressions(&cache.key, &matrix_keys)?;
+    }
 
     Ok(ManifestAction {
         name,
         commands,
         configuration_type: Type::Container,
         configuration_version: configuration,
+        needs,
+        allow_failure,
+        env,
+        matrix,
+        timeout,
+        retry,
+        condition,
+        artifacts,
+        cache,
+        services,
+        shell,
     })
 }
 
@@ -144,40 +265,622 @@ fn parse_configuration(action: &Yaml) -> Result<String, ParsingError> {
         .map(String::from)
 }
 
-fn parse_commands(action: &Yaml) -> Result<Vec<String>, ParsingError> {
+fn parse_commands(action: &Yaml) -> Result<Vec<Step>, ParsingError> {
     let commands = action["commands"]
         .as_vec()
         .ok_or(ParsingError::MissingCommands)?;
     if commands.is_empty() {
         return Err(ParsingError::MissingCommands);
     }
-    commands
+    commands.iter().map(parse_step).collect()
+}
+
+/// Parses an entry of the `commands` of an action: either the command itself
+/// or an object such as
+/// `{ name: lint, run: cargo clippy, working_directory: core, continue_on_error: true }`.
+fn parse_step(step: &Yaml) -> Result<Step, ParsingError> {
+    let fields = match step {
+        Yaml::String(run) => return Ok(Step::new(run.clone())),
+        Yaml::Hash(fields) => fields,
+        _ => return Err(ParsingError::YamlNotCompliant),
+    };
+    if !fields.keys().all(|k| {
+        matches!(
+            k.as_str(),
+            Some("name" | "run" | "working_directory" | "env" | "continue_on_error")
+        )
+    }) {
+        return Err(ParsingError::InvalidStep);
+    }
+    let optional_string = |key: &str| match &step[key] {
+        Yaml::BadValue => Ok(None),
+        Yaml::String(value) if !value.trim().is_empty() => Ok(Some(value.clone())),
+        _ => Err(ParsingError::InvalidStep),
+    };
+    let run = optional_string("run")?.ok_or(ParsingError::InvalidStep)?;
+    let continue_on_error = match &step["continue_on_error"] {
+        Yaml::BadValue => false,
+        Yaml::Boolean(continue_on_error) => *continue_on_error,
+        _ => return Err(ParsingError::InvalidStep),
+    };
+    Ok(Step {
+        name: optional_string("name")?,
+        run,
+        working_directory: optional_string("working_directory")?,
+        env: parse_env(step)?,
+        continue_on_error,
+    })
+}
+
+/// Parses the `shell` of a pipeline or an action, the program each command
+/// is passed to as its last argument: either a command line such as
+/// `bash -eo pipefail -c` or the list of its arguments.
+fn parse_shell(node: &Yaml) -> Result<Option<Vec<String>>, ParsingError> {
+    let shell = match &node["shell"] {
+        Yaml::BadValue => return Ok(None),
+        Yaml::String(shell) => shell.split_whitespace().map(String::from).collect(),
+        Yaml::Array(shell) => shell
+            .iter()
+            .map(|argument| {
+                argument
+                    .as_str()
+                    .map(String::from)
+                    .ok_or(ParsingError::InvalidShell)
+            })
+            .collect::<Result<Vec<String>, ParsingError>>()?,
+        _ => return Err(ParsingError::InvalidShell),
+    };
+    match shell.first() {
+        Some(program) if !program.trim().is_empty() => Ok(Some(shell)),
+        _ => Err(ParsingError::InvalidShell),
+    }
+}
+
+/// Parses how the agents check the repository out: `depth` is the number of
+/// commits fetched, the whole history when 0, and `submodules` whether the
+/// submodules are checked out too.
+fn parse_checkout(doc: &Yaml) -> Result<CheckoutOptions, ParsingError> {
+    let checkout = match &doc["checkout"] {
+        Yaml::BadValue => return Ok(CheckoutOptions::default()),
+        Yaml::Hash(checkout) => checkout,
+        _ => return Err(ParsingError::InvalidCheckout),
+    };
+    if !checkout
+        .keys()
+        .all(|k| matches!(k.as_str(), Some("depth") | Some("submodules")))
+    {
+        return Err(ParsingError::InvalidCheckout);
+    }
+    let mut options = CheckoutOptions::default();
+    match checkout.get(&Yaml::String("depth".to_string())) {
+        None => {}
+        Some(Yaml::Integer(depth)) => {
+            options.depth = i32::try_from(*depth)
+                .ok()
+                .filter(|depth| *depth >= 0)
+                .ok_or(ParsingError::InvalidCheckout)?
+        }
+        Some(_) => return Err(ParsingError::InvalidCheckout),
+    }
+    match checkout.get(&Yaml::String("submodules".to_string())) {
+        None => {}
+        Some(Yaml::Boolean(submodules)) => options.submodules = *submodules,
+        Some(_) => return Err(ParsingError::InvalidCheckout),
+    }
+    Ok(options)
+}
+
+fn parse_needs(action: &Yaml) -> Result<Vec<String>, ParsingError> {
+    let needs = match &action["needs"] {
+        Yaml::BadValue => return Ok(Vec::new()),
+        Yaml::String(name) => return Ok(vec![name.clone()]),
+        Yaml::Array(needs) => needs,
+        _ => return Err(ParsingError::InvalidNeeds),
+    };
+    needs
+        .iter()
+        .map(|need| {
+            need.as_str()
+                .ok_or(ParsingError::InvalidNeeds)
+                .map(String::from)
+        })
+        .collect()
+}
+
+/// Parses the `env` map of a pipeline or an action. Scalar values are
+/// converted to their string representation.
+fn parse_env(node: &Yaml) -> Result<HashMap<String, String>, ParsingError> {
+    let env = match &node["env"] {
+        Yaml::BadValue => return Ok(HashMap::new()),
+        Yaml::Hash(env) => env,
+        _ => return Err(ParsingError::InvalidEnvironment),
+    };
+    env.iter()
+        .map(|(key, value)| {
+            let key = key
+                .as_str()
+                .filter(|k| is_valid_env_name(k))
+                .ok_or(ParsingError::InvalidEnvironment)?;
+            let value = parse_scalar(value).ok_or(ParsingError::InvalidEnvironment)?;
+            Ok((key.to_string(), value))
+        })
+        .collect()
+}
+
+/// Converts a scalar to its string representation.
+fn parse_scalar(value: &Yaml) -> Option<String> {
+    match value {
+        Yaml::String(v) => Some(v.clone()),
+        Yaml::Integer(v) => Some(v.to_string()),
+        Yaml::Real(v) => Some(v.clone()),
+        Yaml::Boolean(v) => Some(v.to_string()),
+        _ => None,
+    }
+}
+
+/// Parses the `matrix` of an action: axes mapping a name to a list of
+/// values, plus optional `include` and `exclude` lists of combinations.
+fn parse_matrix(action: &Yaml) -> Result<Option<Matrix>, ParsingError> {
+    let matrix_yaml = match &action["matrix"] {
+        Yaml::BadValue => return Ok(None),
+        Yaml::Hash(matrix) => matrix,
+        _ => return Err(ParsingError::InvalidMatrix),
+    };
+    let mut matrix = Matrix::default();
+    for (key, value) in matrix_yaml {
+        match key.as_str() {
+            Some("include") => matrix.include = parse_matrix_combinations(value)?,
+            Some("exclude") => matrix.exclude = parse_matrix_combinations(value)?,
+            Some(axis) if is_valid_env_name(axis) => {
+                let values = value
+                    .as_vec()
+                    .filter(|values| !values.is_empty())
+                    .ok_or(ParsingError::InvalidMatrix)?
+                    .iter()
+                    .map(|value| parse_scalar(value).ok_or(ParsingError::InvalidMatrix))
+                    .collect::<Result<Vec<String>, ParsingError>>()?;
+                matrix.axes.push((axis.to_string(), values));
+            }
+            _ => return Err(ParsingError::InvalidMatrix),
+        }
+    }
+
+    // Excluding on anything else than an axis would silently exclude nothing
+    if matrix
+        .exclude
+        .iter()
+        .flatten()
+        .any(|(key, _)| !matrix.axes.iter().any(|(axis, _)| axis == key))
+    {
+        return Err(ParsingError::InvalidMatrix);
+    }
+    let cross_product = matrix.axes.iter().try_fold(1usize, |product, (_, values)| {
+        product.checked_mul(values.len())
+    });
+    if cross_product.is_none_or(|product| product > MAX_MATRIX_CROSS_PRODUCT) {
+        return Err(ParsingError::InvalidMatrix);
+    }
+    let combinations = matrix.combinations().len();
+    if combinations == 0 || combinations > MAX_MATRIX_COMBINATIONS {
+        return Err(ParsingError::InvalidMatrix);
+    }
+    Ok(Some(matrix))
+}
+
+fn parse_matrix_combinations(node: &Yaml) -> Result<Vec<MatrixCombination>, ParsingError> {
+    node.as_vec()
+        .ok_or(ParsingError::InvalidMatrix)?
+        .iter()
+        .map(|combination| {
+            let combination = combination
+                .as_hash()
+                .filter(|combination| !combination.is_empty())
+                .ok_or(ParsingError::InvalidMatrix)?;
+            combination
+                .iter()
+                .map(|(key, value)| {
+                    let key = key
+                        .as_str()
+                        .filter(|k| is_valid_env_name(k))
+                        .ok_or(ParsingError::InvalidMatrix)?;
+                    let value = parse_scalar(value).ok_or(ParsingError::InvalidMatrix)?;
+                    Ok((key.to_string(), value))
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Env values may reference the repository secrets and, for matrix actions,
+/// the values of the matrix.
+fn check_env_expressions(
+    env: &HashMap<String, String>,
+    matrix_keys: &[&str],
+) -> Result<(), ParsingError> {
+    for value in env.values() {
+        let found = expressions(value).ok_or(ParsingError::InvalidSecretReference)?;
+        for expression in found {
+            if let Some(key) = expression.strip_prefix(MATRIX_CONTEXT) {
+                if !matrix_keys.contains(&key) {
+                    return Err(ParsingError::InvalidMatrixReference);
+                }
+            } else if !expression
+                .strip_prefix(SECRETS_CONTEXT)
+                .is_some_and(is_valid_secret_name)
+            {
+                return Err(ParsingError::InvalidSecretReference);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Commands and the container image may only reference the matrix values.
+fn check_matrix_expressions(value: &str, matrix_keys: &[&str]) -> Result<(), ParsingError> {
+    let found = expressions(value).ok_or(ParsingError::InvalidMatrixReference)?;
+    if found.iter().any(|expression| {
+        !expression
+            .strip_prefix(MATRIX_CONTEXT)
+            .is_some_and(|key| matrix_keys.contains(&key))
+    }) {
+        return Err(ParsingError::InvalidMatrixReference);
+    }
+    Ok(())
+}
+
+/// Parses the `timeout` of a pipeline or an action into seconds: either a
+/// number of seconds or a duration such as `1h30m`, `15m` or `90s`.
+fn parse_timeout(node: &Yaml) -> Result<Option<u64>, ParsingError> {
+    let seconds = match &node["timeout"] {
+        Yaml::BadValue => return Ok(None),
+        Yaml::Integer(seconds) => u64::try_from(*seconds).ok(),
+        Yaml::String(duration) => parse_duration(duration),
+        _ => None,
+    };
+    match seconds {
+        Some(seconds) if seconds > 0 => Ok(Some(seconds)),
+        _ => Err(ParsingError::InvalidTimeout),
+    }
+}
+
+fn parse_duration(duration: &str) -> Option<u64> {
+    let duration = duration.trim();
+    // A bare number is a number of seconds
+    if let Ok(seconds) = duration.parse() {
+        return Some(seconds);
+    }
+    let mut seconds: u64 = 0;
+    let mut number = String::new();
+    for c in duration.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit = match c {
+            'h' => 3600,
+            'm' => 60,
+            's' => 1,
+            _ => return None,
+        };
+        let value: u64 = number.parse().ok()?;
+        seconds = seconds.checked_add(value.checked_mul(unit)?)?;
+        number.clear();
+    }
+    number.is_empty().then_some(seconds)
+}
+
+/// Parses the `retry` policy of an action, such as
+/// `{ max_attempts: 3, on: [agent_lost, exit_code: [137]] }`.
+fn parse_retry(action: &Yaml) -> Result<Option<RetryPolicy>, ParsingError> {
+    let retry = &action["retry"];
+    match retry {
+        Yaml::BadValue => return Ok(None),
+        Yaml::Hash(_) => {}
+        _ => return Err(ParsingError::InvalidRetry),
+    }
+    let max_attempts = retry["max_attempts"]
+        .as_i64()
+        .and_then(|max_attempts| i32::try_from(max_attempts).ok())
+        .filter(|max_attempts| (1..=MAX_RETRY_ATTEMPTS).contains(max_attempts))
+        .ok_or(ParsingError::InvalidRetry)?;
+    let on = retry["on"]
+        .as_vec()
+        .filter(|on| !on.is_empty())
+        .ok_or(ParsingError::InvalidRetry)?
         .iter()
-        .map(|cmd| {
-            cmd.as_str()
-                .ok_or(ParsingError::YamlNotCompliant)
+        .map(parse_retry_condition)
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(Some(RetryPolicy { max_attempts, on }))
+}
+
+fn parse_retry_condition(condition: &Yaml) -> Result<RetryCondition, ParsingError> {
+    match condition {
+        Yaml::String(condition) if condition == "agent_lost" => Ok(RetryCondition::AgentLost),
+        Yaml::Hash(hash) if hash.len() == 1 => {
+            let codes = match &condition["exit_code"] {
+                Yaml::Integer(code) => vec![*code],
+                Yaml::Array(codes) if !codes.is_empty() => codes
+                    .iter()
+                    .map(|code| code.as_i64().ok_or(ParsingError::InvalidRetry))
+                    .collect::<Result<Vec<_>, _>>()?,
+                _ => return Err(ParsingError::InvalidRetry),
+            };
+            codes
+                .into_iter()
+                .map(|code| i32::try_from(code).map_err(|_| ParsingError::InvalidRetry))
+                .collect::<Result<Vec<_>, _>>()
+                .map(RetryCondition::ExitCode)
+        }
+        _ => Err(ParsingError::InvalidRetry),
+    }
+}
+
+/// Parses the `if` condition of an action, which is only evaluated when the
+/// action is about to be dispatched but must be valid from the start.
+fn parse_condition(action: &Yaml) -> Result<Option<String>, ParsingError> {
+    match &action["if"] {
+        Yaml::BadValue => Ok(None),
+        Yaml::String(condition) => Condition::parse(condition)
+            .map(|_| Some(condition.clone()))
+            .map_err(|_| ParsingError::InvalidCondition),
+        _ => Err(ParsingError::InvalidCondition),
+    }
+}
+
+/// Parses the `artifacts` of an action, the paths archived once its commands
+/// have run, such as `artifacts: { paths: [target/release/app] }`.
+fn parse_artifacts(action: &Yaml) -> Result<Option<Artifacts>, ParsingError> {
+    let artifacts = match &action["artifacts"] {
+        Yaml::BadValue => return Ok(None),
+        Yaml::Hash(artifacts) => artifacts,
+        _ => return Err(ParsingError::InvalidArtifacts),
+    };
+    if !artifacts.keys().all(|k| k.as_str() == Some("paths")) {
+        return Err(ParsingError::InvalidArtifacts);
+    }
+    let paths = artifacts
+        .get(&Yaml::String("paths".to_string()))
+        .and_then(Yaml::as_vec)
+        .filter(|paths| !paths.is_empty())
+        .ok_or(ParsingError::InvalidArtifacts)?
+        .iter()
+        .map(|path| {
+            path.as_str()
+                .filter(|path| !path.trim().is_empty())
+                .map(String::from)
+                .ok_or(ParsingError::InvalidArtifacts)
+        })
+        .collect::<Result<Vec<String>, ParsingError>>()?;
+    Ok(Some(Artifacts { paths }))
+}
+
+/// Parses the `cache` of an action, the paths an agent keeps between runs
+/// under a key, such as
+/// `cache: { key: "cargo-${{ hashFiles('Cargo.lock') }}", paths: [target] }`.
+fn parse_cache(action: &Yaml) -> Result<Option<Cache>, ParsingError> {
+    let cache = match &action["cache"] {
+        Yaml::BadValue => return Ok(None),
+        Yaml::Hash(cache) => cache,
+        _ => return Err(ParsingError::InvalidCache),
+    };
+    if !cache
+        .keys()
+        .all(|k| matches!(k.as_str(), Some("key") | Some("paths")))
+    {
+        return Err(ParsingError::InvalidCache);
+    }
+    let key = cache
+        .get(&Yaml::String("key".to_string()))
+        .and_then(Yaml::as_str)
+        .filter(|key| !key.trim().is_empty())
+        .ok_or(ParsingError::InvalidCache)?
+        .to_string();
+    let paths = cache
+        .get(&Yaml::String("paths".to_string()))
+        .and_then(Yaml::as_vec)
+        .filter(|paths| !paths.is_empty())
+        .ok_or(ParsingError::InvalidCache)?
+        .iter()
+        .map(|path| {
+            path.as_str()
+                .filter(|path| !path.trim().is_empty())
                 .map(String::from)
+                .ok_or(ParsingError::InvalidCache)
+        })
+        .collect::<Result<Vec<String>, ParsingError>>()?;
+    Ok(Some(Cache { key, paths }))
+}
+
+/// Cache keys may reference the matrix values and hash files of the
+/// repository with `hashFiles('<pattern>', ...)`.
+fn check_cache_key_expressions(key: &str, matrix_keys: &[&str]) -> Result<(), ParsingError> {
+    let found = expressions(key).ok_or(ParsingError::InvalidCache)?;
+    for expression in found {
+        if let Some(key) = expression.strip_prefix(MATRIX_CONTEXT) {
+            if !matrix_keys.contains(&key) {
+                return Err(ParsingError::InvalidMatrixReference);
+            }
+        } else if !is_hash_files_expression(expression) {
+            return Err(ParsingError::InvalidCache);
+        }
+    }
+    Ok(())
+}
+
+/// Whether `expression` is a `hashFiles` call on one or more quoted patterns.
+fn is_hash_files_expression(expression: &str) -> bool {
+    let Some(arguments) = expression
+        .strip_prefix("hashFiles(")
+        .and_then(|e| e.strip_suffix(')'))
+    else {
+        return false;
+    };
+    arguments.split(',').all(|argument| {
+        let argument = argument.trim();
+        argument.len() > 2
+            && ((argument.starts_with('\'') && argument.ends_with('\''))
+                || (argument.starts_with('"') && argument.ends_with('"')))
+    })
+}
+
+/// Parses the `services` of an action, the containers started next to it
+/// and reached under their name, such as
+/// `services: { db: { image: postgres:16, env: { POSTGRES_PASSWORD: ci } } }`.
+fn parse_services(action: &Yaml) -> Result<HashMap<String, Service>, ParsingError> {
+    let services = match &action["services"] {
+        Yaml::BadValue => return Ok(HashMap::new()),
+        Yaml::Hash(services) => services,
+        _ => return Err(ParsingError::InvalidServices),
+    };
+    services
+        .iter()
+        .map(|(name, service)| {
+            let name = name
+                .as_str()
+                .filter(|name| is_valid_service_name(name))
+                .ok_or(ParsingError::InvalidServices)?;
+            let fields = service.as_hash().ok_or(ParsingError::InvalidServices)?;
+            if !fields.keys().all(|k| {
+                matches!(
+                    k.as_str(),
+                    Some("image") | Some("env") | Some("healthcheck")
+                )
+            }) {
+                return Err(ParsingError::InvalidServices);
+            }
+            let image = service["image"]
+                .as_str()
+                .filter(|image| !image.trim().is_empty())
+                .ok_or(ParsingError::InvalidServices)?
+                .to_string();
+            let healthcheck = match &service["healthcheck"] {
+                Yaml::BadValue => None,
+                Yaml::String(healthcheck) if !healthcheck.trim().is_empty() => {
+                    Some(healthcheck.clone())
+                }
+                _ => return Err(ParsingError::InvalidServices),
+            };
+            let service = Service {
+                image,
+                env: parse_env(service)?,
+                healthcheck,
+            };
+            Ok((name.to_string(), service))
         })
         .collect()
 }
 
+fn parse_allow_failure(action: &Yaml) -> Result<bool, ParsingError> {
+    match &action["allow_failure"] {
+        Yaml::BadValue => Ok(false),
+        Yaml::Boolean(allow_failure) => Ok(*allow_failure),
+        _ => Err(ParsingError::InvalidAllowFailure),
+    }
+}
+
+/// Checks that every `needs` entry references an action of the pipeline and
+/// that the resulting dependency graph has no cycle.
+fn check_dependencies(actions: &[ManifestAction]) -> Result<(), ParsingError> {
+    let names: HashSet<&str> = actions.iter().map(|a| a.name.as_str()).collect();
+    if actions
+        .iter()
+        .flat_map(|a| a.needs.iter())
+        .any(|need| !names.contains(need.as_str()))
+    {
+        return Err(ParsingError::UnknownDependency);
+    }
+
+    // Kahn's algorithm: repeatedly resolve actions whose needs are all resolved.
+    // Anything left over once no progress can be made sits on a cycle.
+    let mut remaining: HashMap<&str, &Vec<String>> = actions
+        .iter()
+        .map(|a| (a.name.as_str(), &a.needs))
+        .collect();
+    let mut resolved: HashSet<&str> = HashSet::new();
+    while !remaining.is_empty() {
+        let ready: Vec<&str> = remaining
+            .iter()
+            .filter(|(_, needs)| needs.iter().all(|n| resolved.contains(n.as_str())))
+            .map(|(name, _)| *name)
+            .collect();
+        if ready.is_empty() {
+            return Err(ParsingError::DependencyCycle);
+        }
+        for name in ready {
+            remaining.remove(name);
+            resolved.insert(name);
+        }
+    }
+    Ok(())
+}
+
 fn is_valid_action_name(name: &str) -> bool {
     let valid_chars = |c: char| c.is_alphanumeric() || c == ' ' || c == '&' || c == '-' || c == '_';
     !name.is_empty() && name.chars().all(valid_chars)
 }
 
+/// Service names are host names on the network of the action.
+fn is_valid_service_name(name: &str) -> bool {
+    let valid_chars = |c: char| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-';
+    !name.is_empty()
+        && name.len() <= 63
+        && !name.starts_with('-')
+        && !name.ends_with('-')
+        && name.chars().all(valid_chars)
+}
+
+/// Environment variable names are made of letters, digits and underscores,
+/// and do not start with a digit.
+pub fn is_valid_env_name(name: &str) -> bool {
+    let valid_chars = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(valid_chars)
+}
+
+/// Whether `line` ends with a block scalar indicator, as in `- |` or `run: >-`.
+fn is_block_scalar_header(line: &str) -> bool {
+    matches!(
+        line.split_whitespace().last(),
+        Some("|" | "|-" | "|+" | ">" | ">-" | ">+")
+    )
+}
+
 fn check_command_indentation(yaml: &str) -> Result<(), ParsingError> {
     let lines: Vec<&str> = yaml.lines().collect();
     let mut in_commands = false;
+    let mut commands_key_indent = 0;
     let mut command_indent = None;
+    // Indentation of the line opening the block scalar being read, if any
+    let mut block_scalar_indent = None;
 
     for line in lines {
+        let indent = line.chars().take_while(|&c| c == ' ').count();
+        // The lines of a block scalar are script content, not list items
+        if let Some(block_indent) = block_scalar_indent {
+            if line.trim().is_empty() || indent > block_indent {
+                continue;
+            }
+            block_scalar_indent = None;
+        }
+        if is_block_scalar_header(line) {
+            block_scalar_indent = Some(indent);
+        }
         if line.trim().starts_with("commands:") {
             in_commands = true;
+            commands_key_indent = indent;
+            command_indent = None;
             continue;
         }
+        // Any other key at the same level or above closes the commands list
+        if in_commands
+            && !line.trim().is_empty()
+            && !line.trim().starts_with('-')
+            && indent <= commands_key_indent
+        {
+            in_commands = false;
+        }
         if in_commands && line.trim().starts_with('-') {
-            let indent = line.chars().take_while(|&c| c == ' ').count();
             if let Some(prev_indent) = command_indent {
                 if indent != prev_indent {
                     return Err(ParsingError::InconsistentCommandIndentation);
@@ -4,10 +4,14 @@ use std::thread;
 
 use std::time::Duration;
 
-use grpc_scheduler::{controller_server::Controller, ActionRequest, ActionResponse};
+use grpc_scheduler::{
+    controller_server::Controller, ActionRequest, ActionResponse, CancelActionRequest,
+    CancelActionResponse,
+};
 use tokio::sync::mpsc;
 use tokio_stream::wrappers::ReceiverStream;
 use tonic::{async_trait, Request, Response, Status};
+use tracing::info;
 
 pub mod grpc_scheduler {
     tonic::include_proto!("scheduler");
@@ -47,6 +51,10 @@ impl Controller for MockSchedulerService {
                     completion: grpc_scheduler::ActionStatus::Scheduled as i32,
                     exit_code: Some(1),
                 }),
+                artifact: None,
+                step_started: None,
+                step_finished: None,
+                ..Default::default()
             }))
             .await
             .expect("should be sent");
@@ -54,6 +62,29 @@ impl Controller for MockSchedulerService {
             thread::sleep(Duration::from_millis(500));
         }
 
+        tx.send(Ok(ActionResponse {
+            action_id: _request.get_ref().action_id,
+            log: "INFO: completed".to_string(),
+            result: Some(grpc_scheduler::ActionResult {
+                completion: grpc_scheduler::ActionStatus::Completed as i32,
+                exit_code: Some(0),
+            }),
+            artifact: None,
+            step_started: None,
+            step_finished: None,
+            ..Default::default()
+        }))
+        .await
+        .expect("should be sent");
+
         Ok(Response::new(ReceiverStream::new(rx)))
     }
+
+    async fn cancel_action(
+        &self,
+        _request: Request<CancelActionRequest>,
+    ) -> Result<Response<CancelActionResponse>, Status> {
+        info!("Action cancelled");
+        Ok(Response::new(CancelActionResponse {}))
+    }
 }
@@ -0,0 +1,63 @@
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+    use std::path::PathBuf;
+
+    use crate::domain::artifacts::{entities::ArtifactError, ports::ArtifactStorage};
+    use crate::infrastructure::storage::local_artifact_storage::LocalArtifactStorage;
+
+    fn storage_root(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("sealci-{}-{}", name, std::process::id()))
+    }
+
+    #[tokio::test]
+    async fn test_local_storage_appends_chunks() {
+        let root = storage_root("artifacts");
+        let storage = LocalArtifactStorage::new(&root);
+
+        storage.append(1, 1, b"first ").await.unwrap();
+        storage.append(1, 1, b"second").await.unwrap();
+        storage.append(1, 2, b"retried").await.unwrap();
+        storage.commit(1, 1).await.unwrap();
+
+        let mut archive = Vec::new();
+        let mut stream = storage.open(1, 1).await.unwrap();
+        while let Some(chunk) = stream.next().await {
+            archive.extend(chunk.unwrap());
+        }
+        assert_eq!(archive, b"first second");
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[tokio::test]
+    async fn test_local_storage_serves_only_committed_archives() {
+        let root = storage_root("partial-artifacts");
+        let storage = LocalArtifactStorage::new(&root);
+
+        storage.append(1, 1, b"truncated").await.unwrap();
+
+        assert!(matches!(
+            storage.open(1, 1).await,
+            Err(ArtifactError::NotFound)
+        ));
+        storage.discard(1, 1).await.unwrap();
+        assert!(storage.commit(1, 1).await.is_err());
+        // Discarding after a commit keeps the archive
+        storage.append(1, 2, b"complete").await.unwrap();
+        storage.commit(1, 2).await.unwrap();
+        storage.discard(1, 2).await.unwrap();
+        assert!(storage.open(1, 2).await.is_ok());
+
+        let _ = std::fs::remove_dir_all(root);
+    }
+
+    #[tokio::test]
+    async fn test_local_storage_missing_archive() {
+        let storage = LocalArtifactStorage::new(storage_root("missing-artifacts"));
+
+        let result = storage.open(1, 1).await;
+
+        assert!(matches!(result, Err(ArtifactError::NotFound)));
+    }
+}
@@ -0,0 +1,54 @@
+#[cfg(test)]
+mod tests {
+    use crate::domain::action::entities::action::{
+        ActionResponse, ActionStatus, AttemptOutcome, RetryCondition, RetryPolicy,
+    };
+    use crate::infrastructure::grpc::proto_scheduler::{
+        ActionResponse as ProtoActionResponse, ActionResult as ProtoActionResult,
+        ActionStatus as ProtoActionStatus,
+    };
+
+    #[test]
+    fn test_cancelled_result_from_scheduler() {
+        let response = ActionResponse::from(ProtoActionResponse {
+            action_id: 1,
+            log: "Action cancelled".to_string(),
+            result: Some(ProtoActionResult {
+                completion: ProtoActionStatus::Cancelled as i32,
+                exit_code: None,
+            }),
+            ..Default::default()
+        });
+
+        let result = response.result.unwrap();
+        assert_eq!(result.completion, ActionStatus::Cancelled);
+        assert!(result.completion.is_terminal());
+        assert_eq!(result.exit_code, None);
+    }
+
+    #[test]
+    fn test_cancelled_attempt_is_not_retried() {
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            on: vec![
+                RetryCondition::AgentLost,
+                RetryCondition::ExitCode(vec![137]),
+            ],
+        };
+        let cancelled = AttemptOutcome {
+            status: ActionStatus::Cancelled,
+            exit_code: None,
+            agent_lost: false,
+        };
+
+        assert!(!policy.should_retry(1, &cancelled));
+    }
+
+    #[test]
+    fn test_cancelled_status_round_trip() {
+        let status: ActionStatus = "ACTION_STATUS_CANCELLED".parse().unwrap();
+
+        assert_eq!(status, ActionStatus::Cancelled);
+        assert_eq!(status.as_proto_name(), "ACTION_STATUS_CANCELLED");
+    }
+}
@@ -0,0 +1,101 @@
+#[cfg(test)]
+mod tests {
+    use crate::domain::pipeline::entities::{
+        condition::{Condition, ConditionError},
+        pipeline::TriggerContext,
+    };
+
+    fn push_on(branch: &str) -> TriggerContext {
+        TriggerContext {
+            event: Some("push".to_string()),
+            branch: Some(branch.to_string()),
+            message: Some("Fix the build [deploy]".to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn tag(name: &str) -> TriggerContext {
+        TriggerContext {
+            event: Some("tag".to_string()),
+            tag: Some(name.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn evaluate(condition: &str, context: &TriggerContext) -> bool {
+        Condition::parse(condition).unwrap().evaluate(context)
+    }
+
+    #[test]
+    fn test_condition_comparisons() {
+        let condition = r#"branch == "main" && event == "push""#;
+        assert!(evaluate(condition, &push_on("main")));
+        assert!(!evaluate(condition, &push_on("dev")));
+        assert!(evaluate(r#"branch != "main""#, &push_on("dev")));
+    }
+
+    #[test]
+    fn test_condition_regex_match() {
+        assert!(evaluate(r#"tag =~ "^v""#, &tag("v1.2.0")));
+        assert!(!evaluate(r#"tag =~ "^v""#, &tag("nightly")));
+        // Missing variables are empty strings
+        assert!(!evaluate(r#"tag =~ "^v""#, &push_on("main")));
+        assert!(evaluate(r#"message =~ "\\[deploy\\]""#, &push_on("main")));
+    }
+
+    #[test]
+    fn test_condition_operators_precedence() {
+        let condition = r#"!tag && (branch == "dev" || branch == "main")"#;
+        assert!(evaluate(condition, &push_on("main")));
+        assert!(!evaluate(condition, &push_on("feature")));
+        assert!(!evaluate(condition, &tag("v1")));
+
+        let condition = r#"event == "tag" || branch == "main" && event == "push""#;
+        assert!(evaluate(condition, &tag("v1")));
+        assert!(evaluate(condition, &push_on("main")));
+    }
+
+    #[test]
+    fn test_invalid_conditions() {
+        assert_eq!(
+            Condition::parse(r#"commit == "abc""#).unwrap_err(),
+            ConditionError::UnknownVariable("commit".to_string())
+        );
+        assert_eq!(
+            Condition::parse(r#"branch == "main"#).unwrap_err(),
+            ConditionError::UnterminatedString
+        );
+        assert_eq!(
+            Condition::parse("branch ==").unwrap_err(),
+            ConditionError::UnexpectedEnd
+        );
+        assert!(matches!(
+            Condition::parse(r#"tag =~ "(""#),
+            Err(ConditionError::InvalidRegex(_))
+        ));
+        assert!(Condition::parse(r#"(branch == "main""#).is_err());
+        assert!(Condition::parse(r#"branch = "main""#).is_err());
+    }
+
+    #[test]
+    fn test_deeply_nested_conditions() {
+        let nested = format!("{}branch", "!".repeat(64));
+        assert!(Condition::parse(&nested).is_ok());
+
+        let negations = format!("{}branch", "!".repeat(10_000));
+        assert_eq!(
+            Condition::parse(&negations).unwrap_err(),
+            ConditionError::TooDeep(64)
+        );
+        let parentheses = format!("{}branch{}", "(".repeat(10_000), ")".repeat(10_000));
+        assert_eq!(
+            Condition::parse(&parentheses).unwrap_err(),
+            ConditionError::TooDeep(64)
+        );
+        let chain = vec!["branch"; 10_000].join(" && ");
+        assert_eq!(
+            Condition::parse(&chain).unwrap_err(),
+            ConditionError::TooDeep(64)
+        );
+    }
+}
@@ -0,0 +1,164 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use async_trait::async_trait;
+    use tokio::sync::broadcast::{self, error::RecvError};
+    use tokio_stream::StreamExt;
+
+    use crate::application::http::pipeline::handlers::logs::{
+        sse_event, LogCursor, LogPage, Subscription,
+    };
+    use crate::application::ports::log_service::LogService;
+    use crate::application::services::log_hub::LogHub;
+    use crate::domain::log::entities::log::{Log, LogDTO, LogError, LogFilter, LogStream};
+
+    fn log(seq: i64, data: &str) -> Log {
+        Log::new(seq + 100, 1, seq, data.to_string(), 1)
+    }
+
+    #[test]
+    fn test_sse_event_splits_lines() {
+        assert_eq!(
+            sse_event(3, "Compiling\r\nFinished"),
+            "id: 3\nevent: log\ndata: Compiling\ndata: Finished\n\n"
+        );
+    }
+
+    #[test]
+    fn test_cursor_skips_offset_and_seen_lines() {
+        let mut cursor = LogCursor::new(1);
+
+        assert_eq!(cursor.advance(&log(1, "first")), None);
+        assert_eq!(
+            cursor.advance(&log(2, "second")),
+            Some(sse_event(2, "second"))
+        );
+        // Received both from the database and from the hub
+        assert_eq!(cursor.advance(&log(2, "second")), None);
+        assert_eq!(
+            cursor.advance(&log(3, "third")),
+            Some(sse_event(3, "third"))
+        );
+        assert_eq!(cursor.last_seq(), 3);
+    }
+
+    #[test]
+    fn test_log_page_points_to_next_page_when_full() {
+        let page = LogPage::new(vec![log(1, "a"), log(2, "b")], 2);
+        assert_eq!(page.next_after_seq, Some(2));
+
+        let page = LogPage::new(vec![log(3, "c")], 2);
+        assert_eq!(page.next_after_seq, None);
+        assert_eq!(LogPage::new(vec![], 2).next_after_seq, None);
+    }
+
+    #[test]
+    fn test_log_stream_names() {
+        for stream in [LogStream::System, LogStream::Stdout, LogStream::Stderr] {
+            assert_eq!(stream.to_string().parse::<LogStream>(), Ok(stream));
+        }
+        assert!("stdin".parse::<LogStream>().is_err());
+    }
+
+    #[test]
+    fn test_log_row_with_unknown_stream_is_system() {
+        let log = Log::from(LogDTO {
+            id: 1,
+            action_id: 1,
+            seq: 1,
+            attempt: 1,
+            ts: time::OffsetDateTime::UNIX_EPOCH,
+            stream: "console".to_string(),
+            step_index: Some(0),
+            data: "hello".to_string(),
+        });
+        assert_eq!(log.stream, LogStream::System);
+        assert_eq!(log.step_index, Some(0));
+    }
+
+    #[tokio::test]
+    async fn test_hub_sends_lines_until_closed() {
+        let hub = LogHub::new();
+        // Following an action that has not started yet
+        let mut receiver = hub.subscribe(1);
+        hub.open(1);
+        assert!(hub.is_open(1));
+
+        hub.publish(&log(1, "building"));
+        hub.publish(&Log::new(2, 2, 1, "other action".to_string(), 1));
+        hub.close(1);
+
+        assert_eq!(receiver.recv().await.unwrap().data, "building");
+        assert!(matches!(receiver.recv().await, Err(RecvError::Closed)));
+        assert!(!hub.is_open(1));
+    }
+
+    #[tokio::test]
+    async fn test_hub_releases_actions_that_never_ran() {
+        let hub = LogHub::new();
+        let receiver = hub.subscribe(1);
+
+        hub.release(1);
+        let mut other = hub.subscribe(1);
+        drop(receiver);
+        hub.publish(&log(1, "still followed"));
+        assert_eq!(other.recv().await.unwrap().data, "still followed");
+
+        drop(other);
+        hub.release(1);
+        // A new subscriber gets a new channel, without the old lines
+        let mut receiver = hub.subscribe(1);
+        assert!(matches!(
+            receiver.try_recv(),
+            Err(tokio::sync::broadcast::error::TryRecvError::Empty)
+        ));
+    }
+
+    /// Follows the hub, recording the actions released
+    #[derive(Default)]
+    struct HubLogService {
+        hub: LogHub,
+        released: Mutex<Vec<i64>>,
+    }
+
+    #[async_trait]
+    impl LogService for HubLogService {
+        async fn find_page(&self, _: i64, _: &LogFilter) -> Result<Vec<Log>, LogError> {
+            Ok(Vec::new())
+        }
+
+        fn subscribe(&self, action_id: i64) -> broadcast::Receiver<Log> {
+            self.hub.subscribe(action_id)
+        }
+
+        fn is_running(&self, action_id: i64) -> bool {
+            self.hub.is_open(action_id)
+        }
+
+        fn release(&self, action_id: i64) {
+            self.released.lock().unwrap().push(action_id);
+            self.hub.release(action_id)
+        }
+    }
+
+    #[tokio::test]
+    async fn test_subscription_is_released_when_stream_is_dropped() {
+        let service = Arc::new(HubLogService::default());
+        let subscription = Subscription::new(service.clone(), 1);
+        let mut stream = Box::pin(async_stream::stream! {
+            let mut subscription = subscription;
+            while let Ok(log) = subscription.recv().await {
+                yield log.data;
+            }
+        });
+
+        service.hub.publish(&log(1, "building"));
+        assert_eq!(stream.next().await.unwrap(), "building");
+        assert!(service.released.lock().unwrap().is_empty());
+
+        // The client goes away before the action is over
+        drop(stream);
+        assert_eq!(*service.released.lock().unwrap(), vec![1]);
+    }
+}
@@ -1 +1,13 @@
+pub mod artifact_tests;
+pub mod cancel_tests;
+pub mod condition_tests;
+pub mod log_stream_tests;
+pub mod pipeline_metadata_tests;
+pub mod rerun_tests;
+pub mod retry_tests;
+pub mod secret_tests;
+pub mod status_reporter_tests;
+pub mod status_transition_tests;
+pub mod step_tests;
+pub mod webhook_tests;
 pub mod yaml_parser_tests;
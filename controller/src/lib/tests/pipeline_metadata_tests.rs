@@ -0,0 +1,95 @@
+#[cfg(test)]
+mod tests {
+    use time::macros::datetime;
+
+    use crate::domain::pipeline::entities::pipeline::{
+        CheckoutOptions, Pipeline, PipelineDTO, PipelinePage, PipelineStatus,
+    };
+
+    fn pipelines(ids: &[i64]) -> Vec<Pipeline> {
+        ids.iter()
+            .map(|&id| {
+                Pipeline::new(
+                    id,
+                    "https://github.com/dev-sealci/sealci".to_string(),
+                    "ci".to_string(),
+                    vec![],
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_pipeline_row_keeps_trigger_and_times() {
+        let pipeline = Pipeline::from(PipelineDTO {
+            id: 7,
+            repository_url: "https://github.com/dev-sealci/sealci".to_string(),
+            name: "ci".to_string(),
+            event: Some("pull_request".to_string()),
+            branch: Some("feat/logs".to_string()),
+            tag: None,
+            message: Some("Stream the logs".to_string()),
+            commit_sha: Some("4b825dc642cb6eb9a060e54bf8d69288fbee4904".to_string()),
+            git_ref: Some("refs/pull/42/head".to_string()),
+            pr_number: Some(42),
+            author: Some("octocat".to_string()),
+            checkout_depth: 0,
+            checkout_submodules: true,
+            status: "running".to_string(),
+            rerun_of: None,
+            created_at: datetime!(2026-10-18 10:00 UTC),
+            started_at: Some(datetime!(2026-10-18 10:01 UTC)),
+            finished_at: None,
+        });
+
+        assert_eq!(pipeline.trigger.pr_number, Some(42));
+        assert_eq!(
+            pipeline.trigger.git_ref.as_deref(),
+            Some("refs/pull/42/head")
+        );
+        assert_eq!(pipeline.trigger.author.as_deref(), Some("octocat"));
+        assert_eq!(
+            pipeline.checkout,
+            CheckoutOptions {
+                depth: 0,
+                submodules: true,
+            }
+        );
+        assert_eq!(pipeline.status, PipelineStatus::Running);
+        assert_eq!(pipeline.created_at, datetime!(2026-10-18 10:00 UTC));
+        assert_eq!(pipeline.started_at, Some(datetime!(2026-10-18 10:01 UTC)));
+        assert!(pipeline.finished_at.is_none() && pipeline.actions.is_empty());
+    }
+
+    #[test]
+    fn test_pipeline_page_with_extra_pipeline_has_next_cursor() {
+        let page = PipelinePage::new(pipelines(&[9, 8, 7]), 2);
+
+        let ids: Vec<i64> = page.pipelines.iter().map(|pipeline| pipeline.id).collect();
+        assert_eq!(ids, vec![9, 8]);
+        assert_eq!(page.next_cursor, Some(8));
+    }
+
+    #[test]
+    fn test_last_pipeline_page_has_no_cursor() {
+        let page = PipelinePage::new(pipelines(&[9, 8]), 2);
+
+        assert_eq!(page.pipelines.len(), 2);
+        assert_eq!(page.next_cursor, None);
+        assert!(PipelinePage::new(vec![], 2).next_cursor.is_none());
+    }
+
+    #[test]
+    fn test_pipeline_status_round_trip() {
+        for status in [
+            PipelineStatus::Queued,
+            PipelineStatus::Running,
+            PipelineStatus::Success,
+            PipelineStatus::Failed,
+            PipelineStatus::Cancelled,
+        ] {
+            assert_eq!(status.as_str().parse::<PipelineStatus>(), Ok(status));
+        }
+        assert!("ACTION_STATUS_RUNNING".parse::<PipelineStatus>().is_err());
+    }
+}
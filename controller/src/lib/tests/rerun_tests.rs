@@ -0,0 +1,335 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+
+    use async_trait::async_trait;
+
+    use crate::application::ports::{
+        action_service::ActionService, pipeline_service::PipelineService,
+        scheduler_service::SchedulerService,
+    };
+    use crate::application::services::pipeline_service::PipelineServiceImpl;
+    use crate::domain::action::entities::action::{
+        Action, ActionAttempt, ActionError, ActionStatus, ActionStep, ActionType, NewAction,
+        StepEvent,
+    };
+    use crate::domain::command::entities::command::Step;
+    use crate::domain::log::{
+        entities::log::{Log, LogError, LogFilter, NewLog},
+        ports::log_repository::LogRepository,
+    };
+    use crate::domain::pipeline::{
+        entities::pipeline::{
+            CheckoutOptions, Pipeline, PipelineError, PipelineFilter, TriggerContext,
+        },
+        ports::pipeline_repository::PipelineRepository,
+    };
+    use crate::domain::scheduler::entities::scheduler::SchedulerError;
+
+    fn action(id: i64, status: &str) -> Action {
+        Action::new(
+            id,
+            1,
+            format!("action-{}", id),
+            "alpine:latest".to_string(),
+            vec![],
+            ActionType::Container,
+            status.to_string(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_rerun_reuses_completed_actions_only_when_asked() {
+        let completed = action(1, "ACTION_STATUS_COMPLETED");
+
+        assert_eq!(completed.rerun_source(true), Some(1));
+        assert_eq!(completed.rerun_source(false), None);
+    }
+
+    #[test]
+    fn test_rerun_runs_failed_and_skipped_actions_again() {
+        for status in [
+            "ACTION_STATUS_ERROR",
+            "ACTION_STATUS_SKIPPED",
+            "ACTION_STATUS_CANCELLED",
+            "ACTION_STATUS_TIMED_OUT",
+        ] {
+            assert_eq!(action(1, status).rerun_source(true), None);
+        }
+    }
+
+    #[test]
+    fn test_rerun_points_to_the_run_that_produced_the_result() {
+        let mut reused = action(5, "ACTION_STATUS_COMPLETED");
+        reused.reused_from = Some(1);
+
+        assert_eq!(reused.rerun_source(true), Some(1));
+    }
+
+    #[tokio::test]
+    async fn test_rerun_only_failed_reuses_completed_actions() {
+        let store = Arc::new(MemoryStore::default());
+        store.pipelines.lock().unwrap().push(Pipeline::new(
+            1,
+            "https://github.com/sealci/sealci".to_string(),
+            "ci".to_string(),
+            vec![],
+        ));
+        let mut build = action(1, "ACTION_STATUS_COMPLETED");
+        build.commands = vec![Step {
+            run: "cargo build".to_string(),
+            ..Default::default()
+        }];
+        let mut test = action(2, "ACTION_STATUS_ERROR");
+        test.needs = vec!["action-1".to_string()];
+        store.actions.lock().unwrap().extend([build, test]);
+        let service = PipelineServiceImpl::new(
+            Arc::new(MemoryPipelineRepository(store.clone())),
+            Arc::new(NoLogs),
+            Arc::new(MemoryActionService(store.clone())),
+            Arc::new(IdleScheduler),
+        );
+
+        let rerun = service.rerun_pipeline(1, true).await.unwrap();
+
+        assert_eq!(rerun.rerun_of, Some(1));
+        assert_eq!(rerun.actions.len(), 2);
+        let (build, test) = (&rerun.actions[0], &rerun.actions[1]);
+        assert!(build.pipeline_id == rerun.id && test.pipeline_id == rerun.id);
+        assert_eq!(build.status, ActionStatus::Completed);
+        assert_eq!(build.reused_from, Some(1));
+        assert_eq!(build.commands[0].run, "cargo build");
+        assert_eq!(test.status, ActionStatus::Pending);
+        assert_eq!(test.reused_from, None);
+        assert_eq!(test.needs, vec!["action-1".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn test_rerun_only_failed_of_a_successful_pipeline_creates_nothing() {
+        let store = Arc::new(MemoryStore::default());
+        store.pipelines.lock().unwrap().push(Pipeline::new(
+            1,
+            "https://github.com/sealci/sealci".to_string(),
+            "ci".to_string(),
+            vec![],
+        ));
+        store
+            .actions
+            .lock()
+            .unwrap()
+            .push(action(1, "ACTION_STATUS_COMPLETED"));
+        let service = PipelineServiceImpl::new(
+            Arc::new(MemoryPipelineRepository(store.clone())),
+            Arc::new(NoLogs),
+            Arc::new(MemoryActionService(store.clone())),
+            Arc::new(IdleScheduler),
+        );
+
+        let result = service.rerun_pipeline(1, true).await;
+
+        assert!(matches!(result, Err(PipelineError::NothingToRerun)));
+        assert_eq!(store.pipelines.lock().unwrap().len(), 1);
+    }
+
+    /// Pipelines and actions shared by the repository and the action service.
+    #[derive(Default)]
+    struct MemoryStore {
+        pipelines: Mutex<Vec<Pipeline>>,
+        actions: Mutex<Vec<Action>>,
+    }
+
+    struct MemoryPipelineRepository(Arc<MemoryStore>);
+
+    #[async_trait]
+    impl PipelineRepository for MemoryPipelineRepository {
+        async fn create(
+            &self,
+            _repository_url: String,
+            _name: String,
+            _trigger: TriggerContext,
+            _checkout: CheckoutOptions,
+        ) -> Result<Pipeline, PipelineError> {
+            unimplemented!()
+        }
+
+        async fn create_rerun(
+            &self,
+            pipeline_id: i64,
+            actions: Vec<(NewAction, Vec<Step>)>,
+        ) -> Result<Pipeline, PipelineError> {
+            let mut pipelines = self.0.pipelines.lock().unwrap();
+            let original = pipelines
+                .iter()
+                .find(|pipeline| pipeline.id == pipeline_id)
+                .ok_or(PipelineError::NotFound)?;
+            let mut rerun = original.clone();
+            rerun.id = pipelines.len() as i64 + 1;
+            rerun.rerun_of = Some(pipeline_id);
+            pipelines.push(rerun.clone());
+
+            let mut stored = self.0.actions.lock().unwrap();
+            for (new_action, commands) in actions {
+                let mut created = Action::new(
+                    stored.len() as i64 + 1,
+                    rerun.id,
+                    new_action.name,
+                    new_action.container_uri,
+                    commands,
+                    new_action.r#type,
+                    new_action.status,
+                )
+                .unwrap();
+                created.needs = new_action.needs;
+                created.reused_from = new_action.reused_from;
+                stored.push(created);
+            }
+            Ok(rerun)
+        }
+
+        async fn find_page(
+            &self,
+            _filter: &PipelineFilter,
+            _cursor: Option<i64>,
+            _limit: i64,
+        ) -> Result<Vec<Pipeline>, PipelineError> {
+            unimplemented!()
+        }
+
+        async fn find_by_id(&self, pipeline_id: i64) -> Result<Pipeline, PipelineError> {
+            self.0
+                .pipelines
+                .lock()
+                .unwrap()
+                .iter()
+                .find(|pipeline| pipeline.id == pipeline_id)
+                .cloned()
+                .ok_or(PipelineError::NotFound)
+        }
+
+        async fn claim_status_report(
+            &self,
+            _pipeline_id: i64,
+        ) -> Result<Option<Pipeline>, PipelineError> {
+            unimplemented!()
+        }
+    }
+
+    struct MemoryActionService(Arc<MemoryStore>);
+
+    #[async_trait]
+    impl ActionService for MemoryActionService {
+        async fn create(
+            &self,
+            _action: NewAction,
+            _commands: Option<Vec<Step>>,
+        ) -> Result<Action, ActionError> {
+            unimplemented!()
+        }
+
+        async fn find_by_id(&self, _action_id: i64) -> Result<Action, ActionError> {
+            unimplemented!()
+        }
+
+        async fn find_by_pipeline_id(&self, pipeline_id: i64) -> Result<Vec<Action>, ActionError> {
+            Ok(self
+                .0
+                .actions
+                .lock()
+                .unwrap()
+                .iter()
+                .filter(|action| action.pipeline_id == pipeline_id)
+                .cloned()
+                .collect())
+        }
+
+        async fn find_by_pipeline_ids(
+            &self,
+            _pipeline_ids: &[i64],
+        ) -> Result<Vec<Action>, ActionError> {
+            unimplemented!()
+        }
+
+        async fn update_status(
+            &self,
+            _action_id: i64,
+            _status: &String,
+        ) -> Result<Action, ActionError> {
+            unimplemented!()
+        }
+
+        async fn append_log(&self, _action_id: i64, _log: NewLog) -> Result<Log, ActionError> {
+            unimplemented!()
+        }
+
+        async fn start_attempt(&self, _action_id: i64, _attempt: i32) -> Result<(), ActionError> {
+            unimplemented!()
+        }
+
+        async fn retry(&self, _action_id: i64, _attempt: i32) -> Result<Action, ActionError> {
+            unimplemented!()
+        }
+
+        async fn finish_attempt(
+            &self,
+            _action_id: i64,
+            _attempt: i32,
+            _status: &str,
+            _exit_code: Option<i32>,
+        ) -> Result<(), ActionError> {
+            unimplemented!()
+        }
+
+        async fn find_attempts(&self, _action_id: i64) -> Result<Vec<ActionAttempt>, ActionError> {
+            unimplemented!()
+        }
+
+        async fn record_step(
+            &self,
+            _action_id: i64,
+            _attempt: i32,
+            _event: &StepEvent,
+        ) -> Result<(), ActionError> {
+            unimplemented!()
+        }
+
+        async fn find_steps(&self, _action_id: i64) -> Result<Vec<ActionStep>, ActionError> {
+            unimplemented!()
+        }
+    }
+
+    struct NoLogs;
+
+    #[async_trait]
+    impl LogRepository for NoLogs {
+        async fn create(&self, _action_id: i64, _log: NewLog) -> Result<Log, LogError> {
+            unimplemented!()
+        }
+
+        async fn find_page(
+            &self,
+            _action_id: i64,
+            _filter: &LogFilter,
+        ) -> Result<Vec<Log>, LogError> {
+            unimplemented!()
+        }
+
+        async fn find_tail(&self, _action_id: i64, _limit: i64) -> Result<Vec<Log>, LogError> {
+            unimplemented!()
+        }
+    }
+
+    /// Leaves the dispatched pipelines pending.
+    struct IdleScheduler;
+
+    #[async_trait]
+    impl SchedulerService for IdleScheduler {
+        async fn execute_pipeline(&self, _pipeline_id: i64) -> Result<(), SchedulerError> {
+            Ok(())
+        }
+
+        async fn cancel_action(&self, _action_id: i64) -> Result<bool, SchedulerError> {
+            Ok(false)
+        }
+    }
+}
@@ -0,0 +1,47 @@
+#[cfg(test)]
+mod tests {
+    use crate::domain::action::entities::action::{
+        ActionStatus, AttemptOutcome, RetryCondition, RetryPolicy,
+    };
+
+    fn policy() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            on: vec![
+                RetryCondition::AgentLost,
+                RetryCondition::ExitCode(vec![137]),
+            ],
+        }
+    }
+
+    fn failed(exit_code: Option<i32>, agent_lost: bool) -> AttemptOutcome {
+        AttemptOutcome {
+            status: ActionStatus::Error,
+            exit_code,
+            agent_lost,
+        }
+    }
+
+    #[test]
+    fn test_retry_on_matching_failures() {
+        let policy = policy();
+
+        assert!(policy.should_retry(1, &failed(Some(137), false)));
+        assert!(policy.should_retry(2, &failed(None, true)));
+        assert!(!policy.should_retry(1, &failed(Some(1), false)));
+        assert!(!policy.should_retry(1, &failed(None, false)));
+    }
+
+    #[test]
+    fn test_retry_stops_after_max_attempts_or_success() {
+        let policy = policy();
+
+        assert!(!policy.should_retry(3, &failed(Some(137), false)));
+        let completed = AttemptOutcome {
+            status: ActionStatus::Completed,
+            exit_code: Some(0),
+            agent_lost: false,
+        };
+        assert!(!policy.should_retry(1, &completed));
+    }
+}
@@ -0,0 +1,172 @@
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+    use wiremock::matchers::{body_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::domain::pipeline::entities::pipeline::{Pipeline, PipelineStatus};
+    use crate::domain::reporting::{entities::ReportError, ports::StatusReporter};
+    use crate::infrastructure::reporting::github_status_reporter::{
+        commit_state, repository_host, repository_slug, GithubStatusReporter,
+    };
+
+    const SHA: &str = "3f786850e387550fdab836ed7e6dc881de23001b";
+
+    fn pipeline(status: PipelineStatus) -> Pipeline {
+        let mut pipeline = Pipeline::new(
+            42,
+            "https://github.com/dev-sealci/sealci.git".to_string(),
+            "ci".to_string(),
+            vec![],
+        );
+        pipeline.status = status;
+        pipeline.trigger.commit_sha = Some(SHA.to_string());
+        pipeline
+    }
+
+    fn statuses_path() -> String {
+        format!("/repos/dev-sealci/sealci/statuses/{}", SHA)
+    }
+
+    fn reporter(server: &MockServer) -> GithubStatusReporter {
+        GithubStatusReporter::new(
+            server.uri(),
+            "github.com".to_string(),
+            "token".to_string(),
+            None,
+        )
+    }
+
+    /// A server failing the test if anything is posted to it
+    async fn unused_server() -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(0)
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_status_is_posted_on_its_commit() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(statuses_path()))
+            .and(header("authorization", "Bearer token"))
+            .and(body_json(json!({
+                "state": "failure",
+                "target_url": "https://ci.example.com/pipelines/42",
+                "description": "Pipeline failed",
+                "context": "sealci/ci",
+            })))
+            .respond_with(ResponseTemplate::new(201))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let reporter = GithubStatusReporter::new(
+            server.uri(),
+            "github.com".to_string(),
+            "token".to_string(),
+            Some("https://ci.example.com/".to_string()),
+        );
+
+        reporter
+            .report(&pipeline(PipelineStatus::Failed))
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_rejected_status_is_an_error() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(statuses_path()))
+            .respond_with(ResponseTemplate::new(404).set_body_string("Not Found"))
+            .mount(&server)
+            .await;
+
+        let reporter = reporter(&server);
+
+        let result = reporter.report(&pipeline(PipelineStatus::Running)).await;
+
+        assert!(matches!(
+            result,
+            Err(ReportError::Rejected { status: 404, ref body }) if body == "Not Found"
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_pipeline_without_commit_is_not_reported() {
+        let server = unused_server().await;
+
+        let reporter = reporter(&server);
+        let mut pipeline = pipeline(PipelineStatus::Success);
+        pipeline.trigger.commit_sha = None;
+
+        reporter.report(&pipeline).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_invalid_commit_is_not_reported() {
+        let server = unused_server().await;
+
+        let reporter = reporter(&server);
+        let mut pipeline = pipeline(PipelineStatus::Success);
+        pipeline.trigger.commit_sha = Some("../../../user/repos".to_string());
+
+        let result = reporter.report(&pipeline).await;
+
+        assert!(matches!(result, Err(ReportError::InvalidCommit(_))));
+    }
+
+    #[tokio::test]
+    async fn test_repository_of_another_forge_is_not_reported() {
+        let server = unused_server().await;
+
+        let reporter = reporter(&server);
+        let mut pipeline = pipeline(PipelineStatus::Success);
+        pipeline.repository_url = "https://gitlab.com/dev-sealci/sealci.git".to_string();
+
+        reporter.report(&pipeline).await.unwrap();
+    }
+
+    #[test]
+    fn test_repository_slug_from_url() {
+        assert_eq!(
+            repository_slug("https://github.com/dev-sealci/sealci"),
+            Some(("dev-sealci", "sealci"))
+        );
+        assert_eq!(
+            repository_slug("git@github.com:dev-sealci/sealci.git"),
+            Some(("dev-sealci", "sealci"))
+        );
+        assert_eq!(repository_slug("https://github.com/sealci"), None);
+    }
+
+    #[test]
+    fn test_repository_host_from_url() {
+        assert_eq!(
+            repository_host("https://token@GitHub.com:443/dev-sealci/sealci"),
+            Some("GitHub.com")
+        );
+        assert_eq!(
+            repository_host("git@github.com:dev-sealci/sealci.git"),
+            Some("github.com")
+        );
+        assert_eq!(
+            repository_host("ssh://git@gitlab.com/dev-sealci/sealci.git"),
+            Some("gitlab.com")
+        );
+    }
+
+    #[test]
+    fn test_pipeline_status_to_commit_state() {
+        assert_eq!(commit_state(PipelineStatus::Queued).0, "pending");
+        assert_eq!(commit_state(PipelineStatus::Running).0, "pending");
+        assert_eq!(commit_state(PipelineStatus::Success).0, "success");
+        assert_eq!(commit_state(PipelineStatus::Failed).0, "failure");
+        assert_eq!(commit_state(PipelineStatus::Cancelled).0, "error");
+    }
+}
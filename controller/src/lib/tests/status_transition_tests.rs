@@ -0,0 +1,69 @@
+#[cfg(test)]
+mod tests {
+    use crate::domain::action::entities::action::{ActionError, ActionStatus};
+
+    #[test]
+    fn test_action_lifecycle_is_allowed() {
+        assert!(ActionStatus::Pending.can_transition_to(ActionStatus::Scheduled));
+        assert!(ActionStatus::Scheduled.can_transition_to(ActionStatus::Running));
+        assert!(ActionStatus::Running.can_transition_to(ActionStatus::Completed));
+        assert!(ActionStatus::Running.can_transition_to(ActionStatus::Error));
+        assert!(ActionStatus::Running.can_transition_to(ActionStatus::TimedOut));
+        // An action without output reports its result straight away
+        assert!(ActionStatus::Scheduled.can_transition_to(ActionStatus::Completed));
+    }
+
+    #[test]
+    fn test_failed_attempt_is_only_scheduled_again_by_a_retry() {
+        assert!(!ActionStatus::Error.can_transition_to(ActionStatus::Scheduled));
+        assert!(!ActionStatus::TimedOut.can_transition_to(ActionStatus::Scheduled));
+        assert!(ActionStatus::Error.can_retry());
+        assert!(ActionStatus::TimedOut.can_retry());
+        assert!(!ActionStatus::Completed.can_retry());
+        assert!(!ActionStatus::Cancelled.can_retry());
+        assert!(!ActionStatus::Running.can_retry());
+    }
+
+    #[test]
+    fn test_cancelled_action_cannot_be_resumed() {
+        for status in [
+            ActionStatus::Pending,
+            ActionStatus::Scheduled,
+            ActionStatus::Running,
+        ] {
+            assert!(status.can_transition_to(ActionStatus::Cancelled));
+        }
+        assert!(!ActionStatus::Cancelled.can_transition_to(ActionStatus::Scheduled));
+        assert!(!ActionStatus::Cancelled.can_transition_to(ActionStatus::Running));
+        assert!(!ActionStatus::Cancelled.can_transition_to(ActionStatus::Error));
+    }
+
+    #[test]
+    fn test_illegal_transitions_are_rejected() {
+        assert!(!ActionStatus::Pending.can_transition_to(ActionStatus::Running));
+        assert!(!ActionStatus::Pending.can_transition_to(ActionStatus::Completed));
+        assert!(!ActionStatus::Running.can_transition_to(ActionStatus::Pending));
+        assert!(!ActionStatus::Running.can_transition_to(ActionStatus::Skipped));
+        assert!(!ActionStatus::Completed.can_transition_to(ActionStatus::Error));
+        assert!(!ActionStatus::Skipped.can_transition_to(ActionStatus::Scheduled));
+    }
+
+    #[test]
+    fn test_reporting_the_same_status_is_allowed() {
+        assert!(ActionStatus::Running.can_transition_to(ActionStatus::Running));
+        assert!(ActionStatus::Cancelled.can_transition_to(ActionStatus::Cancelled));
+    }
+
+    #[test]
+    fn test_invalid_transition_error_names_both_statuses() {
+        let error = ActionError::InvalidTransition {
+            from: ActionStatus::Completed,
+            to: ActionStatus::Running,
+        };
+
+        assert_eq!(
+            error.to_string(),
+            "Invalid transition from ACTION_STATUS_COMPLETED to ACTION_STATUS_RUNNING"
+        );
+    }
+}
@@ -0,0 +1,88 @@
+#[cfg(test)]
+mod tests {
+    use time::OffsetDateTime;
+
+    use crate::domain::action::entities::action::{ActionResponse, StepEvent};
+    use crate::domain::log::entities::log::LogStream;
+    use crate::infrastructure::grpc::proto_scheduler::{
+        ActionResponse as ProtoActionResponse, LogStream as ProtoLogStream, StepFinished,
+        StepStarted,
+    };
+
+    fn at(millis: i64) -> OffsetDateTime {
+        OffsetDateTime::from_unix_timestamp_nanos(millis as i128 * 1_000_000).unwrap()
+    }
+
+    #[test]
+    fn test_step_boundaries_from_scheduler_response() {
+        let started = ActionResponse::from(ProtoActionResponse {
+            action_id: 1,
+            step_started: Some(StepStarted {
+                index: 0,
+                name: "Unit tests".to_string(),
+                started_at: 1_700_000_000_000,
+            }),
+            ..Default::default()
+        });
+        let finished = ActionResponse::from(ProtoActionResponse {
+            action_id: 1,
+            step_finished: Some(StepFinished {
+                index: 0,
+                name: "Unit tests".to_string(),
+                exit_code: 1,
+                started_at: 1_700_000_000_000,
+                finished_at: 1_700_000_001_500,
+            }),
+            ..Default::default()
+        });
+
+        assert_eq!(
+            started.step,
+            Some(StepEvent::Started {
+                index: 0,
+                name: "Unit tests".to_string(),
+                started_at: at(1_700_000_000_000),
+            })
+        );
+        assert_eq!(
+            finished.step,
+            Some(StepEvent::Finished {
+                index: 0,
+                name: "Unit tests".to_string(),
+                exit_code: 1,
+                started_at: at(1_700_000_000_000),
+                finished_at: at(1_700_000_001_500),
+            })
+        );
+        assert!(started.result.is_none() && finished.artifact.is_none());
+    }
+
+    #[test]
+    fn test_response_without_step() {
+        let response = ActionResponse::from(ProtoActionResponse {
+            action_id: 1,
+            log: "Compiling sealci".to_string(),
+            ..Default::default()
+        });
+
+        assert_eq!(response.step, None);
+        assert_eq!(response.timestamp, None);
+        assert_eq!(response.stream, LogStream::System);
+    }
+
+    #[test]
+    fn test_response_log_metadata() {
+        let response = ActionResponse::from(ProtoActionResponse {
+            action_id: 1,
+            log: "warning: unused variable".to_string(),
+            timestamp: 1_700_000_000_250,
+            stream: ProtoLogStream::Stderr.into(),
+            step_index: Some(2),
+            ..Default::default()
+        });
+
+        assert_eq!(response.timestamp, Some(at(1_700_000_000_250)));
+        assert_eq!(response.stream, LogStream::Stderr);
+        assert_eq!(response.step_index, Some(2));
+    }
+}
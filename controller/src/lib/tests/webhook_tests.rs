@@ -0,0 +1,342 @@
+#[cfg(test)]
+mod tests {
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use async_trait::async_trait;
+    use serde_json::{json, Value};
+    use time::OffsetDateTime;
+    use wiremock::matchers::{header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    use crate::application::ports::webhook_service::WebhookService;
+    use crate::application::services::webhook_service::WebhookServiceImpl;
+    use crate::domain::pipeline::entities::pipeline::{Pipeline, PipelineStatus};
+    use crate::domain::webhooks::{
+        entities::{
+            pipeline_payload, retry_delay, sign, DeliveryStatus, EncryptedWebhook, Webhook,
+            WebhookDelivery, WebhookError, WebhookEvent, SIGNATURE_HEADER,
+        },
+        ports::{WebhookRepository, WebhookSender},
+    };
+    use crate::infrastructure::crypto::aes_gcm_cipher::AesGcmSecretCipher;
+    use crate::infrastructure::webhooks::http_webhook_sender::HttpWebhookSender;
+
+    // base64 of 32 zero bytes
+    const MASTER_KEY: &str = "AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=";
+
+    fn delivery() -> WebhookDelivery {
+        WebhookDelivery {
+            id: 7,
+            webhook_id: 3,
+            event: WebhookEvent::PipelineFailed,
+            payload: json!({ "event": "pipeline_failed", "pipeline": { "id": 42 } }),
+            status: DeliveryStatus::Pending,
+            attempts: 0,
+            response_status: None,
+            error: None,
+            created_at: OffsetDateTime::now_utc(),
+            delivered_at: None,
+        }
+    }
+
+    #[test]
+    fn test_sign_payload_with_hmac_sha256() {
+        assert_eq!(
+            sign("key", b"The quick brown fox jumps over the lazy dog"),
+            "sha256=f7bc83f430538424b13298e6aa6fb143ef4d59a14946175997479dbc2d1a3cd8"
+        );
+    }
+
+    #[test]
+    fn test_retry_delay_doubles_after_each_attempt() {
+        assert_eq!(retry_delay(1), Duration::from_secs(10));
+        assert_eq!(retry_delay(2), Duration::from_secs(20));
+        assert_eq!(retry_delay(4), Duration::from_secs(80));
+        assert_eq!(retry_delay(0), retry_delay(1));
+    }
+
+    #[test]
+    fn test_webhook_event_round_trip() {
+        for event in [
+            WebhookEvent::PipelineStarted,
+            WebhookEvent::PipelineSucceeded,
+            WebhookEvent::PipelineFailed,
+            WebhookEvent::ReleaseCreated,
+        ] {
+            assert_eq!(event.as_str().parse::<WebhookEvent>().unwrap(), event);
+            assert_eq!(serde_json::to_value(event).unwrap(), json!(event.as_str()));
+        }
+        assert!(matches!(
+            "pipeline_cancelled".parse::<WebhookEvent>(),
+            Err(WebhookError::InvalidEvent(_))
+        ));
+    }
+
+    #[test]
+    fn test_only_succeeded_and_failed_pipelines_have_a_finished_event() {
+        assert_eq!(
+            WebhookEvent::finished(PipelineStatus::Success),
+            Some(WebhookEvent::PipelineSucceeded)
+        );
+        assert_eq!(
+            WebhookEvent::finished(PipelineStatus::Failed),
+            Some(WebhookEvent::PipelineFailed)
+        );
+        assert_eq!(WebhookEvent::finished(PipelineStatus::Cancelled), None);
+        assert_eq!(WebhookEvent::finished(PipelineStatus::Running), None);
+    }
+
+    #[test]
+    fn test_webhook_subscribes_to_its_events_only() {
+        let webhook = Webhook {
+            id: 1,
+            repository_url: "https://github.com/dev-sealci/sealci".to_string(),
+            url: "https://chat.example.com/hooks/ci".to_string(),
+            events: vec![WebhookEvent::PipelineFailed, WebhookEvent::ReleaseCreated],
+            created_at: OffsetDateTime::now_utc(),
+        };
+
+        assert!(webhook.subscribes_to(WebhookEvent::PipelineFailed));
+        assert!(!webhook.subscribes_to(WebhookEvent::PipelineStarted));
+    }
+
+    #[test]
+    fn test_pipeline_payload_leaves_actions_out() {
+        let pipeline = Pipeline::new(
+            42,
+            "https://github.com/dev-sealci/sealci".to_string(),
+            "ci".to_string(),
+            vec![],
+        );
+
+        let payload = pipeline_payload(WebhookEvent::PipelineStarted, &pipeline);
+
+        assert_eq!(payload["event"], "pipeline_started");
+        assert_eq!(
+            payload["repository_url"],
+            "https://github.com/dev-sealci/sealci"
+        );
+        assert_eq!(payload["pipeline"]["id"], 42);
+        assert_eq!(payload["pipeline"]["actions"], json!([]));
+    }
+
+    #[tokio::test]
+    async fn test_delivery_is_posted_with_its_signature() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/hooks/ci"))
+            .and(header("content-type", "application/json"))
+            .and(header("x-sealci-event", "pipeline_failed"))
+            .and(header("x-sealci-delivery", "7"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let status = HttpWebhookSender::new()
+            .send(&format!("{}/hooks/ci", server.uri()), &delivery(), "secret")
+            .await
+            .unwrap();
+
+        assert_eq!(status, 204);
+        let requests = server.received_requests().await.unwrap();
+        let signature = requests[0].headers.get(SIGNATURE_HEADER).unwrap();
+        assert_eq!(
+            signature.to_str().unwrap(),
+            sign("secret", &requests[0].body)
+        );
+        let body: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(body, delivery().payload);
+    }
+
+    #[tokio::test]
+    async fn test_rejected_delivery_returns_its_status() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(500))
+            .mount(&server)
+            .await;
+
+        let status = HttpWebhookSender::new()
+            .send(&server.uri(), &delivery(), "secret")
+            .await
+            .unwrap();
+
+        assert_eq!(status, 500);
+    }
+
+    #[tokio::test]
+    async fn test_unreachable_webhook_is_an_error() {
+        // Nothing listens on the port 1
+        let result = HttpWebhookSender::new()
+            .send("http://127.0.0.1:1/hooks/ci", &delivery(), "secret")
+            .await;
+
+        assert!(matches!(result, Err(WebhookError::RequestError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_redirect_is_not_followed() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/hooks/ci"))
+            .respond_with(ResponseTemplate::new(302).insert_header("location", "/elsewhere"))
+            .mount(&server)
+            .await;
+        Mock::given(path("/elsewhere"))
+            .respond_with(ResponseTemplate::new(200))
+            .expect(0)
+            .mount(&server)
+            .await;
+
+        let status = HttpWebhookSender::new()
+            .send(&format!("{}/hooks/ci", server.uri()), &delivery(), "secret")
+            .await
+            .unwrap();
+
+        assert_eq!(status, 302);
+    }
+
+    /// Holds a single webhook and the deliveries left pending by a previous
+    /// run, recording the attempts
+    #[derive(Default)]
+    struct MemoryWebhookRepository {
+        webhook: Mutex<Option<EncryptedWebhook>>,
+        pending: Vec<WebhookDelivery>,
+        attempts: Mutex<Vec<(i64, DeliveryStatus)>>,
+    }
+
+    #[async_trait]
+    impl WebhookRepository for MemoryWebhookRepository {
+        async fn create(
+            &self,
+            repository_url: String,
+            url: String,
+            events: Vec<WebhookEvent>,
+            nonce: Vec<u8>,
+            ciphertext: Vec<u8>,
+        ) -> Result<Webhook, WebhookError> {
+            let webhook = Webhook {
+                id: 3,
+                repository_url,
+                url,
+                events,
+                created_at: OffsetDateTime::now_utc(),
+            };
+            *self.webhook.lock().unwrap() = Some(EncryptedWebhook {
+                webhook: webhook.clone(),
+                nonce,
+                ciphertext,
+            });
+            Ok(webhook)
+        }
+
+        async fn list(&self, _: String) -> Result<Vec<Webhook>, WebhookError> {
+            unimplemented!()
+        }
+
+        async fn find_encrypted(&self, id: i64) -> Result<EncryptedWebhook, WebhookError> {
+            self.webhook
+                .lock()
+                .unwrap()
+                .clone()
+                .filter(|encrypted| encrypted.webhook.id == id)
+                .ok_or(WebhookError::NotFound)
+        }
+
+        async fn find_subscribed(
+            &self,
+            _: String,
+            _: WebhookEvent,
+        ) -> Result<Vec<EncryptedWebhook>, WebhookError> {
+            unimplemented!()
+        }
+
+        async fn delete(&self, _: i64) -> Result<(), WebhookError> {
+            unimplemented!()
+        }
+
+        async fn create_delivery(
+            &self,
+            _: i64,
+            _: WebhookEvent,
+            _: Value,
+        ) -> Result<WebhookDelivery, WebhookError> {
+            unimplemented!()
+        }
+
+        async fn record_attempt(
+            &self,
+            delivery_id: i64,
+            status: DeliveryStatus,
+            _: Option<i32>,
+            _: Option<String>,
+        ) -> Result<(), WebhookError> {
+            self.attempts.lock().unwrap().push((delivery_id, status));
+            Ok(())
+        }
+
+        async fn list_deliveries(
+            &self,
+            _: i64,
+            _: i64,
+        ) -> Result<Vec<WebhookDelivery>, WebhookError> {
+            unimplemented!()
+        }
+
+        async fn find_delivery(&self, _: i64, _: i64) -> Result<WebhookDelivery, WebhookError> {
+            unimplemented!()
+        }
+
+        async fn list_pending(&self) -> Result<Vec<WebhookDelivery>, WebhookError> {
+            Ok(self.pending.clone())
+        }
+    }
+
+    #[tokio::test]
+    async fn test_pending_deliveries_are_resumed() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/hooks/ci"))
+            .and(header("x-sealci-delivery", "7"))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+        let repository = Arc::new(MemoryWebhookRepository {
+            pending: vec![delivery()],
+            ..Default::default()
+        });
+        let service = WebhookServiceImpl::new(
+            repository.clone(),
+            Arc::new(AesGcmSecretCipher::new(MASTER_KEY).unwrap()),
+            Arc::new(HttpWebhookSender::new()),
+        );
+        service
+            .create_webhook(
+                "https://github.com/dev-sealci/sealci",
+                &format!("{}/hooks/ci", server.uri()),
+                vec![WebhookEvent::PipelineFailed],
+                "secret",
+            )
+            .await
+            .unwrap();
+
+        // As after a restart of the controller
+        service.resume_deliveries().await;
+
+        let attempts = tokio::time::timeout(Duration::from_secs(5), async {
+            loop {
+                let attempts = repository.attempts.lock().unwrap().clone();
+                if !attempts.is_empty() {
+                    return attempts;
+                }
+                tokio::time::sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(attempts, vec![(7, DeliveryStatus::Succeeded)]);
+    }
+}
@@ -12,7 +12,11 @@ fn read_yaml_file(file_path: &str) -> String {
 
 #[cfg(test)]
 mod tests {
+    use crate::domain::action::entities::action::{Cache, RetryCondition, RetryPolicy, Service};
+    use crate::domain::command::entities::command::Step;
+    use crate::domain::pipeline::entities::pipeline::{Artifacts, CheckoutOptions};
     use crate::parser::pipe_parser::{ManifestParser, ParsingError, PipeParser, Type};
+    use std::collections::HashMap;
 
     use super::*;
 
@@ -33,15 +37,15 @@ mod tests {
         assert_eq!(build_action.configuration_version, "node:14");
         assert_eq!(build_action.configuration_type, Type::Container);
         assert_eq!(build_action.commands.len(), 2);
-        assert_eq!(build_action.commands[0], "npm install");
-        assert_eq!(build_action.commands[1], "npm run build");
+        assert_eq!(build_action.commands[0].run, "npm install");
+        assert_eq!(build_action.commands[1].run, "npm run build");
 
         let test_action = pipeline.actions.iter().find(|a| a.name == "test").unwrap();
         assert_eq!(test_action.configuration_version, "node:14");
         assert_eq!(test_action.configuration_type, Type::Container);
         assert_eq!(test_action.commands.len(), 2);
-        assert_eq!(test_action.commands[0], "npm run test");
-        assert_eq!(test_action.commands[1], "npm run lint");
+        assert_eq!(test_action.commands[0].run, "npm run test");
+        assert_eq!(test_action.commands[1].run, "npm run lint");
 
         let deploy_action = pipeline
             .actions
@@ -51,8 +55,10 @@ mod tests {
         assert_eq!(deploy_action.configuration_version, "amazon/aws-cli");
         assert_eq!(deploy_action.configuration_type, Type::Container);
         assert_eq!(deploy_action.commands.len(), 2);
-        assert!(deploy_action.commands[0].contains("s3://my-app-bucket"));
-        assert!(deploy_action.commands[1].contains("aws cloudfront create-invalidation"));
+        assert!(deploy_action.commands[0].run.contains("s3://my-app-bucket"));
+        assert!(deploy_action.commands[1]
+            .run
+            .contains("aws cloudfront create-invalidation"));
     }
 
     #[test]
@@ -100,7 +106,8 @@ mod tests {
 
     #[test]
     fn test_yaml_parsing_special_characters_valid() {
-        let yaml_content = read_yaml_file("src/lib/tests/data/valid_special_characters_pipeline.yaml");
+        let yaml_content =
+            read_yaml_file("src/lib/tests/data/valid_special_characters_pipeline.yaml");
         let parser = PipeParser {};
         let result = parser.parse(yaml_content);
 
@@ -111,7 +118,10 @@ mod tests {
             assert_eq!(pipeline.actions[0].name, "build & test");
             assert_eq!(
                 pipeline.actions[0].commands,
-                vec!["echo \"Hello, World!\"", "npm run test"]
+                vec![
+                    Step::new("echo \"Hello, World!\"".to_string()),
+                    Step::new("npm run test".to_string())
+                ]
             );
             assert_eq!(pipeline.actions[0].configuration_type, Type::Container);
             assert_eq!(pipeline.actions[0].configuration_version, "node:14");
@@ -131,7 +141,8 @@ mod tests {
 
     #[test]
     fn test_inconsistent_command_indentation() {
-        let yaml_content = read_yaml_file("src/lib/tests/data/inconsistent_command_indentation.yaml");
+        let yaml_content =
+            read_yaml_file("src/lib/tests/data/inconsistent_command_indentation.yaml");
         let parser = PipeParser {};
         let result = parser.parse(yaml_content);
 
@@ -141,4 +152,562 @@ mod tests {
             Err(ParsingError::InconsistentCommandIndentation)
         ));
     }
+
+    #[test]
+    fn test_yaml_parsing_with_needs() {
+        let yaml_content = read_yaml_file("src/lib/tests/data/needs_pipeline.yaml");
+        let parser = PipeParser {};
+        let result = parser.parse(yaml_content);
+
+        assert!(result.is_ok());
+        let pipeline = result.unwrap();
+        assert_eq!(pipeline.actions.len(), 4);
+
+        let lint_action = pipeline.actions.iter().find(|a| a.name == "lint").unwrap();
+        assert!(lint_action.needs.is_empty());
+
+        let release_action = pipeline
+            .actions
+            .iter()
+            .find(|a| a.name == "release")
+            .unwrap();
+        assert_eq!(release_action.needs, vec!["lint", "test", "docs"]);
+        assert_eq!(
+            release_action.commands,
+            vec![Step::new("cargo build --release".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_unknown_needs() {
+        let yaml_content = read_yaml_file("src/lib/tests/data/unknown_needs_pipeline.yaml");
+        let parser = PipeParser {};
+        let result = parser.parse(yaml_content);
+
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), ParsingError::UnknownDependency);
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_cyclic_needs() {
+        let yaml_content = read_yaml_file("src/lib/tests/data/cyclic_needs_pipeline.yaml");
+        let parser = PipeParser {};
+        let result = parser.parse(yaml_content);
+
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), ParsingError::DependencyCycle);
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_allow_failure() {
+        let yaml_content = read_yaml_file("src/lib/tests/data/allow_failure_pipeline.yaml");
+        let parser = PipeParser {};
+        let result = parser.parse(yaml_content);
+
+        assert!(result.is_ok());
+        let pipeline = result.unwrap();
+
+        let lint_action = pipeline.actions.iter().find(|a| a.name == "lint").unwrap();
+        assert!(lint_action.allow_failure);
+
+        let build_action = pipeline.actions.iter().find(|a| a.name == "build").unwrap();
+        assert!(!build_action.allow_failure);
+        assert_eq!(build_action.needs, vec!["lint"]);
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_invalid_allow_failure() {
+        let yaml_content =
+            read_yaml_file("src/lib/tests/data/invalid_allow_failure_pipeline.yaml");
+        let parser = PipeParser {};
+        let result = parser.parse(yaml_content);
+
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), ParsingError::InvalidAllowFailure);
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_env() {
+        let yaml_content = read_yaml_file("src/lib/tests/data/env_pipeline.yaml");
+        let parser = PipeParser {};
+        let result = parser.parse(yaml_content);
+
+        assert!(result.is_ok());
+        let pipeline = result.unwrap();
+        assert_eq!(pipeline.env.len(), 2);
+        assert_eq!(pipeline.env["RUST_LOG"], "info");
+        assert_eq!(pipeline.env["CI"], "true");
+
+        let test_action = pipeline.actions.iter().find(|a| a.name == "test").unwrap();
+        assert_eq!(test_action.env.len(), 2);
+        assert_eq!(test_action.env["RUST_LOG"], "debug");
+        assert_eq!(test_action.env["RUST_TEST_THREADS"], "1");
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_invalid_env() {
+        let yaml_content = read_yaml_file("src/lib/tests/data/invalid_env_pipeline.yaml");
+        let parser = PipeParser {};
+        let result = parser.parse(yaml_content);
+
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), ParsingError::InvalidEnvironment);
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_secret_references() {
+        let yaml_content = read_yaml_file("src/lib/tests/data/secrets_pipeline.yaml");
+        let parser = PipeParser {};
+        let result = parser.parse(yaml_content);
+
+        assert!(result.is_ok());
+        let pipeline = result.unwrap();
+        let publish_action = pipeline
+            .actions
+            .iter()
+            .find(|a| a.name == "publish")
+            .unwrap();
+        // References are kept as-is and only resolved when the action is dispatched
+        assert_eq!(
+            publish_action.env["CARGO_REGISTRY_TOKEN"],
+            "${{ secrets.CARGO_TOKEN }}"
+        );
+        assert_eq!(
+            publish_action.env["AUTH_HEADER"],
+            "Bearer ${{secrets.API_TOKEN}}"
+        );
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_invalid_secret_reference() {
+        let yaml_content =
+            read_yaml_file("src/lib/tests/data/invalid_secret_reference_pipeline.yaml");
+        let parser = PipeParser {};
+        let result = parser.parse(yaml_content);
+
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), ParsingError::InvalidSecretReference);
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_matrix() {
+        let yaml_content = read_yaml_file("src/lib/tests/data/matrix_pipeline.yaml");
+        let parser = PipeParser {};
+        let result = parser.parse(yaml_content);
+
+        assert!(result.is_ok());
+        let pipeline = result.unwrap();
+        let test_action = pipeline.actions.iter().find(|a| a.name == "test").unwrap();
+        let matrix = test_action.matrix.as_ref().unwrap();
+        assert_eq!(matrix.keys(), vec!["image", "features", "experimental"]);
+
+        let pair = |key: &str, value: &str| (key.to_string(), value.to_string());
+        assert_eq!(
+            matrix.combinations(),
+            vec![
+                vec![pair("image", "rust:1.78"), pair("features", "a")],
+                vec![
+                    pair("image", "rust:1.80"),
+                    pair("features", "a"),
+                    pair("experimental", "true")
+                ],
+                vec![
+                    pair("image", "rust:1.80"),
+                    pair("features", "b"),
+                    pair("experimental", "true")
+                ],
+                vec![pair("image", "rust:nightly"), pair("features", "a")],
+            ]
+        );
+
+        let release_action = pipeline
+            .actions
+            .iter()
+            .find(|a| a.name == "release")
+            .unwrap();
+        assert!(release_action.matrix.is_none());
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_invalid_matrix_reference() {
+        let yaml_content =
+            read_yaml_file("src/lib/tests/data/invalid_matrix_reference_pipeline.yaml");
+        let parser = PipeParser {};
+        let result = parser.parse(yaml_content);
+
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), ParsingError::InvalidMatrixReference);
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_empty_matrix() {
+        let yaml_content = read_yaml_file("src/lib/tests/data/empty_matrix_pipeline.yaml");
+        let parser = PipeParser {};
+        let result = parser.parse(yaml_content);
+
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), ParsingError::InvalidMatrix);
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_oversized_matrix() {
+        // 10 axes of 10 values are rejected before the cross product is built
+        let yaml_content = read_yaml_file("src/lib/tests/data/oversized_matrix_pipeline.yaml");
+        let parser = PipeParser {};
+        let result = parser.parse(yaml_content);
+
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), ParsingError::InvalidMatrix);
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_timeout() {
+        let yaml_content = read_yaml_file("src/lib/tests/data/timeout_pipeline.yaml");
+        let parser = PipeParser {};
+        let result = parser.parse(yaml_content);
+
+        assert!(result.is_ok());
+        let pipeline = result.unwrap();
+        assert_eq!(pipeline.timeout, Some(3600));
+        let timeout_of = |name: &str| {
+            pipeline
+                .actions
+                .iter()
+                .find(|a| a.name == name)
+                .unwrap()
+                .timeout
+        };
+        assert_eq!(timeout_of("test"), Some(900));
+        assert_eq!(timeout_of("bench"), Some(5400));
+        assert_eq!(timeout_of("lint"), None);
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_invalid_timeout() {
+        let yaml_content = read_yaml_file("src/lib/tests/data/invalid_timeout_pipeline.yaml");
+        let parser = PipeParser {};
+        let result = parser.parse(yaml_content);
+
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), ParsingError::InvalidTimeout);
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_retry() {
+        let yaml_content = read_yaml_file("src/lib/tests/data/retry_pipeline.yaml");
+        let parser = PipeParser {};
+        let result = parser.parse(yaml_content);
+
+        assert!(result.is_ok());
+        let pipeline = result.unwrap();
+        let retry_of = |name: &str| {
+            pipeline
+                .actions
+                .iter()
+                .find(|a| a.name == name)
+                .unwrap()
+                .retry
+                .clone()
+        };
+        assert_eq!(
+            retry_of("integration"),
+            Some(RetryPolicy {
+                max_attempts: 3,
+                on: vec![
+                    RetryCondition::AgentLost,
+                    RetryCondition::ExitCode(vec![137])
+                ],
+            })
+        );
+        assert_eq!(
+            retry_of("fetch"),
+            Some(RetryPolicy {
+                max_attempts: 2,
+                on: vec![RetryCondition::ExitCode(vec![1])],
+            })
+        );
+        assert_eq!(retry_of("lint"), None);
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_invalid_retry() {
+        let yaml_content = read_yaml_file("src/lib/tests/data/invalid_retry_pipeline.yaml");
+        let parser = PipeParser {};
+        let result = parser.parse(yaml_content);
+
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), ParsingError::InvalidRetry);
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_condition() {
+        let yaml_content = read_yaml_file("src/lib/tests/data/condition_pipeline.yaml");
+        let parser = PipeParser {};
+        let result = parser.parse(yaml_content);
+
+        assert!(result.is_ok());
+        let pipeline = result.unwrap();
+        let condition_of = |name: &str| {
+            pipeline
+                .actions
+                .iter()
+                .find(|a| a.name == name)
+                .unwrap()
+                .condition
+                .clone()
+        };
+        assert_eq!(condition_of("deploy"), Some(r#"tag =~ "^v""#.to_string()));
+        assert_eq!(
+            condition_of("preview"),
+            Some(r#"branch == "main" && event == "push""#.to_string())
+        );
+        assert_eq!(condition_of("build"), None);
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_invalid_condition() {
+        let yaml_content = read_yaml_file("src/lib/tests/data/invalid_condition_pipeline.yaml");
+        let parser = PipeParser {};
+        let result = parser.parse(yaml_content);
+
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), ParsingError::InvalidCondition);
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_artifacts() {
+        let yaml_content = read_yaml_file("src/lib/tests/data/artifacts_pipeline.yaml");
+        let parser = PipeParser {};
+        let result = parser.parse(yaml_content);
+
+        assert!(result.is_ok());
+        let pipeline = result.unwrap();
+        let artifacts_of = |name: &str| {
+            pipeline
+                .actions
+                .iter()
+                .find(|a| a.name == name)
+                .unwrap()
+                .artifacts
+                .clone()
+        };
+        assert_eq!(
+            artifacts_of("build"),
+            Some(Artifacts {
+                paths: vec!["target/release/sealci".to_string(), "*.log".to_string()],
+            })
+        );
+        assert_eq!(artifacts_of("test"), None);
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_invalid_artifacts() {
+        let yaml_content = read_yaml_file("src/lib/tests/data/invalid_artifacts_pipeline.yaml");
+        let parser = PipeParser {};
+        let result = parser.parse(yaml_content);
+
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), ParsingError::InvalidArtifacts);
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_cache() {
+        let yaml_content = read_yaml_file("src/lib/tests/data/cache_pipeline.yaml");
+        let parser = PipeParser {};
+        let result = parser.parse(yaml_content);
+
+        assert!(result.is_ok());
+        let pipeline = result.unwrap();
+        let cache_of = |name: &str| {
+            pipeline
+                .actions
+                .iter()
+                .find(|a| a.name == name)
+                .unwrap()
+                .cache
+                .clone()
+        };
+        assert_eq!(
+            cache_of("build"),
+            Some(Cache {
+                key: "cargo-${{ matrix.image }}-${{ hashFiles('Cargo.lock') }}".to_string(),
+                paths: vec!["~/.cargo/registry".to_string(), "target".to_string()],
+            })
+        );
+        assert_eq!(cache_of("lint"), None);
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_invalid_cache() {
+        let yaml_content = read_yaml_file("src/lib/tests/data/invalid_cache_pipeline.yaml");
+        let parser = PipeParser {};
+        let result = parser.parse(yaml_content);
+
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), ParsingError::InvalidCache);
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_services() {
+        let yaml_content = read_yaml_file("src/lib/tests/data/services_pipeline.yaml");
+        let parser = PipeParser {};
+        let result = parser.parse(yaml_content);
+
+        assert!(result.is_ok());
+        let pipeline = result.unwrap();
+        let services = &pipeline.actions[0].services;
+        assert_eq!(services.len(), 2);
+        assert_eq!(
+            services["db"],
+            Service {
+                image: "postgres:16".to_string(),
+                env: HashMap::from([
+                    ("POSTGRES_USER".to_string(), "ci".to_string()),
+                    (
+                        "POSTGRES_PASSWORD".to_string(),
+                        "${{ secrets.DB_PASSWORD }}".to_string()
+                    ),
+                ]),
+                healthcheck: Some("pg_isready -U ci".to_string()),
+            }
+        );
+        assert_eq!(
+            services["redis"],
+            Service {
+                image: "redis:7".to_string(),
+                env: HashMap::new(),
+                healthcheck: None,
+            }
+        );
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_invalid_service_name() {
+        let yaml_content = read_yaml_file("src/lib/tests/data/invalid_services_pipeline.yaml");
+        let parser = PipeParser {};
+        let result = parser.parse(yaml_content);
+
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), ParsingError::InvalidServices);
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_shell_and_multi_line_commands() {
+        let yaml_content = read_yaml_file("src/lib/tests/data/shell_pipeline.yaml");
+        let parser = PipeParser {};
+        let result = parser.parse(yaml_content);
+
+        assert!(result.is_ok());
+        let pipeline = result.unwrap();
+        assert_eq!(
+            pipeline.shell,
+            Some(vec![
+                "bash".to_string(),
+                "-eo".to_string(),
+                "pipefail".to_string(),
+                "-c".to_string()
+            ])
+        );
+        let action = |name: &str| pipeline.actions.iter().find(|a| a.name == name).unwrap();
+        assert_eq!(
+            action("build").commands,
+            vec![
+                Step::new(
+                    "cargo build \\\n  --release\nls target/release | grep sealci > built.txt\n"
+                        .to_string()
+                ),
+                Step::new("echo \"done\" && cat built.txt".to_string()),
+            ]
+        );
+        assert_eq!(action("build").shell, None);
+        assert_eq!(
+            action("lint").shell,
+            Some(vec![
+                "/bin/ash".to_string(),
+                "-e".to_string(),
+                "-c".to_string()
+            ])
+        );
+        assert_eq!(
+            action("lint").commands,
+            vec![Step::new("echo linting".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_invalid_shell() {
+        let yaml_content = read_yaml_file("src/lib/tests/data/invalid_shell_pipeline.yaml");
+        let parser = PipeParser {};
+        let result = parser.parse(yaml_content);
+
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), ParsingError::InvalidShell);
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_step_objects() {
+        let yaml_content = read_yaml_file("src/lib/tests/data/steps_pipeline.yaml");
+        let parser = PipeParser {};
+        let pipeline = parser.parse(yaml_content).unwrap();
+
+        let commands = &pipeline.actions[0].commands;
+        assert_eq!(commands.len(), 3);
+        assert_eq!(commands[0], Step::new("cargo build".to_string()));
+        assert_eq!(
+            commands[1],
+            Step {
+                name: Some("Unit tests".to_string()),
+                run: "cargo test".to_string(),
+                working_directory: Some("core".to_string()),
+                env: HashMap::from([("RUST_LOG".to_string(), "debug".to_string())]),
+                continue_on_error: false,
+            }
+        );
+        assert_eq!(commands[2].name.as_deref(), Some("Lint"));
+        assert!(commands[2].continue_on_error);
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_step_without_run() {
+        let yaml_content = read_yaml_file("src/lib/tests/data/invalid_steps_pipeline.yaml");
+        let parser = PipeParser {};
+        let result = parser.parse(yaml_content);
+
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), ParsingError::InvalidStep);
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_checkout() {
+        let yaml_content = read_yaml_file("src/lib/tests/data/checkout_pipeline.yaml");
+        let parser = PipeParser {};
+        let pipeline = parser.parse(yaml_content).unwrap();
+
+        assert_eq!(
+            pipeline.checkout,
+            CheckoutOptions {
+                depth: 0,
+                submodules: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_yaml_parsing_without_checkout_is_shallow() {
+        let yaml_content = read_yaml_file("src/lib/tests/data/steps_pipeline.yaml");
+        let parser = PipeParser {};
+        let pipeline = parser.parse(yaml_content).unwrap();
+
+        assert_eq!(pipeline.checkout, CheckoutOptions::default());
+    }
+
+    #[test]
+    fn test_yaml_parsing_with_invalid_checkout() {
+        let yaml_content = read_yaml_file("src/lib/tests/data/invalid_checkout_pipeline.yaml");
+        let parser = PipeParser {};
+        let result = parser.parse(yaml_content);
+
+        assert!(result.is_err());
+        assert_eq!(result.unwrap_err(), ParsingError::InvalidCheckout);
+    }
 }
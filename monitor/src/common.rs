@@ -43,6 +43,13 @@ pub struct UpdateConfigForm {
 #[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
 pub struct GitTag {
     pub name: String,
+    #[serde(default)]
+    pub commit: Option<GitTagCommit>,
+}
+
+#[derive(serde::Serialize, serde::Deserialize, Debug, Clone, Default)]
+pub struct GitTagCommit {
+    pub sha: String,
 }
 
 impl PartialEq for GitTag {
@@ -50,4 +57,3 @@ impl PartialEq for GitTag {
         self.name == other.name
     }
 }
-
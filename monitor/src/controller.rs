@@ -4,6 +4,22 @@ use std::fs::File;
 use std::io::{Read, Seek, SeekFrom};
 use tracing::{debug, info};
 
+/// What triggered a pipeline, sent along with its manifest so that the
+/// controller can evaluate the `if` conditions of its actions.
+#[derive(Debug, Clone, Default)]
+pub struct TriggerContext {
+    /// `push`, `pull_request` or `tag`
+    pub event: String,
+    pub branch: Option<String>,
+    pub tag: Option<String>,
+    pub message: Option<String>,
+    pub commit_sha: Option<String>,
+    /// Full name of the git reference, e.g. `refs/heads/main`
+    pub git_ref: Option<String>,
+    pub pr_number: Option<u64>,
+    pub author: Option<String>,
+}
+
 pub struct ControllerClient {
     controller_url: String,
 }
@@ -17,6 +33,7 @@ impl ControllerClient {
         &self,
         repo_url: &str,
         mut actions_file: &File,
+        trigger: &TriggerContext,
     ) -> Result<(), Box<dyn std::error::Error>> {
         let mut file_ref = actions_file;
         if let Err(_) = actions_file.seek(SeekFrom::Start(0)) {
@@ -36,9 +53,25 @@ impl ControllerClient {
         let file_part: Part = Part::bytes(buffer);
 
         // Créer le formulaire multipart et ajouter les parties
-        let form: Form = Form::new()
+        let mut form: Form = Form::new()
             .text("repo_url", repo_url.to_string())
+            .text("event", trigger.event.clone())
             .part("body", file_part);
+        for (name, value) in [
+            ("branch", &trigger.branch),
+            ("tag", &trigger.tag),
+            ("message", &trigger.message),
+            ("commit_sha", &trigger.commit_sha),
+            ("ref", &trigger.git_ref),
+            ("author", &trigger.author),
+        ] {
+            if let Some(value) = value {
+                form = form.text(name, value.clone());
+            }
+        }
+        if let Some(pr_number) = trigger.pr_number {
+            form = form.text("pr_number", pr_number.to_string());
+        }
 
         debug!("Sending pipeline to controller {}", self.controller_url);
 
@@ -1,6 +1,9 @@
 use crate::common::GitEvent;
 use crate::github::GitHubClient;
-use crate::{controller::ControllerClient, error::Error};
+use crate::{
+    controller::{ControllerClient, TriggerContext},
+    error::Error,
+};
 use std::fs::File;
 use std::io::{Read, Seek, SeekFrom};
 use std::sync::{Arc, RwLock};
@@ -72,6 +75,15 @@ impl Listener {
                 None,
             )
             .await?;
+        // Commits are fetched from the default branch
+        let branch = self
+            .github_client
+            .get_default_branch(
+                self.repo_owner.clone(),
+                self.repo_name.clone(),
+                self.github_token.clone(),
+            )
+            .await?;
 
         let repo_owner = self.repo_owner.clone();
         let repo_name = self.repo_name.clone();
@@ -96,15 +108,25 @@ impl Listener {
                     .await
                 {
                     Ok(current_commit) => {
-                        if last_commit != current_commit {
+                        if last_commit.sha != current_commit.sha {
                             info!(
                                 "{}/{} - New commit found: {}",
-                                repo_owner, repo_name, current_commit
+                                repo_owner, repo_name, current_commit.sha
                             );
+                            let trigger = TriggerContext {
+                                event: "push".to_string(),
+                                branch: Some(branch.clone()),
+                                tag: None,
+                                message: Some(current_commit.message.clone()),
+                                commit_sha: Some(current_commit.sha.clone()),
+                                git_ref: Some(format!("refs/heads/{}", branch)),
+                                pr_number: None,
+                                author: current_commit.author.clone(),
+                            };
                             last_commit = current_commit;
 
                             if let Err(e) = controller_client
-                                .send_to_controller(&repo_url, file.as_ref())
+                                .send_to_controller(&repo_url, file.as_ref(), &trigger)
                                 .await
                             {
                                 error!("Error sending to controller: {}", e);
@@ -165,8 +187,17 @@ impl Listener {
                                     repo_owner, repo_name, last_tag_pushed.title
                                 );
                                 last_pr = last_tag_pushed.clone(); // Update the last PR ID
+                                let trigger = TriggerContext {
+                                    event: "pull_request".to_string(),
+                                    branch: Some(last_pr.head.ref_name.clone()),
+                                    commit_sha: Some(last_pr.head.sha.clone()),
+                                    git_ref: Some(format!("refs/pull/{}/head", last_pr.number)),
+                                    pr_number: Some(last_pr.number),
+                                    author: last_pr.user.as_ref().map(|user| user.login.clone()),
+                                    ..Default::default()
+                                };
                                 if let Err(e) = controller_client
-                                    .send_to_controller(&repo_url, file.as_ref())
+                                    .send_to_controller(&repo_url, file.as_ref(), &trigger)
                                     .await
                                 {
                                     error!("Error sending to controller: {}", e);
@@ -236,8 +267,18 @@ impl Listener {
                                 }
 
                                 // Also send the pipeline as before
+                                let trigger = TriggerContext {
+                                    event: "tag".to_string(),
+                                    tag: Some(last_tag_pushed.name.clone()),
+                                    commit_sha: last_tag_pushed
+                                        .commit
+                                        .as_ref()
+                                        .map(|commit| commit.sha.clone()),
+                                    git_ref: Some(format!("refs/tags/{}", last_tag_pushed.name)),
+                                    ..Default::default()
+                                };
                                 if let Err(e) = controller_client
-                                    .send_to_controller(&repo_url, file.as_ref())
+                                    .send_to_controller(&repo_url, file.as_ref(), &trigger)
                                     .await
                                 {
                                     error!("Error sending to controller: {}", e);
@@ -1,4 +1,4 @@
-use models::PullRequest;
+use models::{Commit, PullRequest};
 
 use crate::{common::GitTag, constants::GITHUB_API_URL, error::Error};
 
@@ -22,7 +22,7 @@ impl GitHubClient {
         repo_name: String,
         token: String,
         branch: Option<String>,
-    ) -> Result<String, Error> {
+    ) -> Result<Commit, Error> {
         let url = format!("{}/{}/{}/commits", GITHUB_API_URL, repo_owner, repo_name);
 
         let url = if let Some(branch) = branch {
@@ -35,12 +35,39 @@ impl GitHubClient {
             .client
             .get::<Vec<serde_json::Value>>(url, token)
             .await?;
-        let latest_commit = commits
-            .get(0)
-            .and_then(|commit| commit["sha"].as_str())
-            .ok_or(Error::NoCommitFound)?;
+        let latest_commit = commits.get(0).ok_or(Error::NoCommitFound)?;
+        let sha = latest_commit["sha"].as_str().ok_or(Error::NoCommitFound)?;
+        let message = latest_commit["commit"]["message"]
+            .as_str()
+            .unwrap_or_default();
+
+        // The GitHub account of the author when there is one, else the name
+        // set in the commit
+        let author = latest_commit["author"]["login"]
+            .as_str()
+            .or(latest_commit["commit"]["author"]["name"].as_str())
+            .map(String::from);
+
+        Ok(Commit {
+            sha: sha.to_string(),
+            message: message.to_string(),
+            author,
+        })
+    }
+
+    pub async fn get_default_branch(
+        &self,
+        repo_owner: String,
+        repo_name: String,
+        token: String,
+    ) -> Result<String, Error> {
+        let url = format!("{}/{}/{}", GITHUB_API_URL, repo_owner, repo_name);
 
-        Ok(latest_commit.to_string())
+        let repository = self.client.get::<serde_json::Value>(url, token).await?;
+        repository["default_branch"]
+            .as_str()
+            .map(String::from)
+            .ok_or(Error::Error("No default branch found".to_string()))
     }
 
     pub async fn get_pull_requests(
@@ -57,18 +84,18 @@ impl GitHubClient {
         let pull_requests: Vec<PullRequest> = self.client.get(url, token).await?;
         Ok(pull_requests)
     }
-    
-        pub async fn get_tags(
-            &self,
-            repo_owner: String,
-            repo_name: String,
-            token: String,
-        ) -> Result<Vec<GitTag>, Error> {
-            let url = format!(
-                "https://api.github.com/repos/{}/{}/tags",
-                repo_owner, repo_name
-            );
-            let tags: Vec<GitTag> = self.client.get(url, token).await?;
-            Ok(tags)
-        }
+
+    pub async fn get_tags(
+        &self,
+        repo_owner: String,
+        repo_name: String,
+        token: String,
+    ) -> Result<Vec<GitTag>, Error> {
+        let url = format!(
+            "https://api.github.com/repos/{}/{}/tags",
+            repo_owner, repo_name
+        );
+        let tags: Vec<GitTag> = self.client.get(url, token).await?;
+        Ok(tags)
+    }
 }
@@ -1,6 +1,30 @@
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct PullRequest {
     pub id: u64,
+    pub number: u64,
     pub title: String,
+    pub head: PullRequestHead,
+    pub user: Option<GitHubUser>,
     // commit_url: String,
 }
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct PullRequestHead {
+    /// Name of the branch the pull request comes from
+    #[serde(rename = "ref")]
+    pub ref_name: String,
+    /// Last commit of the pull request
+    pub sha: String,
+}
+
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct GitHubUser {
+    pub login: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Commit {
+    pub sha: String,
+    pub message: String,
+    pub author: Option<String>,
+}
@@ -1,10 +1,22 @@
-use crate::{bucket::BucketClient, compress::CompressClient, git::GitClient, sign::ReleaseSigner};
-use tracing::info;
+use crate::{
+    bucket::BucketClient,
+    compress::CompressClient,
+    git::{GitClient, GitCredentials},
+    sign::ReleaseSigner,
+};
 use tonic::async_trait;
+use tracing::info;
 
 #[async_trait]
-pub trait ReleaseAgentCore<S: ReleaseSigner, B: BucketClient, G: GitClient, C: CompressClient>: Clone + Send + Sync {
-    async fn create_release(&self, revision: &str, repository_url: &str) -> Result<Release, ReleaseAgentError>;
+pub trait ReleaseAgentCore<S: ReleaseSigner, B: BucketClient, G: GitClient, C: CompressClient>:
+    Clone + Send + Sync
+{
+    async fn create_release(
+        &self,
+        revision: &str,
+        repository_url: &str,
+        credentials: Option<GitCredentials>,
+    ) -> Result<Release, ReleaseAgentError>;
     async fn get_root_public_key(&self) -> Result<PublicKey, ReleaseAgentError>;
 }
 
@@ -25,7 +37,6 @@ impl<S: ReleaseSigner, B: BucketClient, G: GitClient, C: CompressClient> Release
             compress_client,
         }
     }
-
 }
 
 #[derive(Debug)]
@@ -41,8 +52,15 @@ pub struct PublicKey {
 }
 
 #[async_trait]
-impl<S: ReleaseSigner, B: BucketClient, G: GitClient, C: CompressClient> ReleaseAgentCore<S,B,G,C> for ReleaseAgent<S, B, G, C> {
-    async fn create_release(&self, revision: &str, repository_url: &str) -> Result<Release, ReleaseAgentError> {
+impl<S: ReleaseSigner, B: BucketClient, G: GitClient, C: CompressClient>
+    ReleaseAgentCore<S, B, G, C> for ReleaseAgent<S, B, G, C>
+{
+    async fn create_release(
+        &self,
+        revision: &str,
+        repository_url: &str,
+        credentials: Option<GitCredentials>,
+    ) -> Result<Release, ReleaseAgentError> {
         //get last two parts separated by '/'
         let repo_owner = repository_url.split('/').nth_back(1).unwrap();
         let repo_name = repository_url.split('/').nth_back(0).unwrap();
@@ -50,42 +68,57 @@ impl<S: ReleaseSigner, B: BucketClient, G: GitClient, C: CompressClient> Release
         info!("Creating release for repository '{repo_name}' owned by '{repo_owner}'.");
         let codebase = self
             .git_client
-            .download_release(repository_url.to_string(), revision.to_string())
+            .download_release(
+                repository_url.to_string(),
+                revision.to_string(),
+                credentials,
+            )
             .await
             .inspect_err(|e| {
                 tracing::error!("Failed to download release: {}", e);
             })?;
-        let (_, compressed_path) =
-            self.compress_client
-                .compress(codebase.clone())
-                .await
-                .inspect_err(|e| {
-                    tracing::error!("Failed to compress codebase: {}", e);
-                })?;
-        let (public_key, signed_codebase) = self .signer
+        let (_, compressed_path) = self
+            .compress_client
+            .compress(codebase.clone())
+            .await
+            .inspect_err(|e| {
+                tracing::error!("Failed to compress codebase: {}", e);
+            })?;
+        let (public_key, signed_codebase) = self
+            .signer
             .sign_release(compressed_path.clone())
             .inspect_err(|e| {
                 tracing::error!("Failed to sign release: {}", e);
             })?;
 
-        let release =format!("{repo_owner}/{repo_name}/{revision}");
+        let release = format!("{repo_owner}/{repo_name}/{revision}");
         self.bucket
-            .put_release(release.clone(), compressed_path.clone(), signed_codebase.clone())
+            .put_release(
+                release.clone(),
+                compressed_path.clone(),
+                signed_codebase.clone(),
+            )
             .await
             .inspect_err(|e| {
                 tracing::error!("Failed to upload release to bucket: {}", e);
             })?;
 
         // clean up
-        self.compress_client.clean_compressed(compressed_path).inspect_err(|e| {
-            tracing::error!("Failed to clean up compressed file: {}", e);
-        })?;
-        self.git_client.clean_release(codebase.clone()).inspect_err(|e| {
-            tracing::error!("Failed to clean up codebase: {}", e);
-        })?;
-        self.signer.clean_release(signed_codebase).inspect_err(|e| {
-            tracing::error!("Failed to clean up signature: {}", e);
-        })?;
+        self.compress_client
+            .clean_compressed(compressed_path)
+            .inspect_err(|e| {
+                tracing::error!("Failed to clean up compressed file: {}", e);
+            })?;
+        self.git_client
+            .clean_release(codebase.clone())
+            .inspect_err(|e| {
+                tracing::error!("Failed to clean up codebase: {}", e);
+            })?;
+        self.signer
+            .clean_release(signed_codebase)
+            .inspect_err(|e| {
+                tracing::error!("Failed to clean up signature: {}", e);
+            })?;
 
         Ok(Release {
             revision: release,
@@ -98,7 +131,6 @@ impl<S: ReleaseSigner, B: BucketClient, G: GitClient, C: CompressClient> Release
     }
 }
 
-
 #[derive(Debug)]
 pub enum ReleaseAgentError {
     BucketNotAvailable,
@@ -113,7 +145,7 @@ pub enum ReleaseAgentError {
     KeyDecryptionError,
     GitTagNotFound,
     KeyGenerationError,
-    AttachKeyError, 
+    AttachKeyError,
     TransportError(tonic::transport::Error), // add more errors here
 }
 
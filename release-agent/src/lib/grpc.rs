@@ -4,7 +4,10 @@ use release_agent_grpc::release_agent_server::ReleaseAgent;
 use tonic::{Request, Response, Status};
 
 use crate::{
-    bucket::BucketClient, compress::CompressClient, core::ReleaseAgentCore, git::GitClient,
+    bucket::BucketClient,
+    compress::CompressClient,
+    core::ReleaseAgentCore,
+    git::{GitClient, GitCredentials},
     sign::ReleaseSigner,
 };
 
@@ -42,9 +45,31 @@ where
     ) -> Result<Response<release_agent_grpc::CreateReleaseResponse>, Status> {
         let request = request.into_inner().clone();
         let repository_url = request.clone().repo_url;
-        let release_path=  format!("releases/{}/{}", repository_url.trim_start_matches("https://github.com/"), request.revision);
+        let release_path = format!(
+            "releases/{}/{}",
+            repository_url.trim_start_matches("https://github.com/"),
+            request.revision
+        );
         let revision = request.revision;
-        match self.core.create_release(&revision, &repository_url).await {
+        let credentials = request.credentials.and_then(|credentials| {
+            if !credentials.ssh_private_key.is_empty() {
+                Some(GitCredentials::SshKey {
+                    private_key: credentials.ssh_private_key,
+                })
+            } else if !credentials.token.is_empty() {
+                Some(GitCredentials::HttpsToken {
+                    username: credentials.username,
+                    token: credentials.token,
+                })
+            } else {
+                None
+            }
+        });
+        match self
+            .core
+            .create_release(&revision, &repository_url, credentials)
+            .await
+        {
             Ok(release) => {
                 let public_key = release_agent_grpc::PublicKey {
                     key_data: release.public_key.key_data,
@@ -115,7 +140,7 @@ where
 {
     pub fn new(core: Arc<R>, signer: S, bucket: B, git_client: G, compress_client: C) -> Self {
         Self {
-            core,            
+            core,
             _signer: signer,
             _bucket: bucket,
             _git_client: git_client,
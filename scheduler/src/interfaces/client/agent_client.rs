@@ -9,25 +9,43 @@ use tonic::transport::Channel;
 use tonic::Request;
 use tracing::debug;
 
-pub(crate) async fn execution_action(
-    action: Action, agent_address: String
-) -> Result<tonic::Streaming<proto::ActionResponseStream>, Error> {
-    debug!("[Scheduler]: Received action: {:?}", action);
-
-    debug!("[Scheduler]: Attempting to connect to agent at address: {}", agent_address);
-
+/// Connects to the agent listening at `agent_address`.
+async fn connect(agent_address: &str) -> Result<ActionClient<Channel>, Error> {
     // Build the gRPC channel using the agent's address, parsed to http::uri::Uri as required by tonic.
     let channel = Channel::builder(
         agent_address
             .parse::<http::uri::Uri>()
-            .map_err(|e| Error::GrpcClientError(tonic::Status::internal(e.to_string())))?
+            .map_err(|e| Error::GrpcClientError(tonic::Status::internal(e.to_string())))?,
     )
     .connect()
     .await
     .map_err(|e| Error::GrpcClientError(tonic::Status::internal(e.to_string())))?;
-    let mut client = ActionClient::new(channel);
+    Ok(ActionClient::new(channel))
+}
+
+pub(crate) async fn execution_action(
+    action: Action,
+    agent_address: String,
+) -> Result<tonic::Streaming<proto::ActionResponseStream>, Error> {
+    // The env of the action and of its services holds the resolved secrets,
+    // so only what identifies the action is logged
+    debug!(
+        "[Scheduler]: Received action {} with image {}",
+        action.get_action_id(),
+        action.get_container_image()
+    );
 
-    debug!("[Scheduler]: Creating ActionRequest for action ID: {}", action.get_action_id());
+    debug!(
+        "[Scheduler]: Attempting to connect to agent at address: {}",
+        agent_address
+    );
+
+    let mut client = connect(&agent_address).await?;
+
+    debug!(
+        "[Scheduler]: Creating ActionRequest for action ID: {}",
+        action.get_action_id()
+    );
 
     let request = Request::new(proto::ActionRequest {
         action_id: action.get_action_id(),
@@ -35,14 +53,83 @@ pub(crate) async fn execution_action(
             r#type: action.get_runner_type(),
             container_image: Some(String::from(action.get_container_image())),
         }),
-        commands: action.get_commands().iter().map(|comm: &String| String::from(comm)).collect(),
+        commands: action
+            .get_commands()
+            .iter()
+            .map(|comm: &String| String::from(comm))
+            .collect(),
         repo_url: action.get_repo_url().clone(),
+        env: action.get_env().clone(),
+        timeout_seconds: action.get_timeout_seconds(),
+        artifacts: action.get_artifacts().to_vec(),
+        cache: action.get_cache().map(|cache| proto::Cache {
+            key: cache.key.clone(),
+            paths: cache.paths.clone(),
+        }),
+        services: action
+            .get_services()
+            .iter()
+            .map(|service| proto::Service {
+                name: service.name.clone(),
+                image: service.image.clone(),
+                env: service.env.clone(),
+                healthcheck: service.healthcheck.clone(),
+            })
+            .collect(),
+        shell: action.get_shell().to_vec(),
+        steps: action
+            .get_steps()
+            .iter()
+            .map(|step| proto::Step {
+                run: step.run.clone(),
+                name: step.name.clone(),
+                working_directory: step.working_directory.clone(),
+                env: step.env.clone(),
+                continue_on_error: step.continue_on_error,
+            })
+            .collect(),
+        checkout: action.get_checkout().map(|checkout| proto::Checkout {
+            revision: checkout.revision.clone(),
+            r#ref: checkout.r#ref.clone(),
+            depth: checkout.depth,
+            submodules: checkout.submodules,
+            credentials: checkout
+                .credentials
+                .as_ref()
+                .map(|credentials| proto::Credentials {
+                    ssh_private_key: credentials.ssh_private_key.clone(),
+                    username: credentials.username.clone(),
+                    token: credentials.token.clone(),
+                }),
+        }),
     });
 
-    debug!("[Scheduler]: Sending ActionRequest: {:?}", request);
+    debug!(
+        "[Scheduler]: Sending ActionRequest for action ID: {}",
+        request.get_ref().action_id
+    );
 
     // The response stream is returned to the caller function for further processing. (controller_interface.rs)
-    let response_stream = client.execution_action(request).await
-        .map_err(|e| Error::GrpcClientError(tonic::Status::internal(e.to_string())))?.into_inner();
+    let response_stream = client
+        .execution_action(request)
+        .await
+        .map_err(|e| Error::GrpcClientError(tonic::Status::internal(e.to_string())))?
+        .into_inner();
     Ok(response_stream)
 }
+
+/// Asks the agent running an action to stop it. The agent then reports the
+/// action as cancelled on its response stream.
+pub(crate) async fn cancel_action(action_id: u32, agent_address: String) -> Result<(), Error> {
+    debug!(
+        "[Scheduler]: Cancelling action {} on agent at address: {}",
+        action_id, agent_address
+    );
+
+    let mut client = connect(&agent_address).await?;
+    client
+        .cancel_action(Request::new(proto::CancelActionRequest { action_id }))
+        .await
+        .map_err(Error::GrpcClientError)?;
+    Ok(())
+}
@@ -5,23 +5,30 @@ use crate::interfaces::client::agent_client;
 use crate::logic::action_queue_logic::Action;
 use crate::logic::agent_pool_logic::AgentPool;
 
+use crate::proto::actions as agent_proto;
 use crate::proto::scheduler as proto;
 use proto::controller_server::Controller;
 
-use tracing::{info, warn};
+use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::mpsc;
 use tokio::sync::Mutex;
 use tokio_stream::wrappers::UnboundedReceiverStream;
+use tracing::{info, warn};
 
 #[derive(Clone)]
 pub struct ControllerService {
     agent_pool: Arc<Mutex<AgentPool>>,
+    /// Address of the agent running each action, to cancel it
+    running_actions: Arc<Mutex<HashMap<u32, String>>>,
 }
 
 impl ControllerService {
     pub fn new(agent_pool: Arc<Mutex<AgentPool>>) -> Self {
-        Self { agent_pool }
+        Self {
+            agent_pool,
+            running_actions: Arc::new(Mutex::new(HashMap::new())),
+        }
     }
 }
 
@@ -38,9 +45,9 @@ impl Controller for ControllerService {
         let action_request = request.into_inner();
 
         // Validate ActionRequest fields
-        let (runner_type, container_image) = self.validate_action_request(&action_request).map_err(|e| {
-            Error::GrpcRequestError(tonic::Status::invalid_argument(e.to_string()))
-        })?;
+        let (runner_type, container_image) = self
+            .validate_action_request(&action_request)
+            .map_err(|e| Error::GrpcRequestError(tonic::Status::invalid_argument(e.to_string())))?;
 
         info!(
             "[Scheduler]: Received Action request: {}, Runner type: {}",
@@ -55,7 +62,8 @@ impl Controller for ControllerService {
             None => {
                 warn!("[Scheduler]: No Agents available to execute Action");
                 // Send back an error response now, and close the stream.
-                let (tx, rx) = mpsc::unbounded_channel::<Result<proto::ActionResponse, tonic::Status>>();
+                let (tx, rx) =
+                    mpsc::unbounded_channel::<Result<proto::ActionResponse, tonic::Status>>();
                 let error_response = proto::ActionResponse {
                     action_id: action_request.action_id,
                     log: "No agents available".to_string(),
@@ -63,6 +71,12 @@ impl Controller for ControllerService {
                         completion: proto::ActionStatus::Error.into(),
                         exit_code: None,
                     }),
+                    artifact: None,
+                    step_started: None,
+                    step_finished: None,
+                    timestamp: 0,
+                    stream: proto::LogStream::System.into(),
+                    step_index: None,
                 };
                 tx.send(Ok(error_response)).unwrap_or_default(); // Send Ok or Err back? need to say schedule_action errored!!
                 return Ok(tonic::Response::new(UnboundedReceiverStream::new(rx)));
@@ -83,12 +97,27 @@ impl Controller for ControllerService {
             },
             action_request.commands,
             action_request.repo_url,
+            action_request.env,
+            action_request.timeout_seconds,
+            action_request.artifacts,
+            action_request.cache,
+            action_request.services,
+            action_request.shell,
+            action_request.steps,
+            action_request.checkout,
         );
 
         // Use an unbounded channel to create the response stream
         let (tx, rx) = mpsc::unbounded_channel();
         // The transmitter is passed into the spawned task to send the response back to the client.
 
+        let action_id = action_request.action_id;
+        let running_actions = self.running_actions.clone();
+        running_actions
+            .lock()
+            .await
+            .insert(action_id, agent_ip.clone());
+
         // Spawn an async task to handle action execution
         tokio::spawn(async move {
             // Send the action to the agent and forward the response/transfer the logs
@@ -103,7 +132,14 @@ impl Controller for ControllerService {
                         match response.result {
                             Some(result) => {
                                 println!("Received a response with a result {:?}", result);
+                                let timed_out =
+                                    result.completion == agent_proto::ActionStatus::TimedOut as i32;
+                                let cancelled = result.completion
+                                    == agent_proto::ActionStatus::Cancelled as i32;
                                 let completion = match result.exit_code {
+                                    // The agent gave up on the action: no exit code to rely on
+                                    _ if timed_out => proto::ActionStatus::TimedOut.into(),
+                                    _ if cancelled => proto::ActionStatus::Cancelled.into(),
                                     Some(exit_code) => {
                                         if exit_code == 0 {
                                             proto::ActionStatus::Completed.into()
@@ -120,6 +156,12 @@ impl Controller for ControllerService {
                                         completion: completion.into(),
                                         exit_code: result.exit_code,
                                     }),
+                                    artifact: None,
+                                    step_started: None,
+                                    step_finished: None,
+                                    timestamp: response.timestamp,
+                                    stream: response.stream,
+                                    step_index: response.step_index,
                                 };
 
                                 if tx.send(Ok(action_response)).is_err() {
@@ -127,6 +169,46 @@ impl Controller for ControllerService {
                                     break;
                                 }
                             }
+                            // Chunks of the artifacts archive and step boundaries are relayed as they come
+                            None if response.artifact.is_some()
+                                || response.step_started.is_some()
+                                || response.step_finished.is_some() =>
+                            {
+                                let relayed_response = proto::ActionResponse {
+                                    action_id: response.action_id,
+                                    log: response.log,
+                                    result: None,
+                                    artifact: response.artifact.map(|artifact| {
+                                        proto::ArtifactChunk {
+                                            data: artifact.data,
+                                            complete: artifact.complete,
+                                        }
+                                    }),
+                                    step_started: response.step_started.map(|step| {
+                                        proto::StepStarted {
+                                            index: step.index,
+                                            name: step.name,
+                                            started_at: step.started_at,
+                                        }
+                                    }),
+                                    step_finished: response.step_finished.map(|step| {
+                                        proto::StepFinished {
+                                            index: step.index,
+                                            name: step.name,
+                                            exit_code: step.exit_code,
+                                            started_at: step.started_at,
+                                            finished_at: step.finished_at,
+                                        }
+                                    }),
+                                    timestamp: response.timestamp,
+                                    stream: response.stream,
+                                    step_index: response.step_index,
+                                };
+                                if tx.send(Ok(relayed_response)).is_err() {
+                                    warn!("[Scheduler]: Failed to send action response");
+                                    break;
+                                }
+                            }
                             None => {
                                 warn!("[Scheduler]: Received a response with no result");
                             }
@@ -142,10 +224,17 @@ impl Controller for ControllerService {
                             completion: proto::ActionStatus::Error.into(),
                             exit_code: None,
                         }),
+                        artifact: None,
+                        step_started: None,
+                        step_finished: None,
+                        timestamp: 0,
+                        stream: proto::LogStream::System.into(),
+                        step_index: None,
                     };
                     let _ = tx.send(Ok(error_response));
                 }
             }
+            running_actions.lock().await.remove(&action_id);
         });
 
         // Now outside the spawned task, the response stream is created and the receiver side of the channel is returned to the client/calling service.
@@ -153,6 +242,29 @@ impl Controller for ControllerService {
         let response_stream = UnboundedReceiverStream::new(rx);
         Ok(tonic::Response::new(response_stream))
     }
+
+    async fn cancel_action(
+        &self,
+        request: tonic::Request<proto::CancelActionRequest>,
+    ) -> Result<tonic::Response<proto::CancelActionResponse>, tonic::Status> {
+        let action_id = request.into_inner().action_id;
+        let agent_ip = self
+            .running_actions
+            .lock()
+            .await
+            .get(&action_id)
+            .cloned()
+            .ok_or_else(|| {
+                tonic::Status::not_found(format!("Action {} is not running", action_id))
+            })?;
+
+        info!(
+            "[Scheduler]: Cancelling action {} on agent {}",
+            action_id, agent_ip
+        );
+        agent_client::cancel_action(action_id, agent_ip).await?;
+        Ok(tonic::Response::new(proto::CancelActionResponse {}))
+    }
 }
 
 impl ControllerService {
@@ -160,19 +272,20 @@ impl ControllerService {
         &self,
         action_request: &proto::ActionRequest,
     ) -> Result<(proto::RunnerType, Option<String>), Error> {
-        let context = action_request
-            .context
-            .clone()
-            .ok_or_else(|| Error::GrpcRequestError(tonic::Status::invalid_argument("Context field is missing")))?;
+        let context = action_request.context.clone().ok_or_else(|| {
+            Error::GrpcRequestError(tonic::Status::invalid_argument("Context field is missing"))
+        })?;
 
         // Convert `context.r#type` (which is an `i32`) to a `RunnerType`
-        let runner_type = proto::RunnerType::try_from(context.r#type)
-            .map_err(|_| Error::GrpcRequestError(tonic::Status::invalid_argument("Invalid RunnerType")))?;
+        let runner_type = proto::RunnerType::try_from(context.r#type).map_err(|_| {
+            Error::GrpcRequestError(tonic::Status::invalid_argument("Invalid RunnerType"))
+        })?;
 
-        let container_image = context
-            .container_image
-            .clone()
-            .ok_or_else(|| Error::GrpcRequestError(tonic::Status::invalid_argument("ContainerImage field is missing")))?;
+        let container_image = context.container_image.clone().ok_or_else(|| {
+            Error::GrpcRequestError(tonic::Status::invalid_argument(
+                "ContainerImage field is missing",
+            ))
+        })?;
 
         Ok((runner_type, Some(container_image)))
     }
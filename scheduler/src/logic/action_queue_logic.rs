@@ -1,5 +1,6 @@
 //use crate::proto::controller as proto;
 use crate::proto::scheduler as proto;
+use std::collections::HashMap;
 
 /// A struct representing an action in the queue.
 /// The action has an ID, a score, and additional fields from the ActionRequest proto.
@@ -9,16 +10,46 @@ pub(crate) struct Action {
     context: proto::ExecutionContext,
     commands: Vec<String>,
     repo_url: String,
+    env: HashMap<String, String>,
+    timeout_seconds: Option<u64>,
+    artifacts: Vec<String>,
+    cache: Option<proto::Cache>,
+    services: Vec<proto::Service>,
+    shell: Vec<String>,
+    steps: Vec<proto::Step>,
+    checkout: Option<proto::Checkout>,
 }
 
 impl Action {
     /// Constructor
-    pub fn new(action_id: u32, context: proto::ExecutionContext, commands: Vec<String>, repo_url: String) -> Self {
+    #[allow(clippy::too_many_arguments)]
+    pub fn new(
+        action_id: u32,
+        context: proto::ExecutionContext,
+        commands: Vec<String>,
+        repo_url: String,
+        env: HashMap<String, String>,
+        timeout_seconds: Option<u64>,
+        artifacts: Vec<String>,
+        cache: Option<proto::Cache>,
+        services: Vec<proto::Service>,
+        shell: Vec<String>,
+        steps: Vec<proto::Step>,
+        checkout: Option<proto::Checkout>,
+    ) -> Self {
         Self {
             action_id,
             context,
             commands,
             repo_url,
+            env,
+            timeout_seconds,
+            artifacts,
+            cache,
+            services,
+            shell,
+            steps,
+            checkout,
         }
     }
 
@@ -55,6 +86,46 @@ impl Action {
         &self.repo_url
     }
 
+    /// Environment variables getter
+    pub(crate) fn get_env(&self) -> &HashMap<String, String> {
+        &self.env
+    }
+
+    /// Timeout getter
+    pub(crate) fn get_timeout_seconds(&self) -> Option<u64> {
+        self.timeout_seconds
+    }
+
+    /// Artifact paths getter
+    pub(crate) fn get_artifacts(&self) -> &[String] {
+        &self.artifacts
+    }
+
+    /// Cache getter
+    pub(crate) fn get_cache(&self) -> Option<&proto::Cache> {
+        self.cache.as_ref()
+    }
+
+    /// Services getter
+    pub(crate) fn get_services(&self) -> &[proto::Service] {
+        &self.services
+    }
+
+    /// Shell getter
+    pub(crate) fn get_shell(&self) -> &[String] {
+        &self.shell
+    }
+
+    /// Steps getter
+    pub(crate) fn get_steps(&self) -> &[proto::Step] {
+        &self.steps
+    }
+
+    /// Checkout getter
+    pub(crate) fn get_checkout(&self) -> Option<&proto::Checkout> {
+        self.checkout.as_ref()
+    }
+
     /// Action ID setter
     pub(crate) fn _set_action_id(&mut self, action_id: u32) {
         self.action_id = action_id;
@@ -85,6 +156,45 @@ impl Action {
         self.repo_url = repo_url;
     }
 
+    /// Environment variables setter
+    pub(crate) fn _set_env(&mut self, env: HashMap<String, String>) {
+        self.env = env;
+    }
+
+    /// Timeout setter
+    pub(crate) fn _set_timeout_seconds(&mut self, timeout_seconds: Option<u64>) {
+        self.timeout_seconds = timeout_seconds;
+    }
+
+    /// Artifact paths setter
+    pub(crate) fn _set_artifacts(&mut self, artifacts: Vec<String>) {
+        self.artifacts = artifacts;
+    }
+
+    /// Cache setter
+    pub(crate) fn _set_cache(&mut self, cache: Option<proto::Cache>) {
+        self.cache = cache;
+    }
+
+    /// Services setter
+    pub(crate) fn _set_services(&mut self, services: Vec<proto::Service>) {
+        self.services = services;
+    }
+
+    /// Shell setter
+    pub(crate) fn _set_shell(&mut self, shell: Vec<String>) {
+        self.shell = shell;
+    }
+
+    /// Steps setter
+    pub(crate) fn _set_steps(&mut self, steps: Vec<proto::Step>) {
+        self.steps = steps;
+    }
+
+    /// Checkout setter
+    pub(crate) fn _set_checkout(&mut self, checkout: Option<proto::Checkout>) {
+        self.checkout = checkout;
+    }
 }
 
 /// ActionsQueue is a collection of Actions stored in a vector.
@@ -95,8 +205,11 @@ pub struct ActionsQueue {
 
 impl ActionsQueue {
     /// Constructor
+    #[allow(clippy::too_many_arguments)]
     pub fn new() -> Self {
-        Self { actions: Vec::new() }
+        Self {
+            actions: Vec::new(),
+        }
     }
 
     /// Insert an Action into the Action Queue and sort the Queue by score.
@@ -122,5 +235,4 @@ impl ActionsQueue {
     pub fn is_empty(&self) -> bool {
         self.actions.is_empty()
     }
-    
 }
@@ -1,12 +1,38 @@
 // Include the generated code for the `scheduler` proto package
 pub mod scheduler {
     tonic::include_proto!("scheduler");
+
+    impl std::fmt::Debug for Credentials {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            super::fmt_credentials(f, &self.ssh_private_key, &self.username, &self.token)
+        }
+    }
 }
 
 // Include the generated code for the `actions` proto package
 pub mod actions {
     tonic::include_proto!("actions");
+
+    impl std::fmt::Debug for Credentials {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            super::fmt_credentials(f, &self.ssh_private_key, &self.username, &self.token)
+        }
+    }
+}
+
+/// Debug output of the credentials of a repository, without the key or token.
+fn fmt_credentials(
+    f: &mut std::fmt::Formatter<'_>,
+    ssh_private_key: &str,
+    username: &str,
+    token: &str,
+) -> std::fmt::Result {
+    let mask = |value: &str| if value.is_empty() { "" } else { "***" };
+    f.debug_struct("Credentials")
+        .field("ssh_private_key", &mask(ssh_private_key))
+        .field("username", &username)
+        .field("token", &mask(token))
+        .finish()
 }
 
-pub const FILE_DESCRIPTOR_SET: &[u8] =
-    tonic::include_file_descriptor_set!("scheduler_descriptor");
+pub const FILE_DESCRIPTOR_SET: &[u8] = tonic::include_file_descriptor_set!("scheduler_descriptor");
@@ -1,27 +1,30 @@
 //use scheduler::proto::agent as agent;
-use sealci_scheduler::proto::scheduler as agent;
 use agent::agent_server::AgentServer;
+use sealci_scheduler::proto::scheduler as agent;
 
 //use scheduler::proto::controller as controller;
-use sealci_scheduler::proto::scheduler as controller;
 use controller::controller_server::ControllerServer;
-use controller::{controller_client::ControllerClient, ActionRequest, ExecutionContext, RunnerType};
+use controller::{
+    controller_client::ControllerClient, ActionRequest, ExecutionContext, RunnerType,
+};
+use sealci_scheduler::proto::scheduler as controller;
 
-use sealci_scheduler::interfaces::server as server;
+use sealci_scheduler::interfaces::server;
 use server::agent_interface::AgentService;
 use server::controller_interface::ControllerService;
 
-use sealci_scheduler::logic as logic;
 use logic::agent_pool_logic::AgentPool;
+use sealci_scheduler::logic;
 // use logic::action_queue_logic::ActionsQueue;
 
-use tonic::transport::Server;
-use tonic::transport::Channel;
-use tonic::Request;
+use std::collections::HashMap;
 use std::error::Error;
 use std::sync::Arc;
 use tokio::sync::Mutex;
 use tokio::time::Duration;
+use tonic::transport::Channel;
+use tonic::transport::Server;
+use tonic::Request;
 
 #[tokio::test]
 async fn test_schedule_action() -> Result<(), Box<dyn Error>> {
@@ -56,8 +59,19 @@ async fn test_schedule_action() -> Result<(), Box<dyn Error>> {
             r#type: RunnerType::Docker.into(),
             container_image: Some("test_image".to_string()),
         }),
-        commands: vec![String::from("echo 'Salut les zagennntss!!!'"), String::from("shutdown now")],
+        commands: vec![
+            String::from("echo 'Salut les zagennntss!!!'"),
+            String::from("shutdown now"),
+        ],
         repo_url: String::from("sealci-repo-url"),
+        env: HashMap::from([(String::from("SEALCI"), String::from("true"))]),
+        timeout_seconds: Some(60),
+        artifacts: vec![String::from("target")],
+        cache: None,
+        services: vec![],
+        shell: vec![],
+        steps: vec![],
+        checkout: None,
     });
 
     let mut response_stream = client.schedule_action(request).await?.into_inner();
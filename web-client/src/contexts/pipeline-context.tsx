@@ -1,6 +1,6 @@
 import { createContext, useContext, ReactNode, useState, useEffect, useCallback } from 'react'
 import type { CreatePipeline, Pipeline } from '@/types'
-import { useGetPipelines } from '@/hooks/use-pipelines'
+import { useGetPipeline, useGetPipelines } from '@/hooks/use-pipelines'
 import { useParams } from 'react-router-dom'
 
 type PipelineContextType = {
@@ -23,7 +23,8 @@ const PipelineContext = createContext<PipelineContextType | undefined>(undefined
 
 export function PipelineProvider({ children }: { children: ReactNode }) {
   const { id } = useParams<{ id: string }>()
-  const { data: fetchedPipelines, isFetching, refetch } = useGetPipelines(true)
+  const { data: fetchedPipelines, isFetching, refetch } = useGetPipelines()
+  const { data: fetchedPipeline } = useGetPipeline(id !== undefined ? +id : undefined)
 
   const [pipelines, setPipelines] = useState<Pipeline[] | undefined>(fetchedPipelines)
   const [currentPipeline, setCurrentPipeline] = useState<Pipeline | undefined>(undefined)
@@ -41,11 +42,11 @@ export function PipelineProvider({ children }: { children: ReactNode }) {
 
   useEffect(() => {
     if (id !== undefined) {
-      setCurrentPipeline(getPipeline(+id))
+      setCurrentPipeline(fetchedPipeline ?? getPipeline(+id))
     } else {
       setCurrentPipeline(undefined)
     }
-  }, [pipelines, id, getPipeline])
+  }, [pipelines, id, getPipeline, fetchedPipeline])
 
   const values: PipelineContextType = {
     pipelines: pipelines,
@@ -1,10 +1,20 @@
-import { fetchPipelines } from '@/lib/api'
+import { fetchPipeline, fetchPipelines } from '@/lib/api'
 import { useQuery } from '@tanstack/react-query'
 
-export const useGetPipelines = (verbose: boolean) => {
+export const useGetPipelines = () => {
   const { data, error, isFetching, refetch } = useQuery({
     queryKey: ['pipelines'],
-    queryFn: () => fetchPipelines({ verbose }),
+    queryFn: () => fetchPipelines(),
+  })
+  return { data: data?.pipelines, error, isFetching, refetch }
+}
+
+// The list has no logs: the pipeline being shown is fetched on its own
+export const useGetPipeline = (id: number | undefined) => {
+  const { data, error, isFetching, refetch } = useQuery({
+    queryKey: ['pipelines', id],
+    queryFn: () => fetchPipeline({ verbose: true, id: id! }),
+    enabled: id !== undefined,
   })
   return { data, error, isFetching, refetch }
 }
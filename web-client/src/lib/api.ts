@@ -1,4 +1,4 @@
-import {CreateMonitor, CreatePipeline, Monitor, Pipeline} from '@/types'
+import {CreateMonitor, CreatePipeline, Monitor, Pipeline, PipelinePage} from '@/types'
 import ky from "ky";
 
 /////////////////////////////////////////////////////////////////////////
@@ -308,12 +308,16 @@ import ky from "ky";
 // }
 
 export const fetchPipelines = async ({
-  verbose,
+  cursor,
+  limit,
 }: {
-  verbose?: boolean
-} = {}): Promise<Pipeline[]> => {
-  const endpoint = verbose ? '/pipeline?verbose=true' : '/pipeline?verbose=false'
-  return await ky.get(import.meta.env.VITE_CONTROLLER_ENDPOINT + endpoint).json<Pipeline[]>()
+  cursor?: number
+  limit?: number
+} = {}): Promise<PipelinePage> => {
+  const searchParams = new URLSearchParams()
+  if (cursor !== undefined) searchParams.set('cursor', cursor.toString())
+  if (limit !== undefined) searchParams.set('limit', limit.toString())
+  return await ky.get(import.meta.env.VITE_CONTROLLER_ENDPOINT + '/pipeline', { searchParams }).json<PipelinePage>()
 }
 
 export const fetchPipeline = async ({ verbose, id }: { verbose: boolean; id: number }): Promise<Pipeline> => {
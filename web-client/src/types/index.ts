@@ -20,6 +20,11 @@ export interface Pipeline {
   actions: Action[]
 }
 
+export interface PipelinePage {
+  pipelines: Pipeline[]
+  next_cursor: number | null
+}
+
 export interface CreatePipeline {
   repo_url: string
   body: File